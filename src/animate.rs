@@ -0,0 +1,57 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use okhsl::{Okhsl, Rgb};
+
+use crate::context::Context;
+use crate::preview::blocks;
+use crate::{palette_hue, rgb_to_hex, term};
+
+const STEP: f32 = 2.0;
+const PER_ROW: usize = 6;
+
+// Rotates the palette through hue space, redrawing the swatches in place about
+// ten times a second, and returns the offset showing when a key was pressed.
+pub fn run(ctx: &mut Context, l: f32, s: f32, offset: f32, count: i32) -> f32 {
+    let stopped = term::with_keys(|tty| rotate(ctx, l, s, offset, count, tty));
+    stopped.unwrap_or_else(|| {
+        eprintln!("--animate needs a terminal to read keys from.");
+        offset
+    })
+}
+
+fn rotate(ctx: &mut Context, l: f32, s: f32, start: f32, count: i32, tty: &mut File) -> f32 {
+    let rows = (count as usize).div_ceil(PER_ROW);
+    let mut offset = start;
+    let mut stdout = io::stdout();
+
+    // Each frame goes out in one write; when stdout is gone the animation stops
+    // on the offset it showed last.
+    let mut frame = String::from("\x1b[?25l");
+    loop {
+        let colors: Vec<Rgb<u8>> = (0..count)
+            .map(|n| ctx.srgb(Okhsl { h: palette_hue(offset, n, count), s, l }))
+            .collect();
+
+        writeln!(frame, "Offset {offset:>3}°  (press any key to keep it)").unwrap();
+        for row in colors.chunks(PER_ROW) {
+            let rgbs: Vec<[u8; 3]> = row.iter().map(|rgb| [rgb.r, rgb.g, rgb.b]).collect();
+            let labels: Vec<String> =
+                row.iter().map(|rgb| format!("#{}", rgb_to_hex(*rgb))).collect();
+            frame.push_str(&blocks(&rgbs, &labels, ctx.bg));
+        }
+        if stdout.write_all(frame.as_bytes()).and_then(|()| stdout.flush()).is_err() {
+            break;
+        }
+
+        if matches!(tty.read(&mut [0; 16]), Ok(1..)) {
+            break;
+        }
+        frame = format!("\x1b[{}A", 1 + rows * 3);
+        offset = (offset + STEP) % 360.0;
+    }
+    let _ = writeln!(stdout, "\x1b[?25h").and_then(|()| stdout.flush());
+
+    offset
+}
@@ -0,0 +1,56 @@
+use crate::term;
+
+// Without truecolor support the 24-bit escapes show up as garbage or wildly
+// wrong colors on older terminals, so everything falls back to xterm-256.
+fn truecolor() -> bool {
+    term::caps().truecolor
+}
+
+pub fn fg(rgb: [u8; 3]) -> String {
+    if term::caps().colors == 0 {
+        String::new()
+    } else if truecolor() {
+        format!("\x1b[38;2;{};{};{}m", rgb[0], rgb[1], rgb[2])
+    } else {
+        format!("\x1b[38;5;{}m", xterm256(rgb))
+    }
+}
+
+pub fn bg(rgb: [u8; 3]) -> String {
+    if term::caps().colors == 0 {
+        String::new()
+    } else if truecolor() {
+        format!("\x1b[48;2;{};{};{}m", rgb[0], rgb[1], rgb[2])
+    } else {
+        format!("\x1b[48;5;{}m", xterm256(rgb))
+    }
+}
+
+// Nearest entry of the 6×6×6 cube or the 24-step gray ramp. The 16 system
+// colors are skipped since their values depend on the terminal's own theme.
+pub fn xterm256(rgb: [u8; 3]) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let nearest_level =
+        |c: u8| (0..6).min_by_key(|&i| (i32::from(LEVELS[i]) - i32::from(c)).abs()).unwrap_or(0);
+    let [ri, gi, bi] = rgb.map(nearest_level);
+    let cube = [LEVELS[ri], LEVELS[gi], LEVELS[bi]];
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    let avg = (u32::from(rgb[0]) + u32::from(rgb[1]) + u32::from(rgb[2])) / 3;
+    let gray_step = (avg.saturating_sub(3) / 10).min(23);
+    let gray_value = (8 + gray_step * 10) as u8;
+    let gray = [gray_value; 3];
+
+    if distance(rgb, gray) < distance(rgb, cube) {
+        (232 + gray_step) as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> i32 {
+    // Weighted toward green, roughly following perceived brightness.
+    let d = [0, 1, 2].map(|i| i32::from(a[i]) - i32::from(b[i]));
+    2 * d[0] * d[0] + 4 * d[1] * d[1] + 3 * d[2] * d[2]
+}
@@ -0,0 +1,164 @@
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::export::Options;
+use crate::log;
+use crate::palette::Palette;
+use crate::preview::{ansi_hues, foreground_for, mix};
+use crate::term;
+
+// The 16 ANSI slots plus default foreground, background and cursor derived
+// from a palette.
+pub struct Scheme {
+    pub background: [u8; 3],
+    pub foreground: [u8; 3],
+    pub cursor: [u8; 3],
+    pub ansi: [[u8; 3]; 16],
+    // Raised backgrounds such as panels and popups: the palette's own surfaces
+    // when it has them, otherwise mixed toward the foreground.
+    pub surface: [u8; 3],
+    pub surface_variant: [u8; 3],
+    // The generated colors in order, for targets that list them as they are.
+    pub palette: Vec<[u8; 3]>,
+    pub options: Options,
+}
+
+impl Scheme {
+    pub fn new(palette: &Palette) -> Self {
+        let bg = palette.bg;
+        let fg = foreground_for(bg);
+        let hues = ansi_hues(&palette.rgbs(), fg);
+
+        let mut ansi = [[0; 3]; 16];
+        ansi[0] = mix(bg, fg, 0.15);
+        ansi[7] = mix(fg, bg, 0.15);
+        ansi[8] = mix(bg, fg, 0.45);
+        ansi[15] = fg;
+        for (i, hue) in hues.iter().enumerate() {
+            ansi[i + 1] = *hue;
+            ansi[i + 9] = brighten(*hue);
+        }
+
+        let surface =
+            |i: usize, step| palette.surfaces.get(i).copied().unwrap_or(mix(bg, fg, step));
+        Self {
+            background: bg,
+            foreground: fg,
+            cursor: fg,
+            ansi,
+            surface: surface(0, 0.08),
+            surface_variant: surface(1, 0.16),
+            palette: palette.rgbs(),
+            options: Options::default(),
+        }
+    }
+
+    // The same scheme exported the way the run asked for.
+    pub const fn with_options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn sequences(&self) -> String {
+        let mut out = String::new();
+        for (slot, rgb) in self.ansi.iter().enumerate() {
+            write!(out, "\x1b]4;{slot};{}\x1b\\", spec(*rgb)).unwrap();
+        }
+        write!(out, "\x1b]10;{}\x1b\\", spec(self.foreground)).unwrap();
+        write!(out, "\x1b]11;{}\x1b\\", spec(self.background)).unwrap();
+        write!(out, "\x1b]12;{}\x1b\\", spec(self.cursor)).unwrap();
+        out
+    }
+}
+
+fn brighten(rgb: [u8; 3]) -> [u8; 3] {
+    let okhsl = Okhsl::from(Oklab::from(Rgb { r: rgb[0], g: rgb[1], b: rgb[2] }));
+    let bright = Okhsl { l: (okhsl.l + 0.1).min(0.95), ..okhsl }.to_srgb();
+    [bright.r, bright.g, bright.b]
+}
+
+fn spec(rgb: [u8; 3]) -> String {
+    format!("rgb:{:02x}/{:02x}/{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// Applies the scheme to the running terminal until a key is pressed. Terminals
+// that answer OSC 4/10/11 queries get their exact previous colors back; others
+// are reset to their configured defaults. When stdout is gone it does not wait
+// for the key.
+pub fn try_it(palette: &Palette) {
+    let restore = if term::caps().osc4 { saved_colors() } else { None }
+        .unwrap_or_else(|| String::from("\x1b]104\x1b\\\x1b]110\x1b\\\x1b]111\x1b\\"))
+        + "\x1b]112\x1b\\";
+
+    let mut stdout = io::stdout();
+    let applied = write!(
+        stdout,
+        "{}\nApplied to this terminal. Press any key to restore the previous colors...",
+        Scheme::new(palette).sequences()
+    )
+    .and_then(|()| stdout.flush());
+    match applied {
+        Ok(()) => term::read_key(),
+        Err(err) => log::warn(format!("Could not apply to this terminal: {err}")),
+    }
+
+    // Also after a failed write, in case part of the scheme got through.
+    let _ = writeln!(stdout, "{restore}").and_then(|()| stdout.flush());
+}
+
+// `colorize apply`: sets the scheme on this terminal for good and, with
+// `all_ttys`, on every other open pseudo-terminal too, like pywal does. A closed
+// stdout is reported and the other terminals are still set.
+pub fn apply(palette: &Palette, all_ttys: bool) {
+    let scheme = Scheme::new(palette);
+    let sequences = scheme.sequences();
+    let mut stdout = io::stdout();
+    if let Err(err) = stdout.write_all(sequences.as_bytes()).and_then(|()| stdout.flush()) {
+        log::warn(format!("Could not apply to this terminal: {err}"));
+    }
+    // Windows Terminal takes the sequences for the running session; the
+    // installed scheme is what keeps them for new tabs.
+    #[cfg(windows)]
+    crate::console::install_scheme(&scheme);
+    if !all_ttys {
+        return;
+    }
+
+    let mut applied = 0;
+    for entry in fs::read_dir("/dev/pts").into_iter().flatten().flatten() {
+        // Only the numbered terminals, not the ptmx multiplexer.
+        if !entry.file_name().to_string_lossy().chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        if let Ok(mut tty) = OpenOptions::new().write(true).open(entry.path())
+            && tty.write_all(sequences.as_bytes()).is_ok()
+        {
+            applied += 1;
+            log::debug(format!("Applied to {}", entry.path().display()));
+        }
+    }
+    log::info(format!("Applied to {applied} terminals."));
+}
+
+fn saved_colors() -> Option<String> {
+    let mut query = String::new();
+    for slot in 0..16 {
+        write!(query, "\x1b]4;{slot};?\x1b\\").unwrap();
+    }
+    query.push_str("\x1b]10;?\x1b\\\x1b]11;?\x1b\\\x1b[c");
+    let response = term::probe(&query)?;
+
+    let mut restore = String::new();
+    for slot in 0..16 {
+        let rgb = term::osc_color(&response, &format!("4;{slot};"))?;
+        write!(restore, "\x1b]4;{slot};{}\x1b\\", spec(rgb)).unwrap();
+    }
+    for code in [10, 11] {
+        let rgb = term::osc_color(&response, &format!("{code};"))?;
+        write!(restore, "\x1b]{code};{}\x1b\\", spec(rgb)).unwrap();
+    }
+    Some(restore)
+}
@@ -0,0 +1,82 @@
+use std::hint::black_box;
+use std::num::NonZeroU16;
+use std::time::{Duration, Instant};
+
+use okhsl::Okhsl;
+
+use crate::context::{Context, Thresholds};
+use crate::{Distinct, apca_contrast, gen_valid_combs, relative_luminance, wcag_contrast};
+
+const ITERATIONS: u32 = 1_000_000;
+// The sweep is timed for a six color palette, as for terminals.
+const SWEEP_COUNT: NonZeroU16 = NonZeroU16::new(6).unwrap();
+
+pub fn run(bg: [u8; 3]) {
+    let ctx = Context::new(bg);
+    let bg_lum = ctx.bg_lum;
+
+    let conversion = time(|| {
+        for i in 0..ITERATIONS {
+            let okhsl = Okhsl {
+                h: f64::from(i % 360) / 360.0,
+                s: (i % 101) as f32 / 100.0,
+                l: (i % 97) as f32 / 96.0,
+            };
+            black_box(black_box(okhsl).to_srgb());
+        }
+    });
+
+    let direct = time(|| {
+        for i in 0..ITERATIONS {
+            let fg = test_color(i);
+            let fg_lum = relative_luminance((
+                f32::from(fg[0]) / 255.0,
+                f32::from(fg[1]) / 255.0,
+                f32::from(fg[2]) / 255.0,
+            ));
+            black_box(wcag_contrast(bg_lum, black_box(fg_lum)));
+            black_box(apca_contrast(black_box(fg), bg));
+        }
+    });
+
+    let cached = time(|| {
+        for i in 0..ITERATIONS {
+            let fg = test_color(i);
+            black_box(ctx.wcag(black_box(fg)));
+            black_box(ctx.apca(black_box(fg)));
+        }
+    });
+
+    let mut valid = 0;
+    let sweep = time(|| {
+        valid = gen_valid_combs(bg, Thresholds::MINIMUM, SWEEP_COUNT, Distinct::default()).len()
+    });
+
+    let [r, g, b] = bg;
+    println!("\nBenchmark against #{r:02X}{g:02X}{b:02X}:");
+    println!("─────────────────────────────────────────────────────────────────");
+    print_throughput("okhsl → sRGB", conversion);
+    print_throughput("Contrast (direct)", direct);
+    print_throughput("Contrast (context)", cached);
+    println!(
+        "{:<24} {:>10.2} s  ({valid} valid combinations)",
+        "Valid-combination sweep",
+        sweep.as_secs_f64()
+    );
+}
+
+const fn test_color(i: u32) -> [u8; 3] {
+    [(i % 256) as u8, (i / 256 % 256) as u8, (i / 65_536 % 256) as u8]
+}
+
+fn time(f: impl FnOnce()) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn print_throughput(name: &str, elapsed: Duration) {
+    let per_op = elapsed.as_secs_f64() * 1e9 / f64::from(ITERATIONS);
+    let per_sec = f64::from(ITERATIONS) / elapsed.as_secs_f64() / 1e6;
+    println!("{name:<24} {per_op:>10.1} ns/op  {per_sec:>8.2} M/s");
+}
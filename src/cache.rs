@@ -0,0 +1,368 @@
+use std::fs;
+use std::io;
+use std::num::NonZeroU16;
+
+use crate::context::Thresholds;
+use crate::log;
+use crate::{Backend, Distinct, ValidCombination, sweep_on};
+
+const CACHE_FILE: &str = "valid_combs.bin";
+const MAGIC: &[u8; 4] = b"CLZ7";
+// Background channels, the minimum WCAG and APCA contrast, the color count, the
+// distances the colors keep (zero for none) and the backend that swept them.
+const KEY_LEN: usize = 28;
+
+// Every (lightness, saturation, offset) point of the sweep gets one bit.
+const CUBE_LEN: usize = 101 * 101 * 360;
+const WORDS: usize = CUBE_LEN.div_ceil(64);
+const SET_BYTES: usize = WORDS * 8;
+
+/// A set of (lightness, saturation, offset) points, one bit each.
+#[derive(Clone)]
+pub struct ValidSet {
+    bits: Vec<u64>,
+}
+
+impl Default for ValidSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValidSet {
+    /// The empty set.
+    pub fn new() -> Self {
+        Self { bits: vec![0; WORDS] }
+    }
+
+    /// Adds `combo` to the set.
+    pub fn insert(&mut self, combo: &ValidCombination) {
+        let idx = index(combo);
+        self.bits[idx / 64] |= 1 << (idx % 64);
+    }
+
+    /// Keeps only what is in `other` too.
+    pub fn intersect(&mut self, other: &Self) {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a &= b;
+        }
+    }
+
+    /// How many combinations are in the set.
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Whether no combination is in the set.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|w| *w == 0)
+    }
+
+    /// Every combination in the set, by lightness, then saturation, then offset.
+    pub fn combinations(&self) -> Vec<ValidCombination> {
+        let mut combinations = Vec::with_capacity(self.len());
+        for (w, &word) in self.bits.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let idx = w * 64 + word.trailing_zeros() as usize;
+                combinations.push(combination(idx));
+                word &= word - 1;
+            }
+        }
+        combinations
+    }
+
+    /// The set the shader's bit words stand for, in the same order.
+    #[cfg(feature = "gpu")]
+    pub const fn from_words(bits: Vec<u64>) -> Self {
+        Self { bits }
+    }
+
+    fn from_bytes(data: &[u8]) -> Self {
+        let bits = data
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+            .collect();
+        Self { bits }
+    }
+
+    fn to_bytes(&self, out: &mut Vec<u8>) {
+        for word in &self.bits {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+}
+
+fn index(combo: &ValidCombination) -> usize {
+    (usize::from(combo.lightness) * 101 + usize::from(combo.saturation)) * 360
+        + usize::from(combo.offset)
+}
+
+const fn combination(idx: usize) -> ValidCombination {
+    let offset = (idx % 360) as u16;
+    let ls = idx / 360;
+    ValidCombination { lightness: (ls / 101) as u8, saturation: (ls % 101) as u8, offset }
+}
+
+// One file holds the valid set of every background and threshold pair seen so
+// far. The header is an index of those keys, followed by one fixed-size bitset
+// per background in the same order, so sets for several surfaces can be ANDed
+// without regenerating.
+struct Cache {
+    entries: Vec<([u8; KEY_LEN], ValidSet)>,
+}
+
+impl Cache {
+    fn load() -> Self {
+        let mut entries = Vec::new();
+        let Ok(data) = fs::read(CACHE_FILE) else {
+            return Self { entries };
+        };
+        if data.len() < 8 || &data[0..4] != MAGIC {
+            return Self { entries };
+        }
+
+        let count = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let sets_start = 8 + count * KEY_LEN;
+        if data.len() != sets_start + count * SET_BYTES {
+            return Self { entries };
+        }
+
+        for i in 0..count {
+            let key = data[8 + i * KEY_LEN..8 + (i + 1) * KEY_LEN].try_into().unwrap();
+            let start = sets_start + i * SET_BYTES;
+            let set = ValidSet::from_bytes(&data[start..start + SET_BYTES]);
+            entries.push((key, set));
+        }
+
+        Self { entries }
+    }
+
+    fn save(&self) -> bool {
+        let mut data = Vec::with_capacity(8 + self.entries.len() * (KEY_LEN + SET_BYTES));
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (key, _) in &self.entries {
+            data.extend_from_slice(key);
+        }
+        for (_, set) in &self.entries {
+            set.to_bytes(&mut data);
+        }
+        fs::write(CACHE_FILE, data).is_ok()
+    }
+
+    fn get(&self, key: [u8; KEY_LEN]) -> Option<&ValidSet> {
+        self.entries.iter().find(|(k, _)| *k == key).map(|(_, set)| set)
+    }
+
+    // Replaces the set stored under `key`, if any, so a key is never kept twice.
+    fn insert(&mut self, key: [u8; KEY_LEN], set: ValidSet) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => *old = set,
+            None => self.entries.push((key, set)),
+        }
+    }
+
+    // The set for `bg`, from the entry of `backend` or, when that is the GPU, of
+    // the CPU it falls back to; otherwise swept and stored under the backend that
+    // ran. The flag tells whether the cache changed.
+    fn get_or_sweep(
+        &mut self,
+        bg: [u8; 3],
+        minimum: Thresholds,
+        count: NonZeroU16,
+        distinct: Distinct,
+        backend: Backend,
+        sweep: impl FnOnce() -> (ValidSet, Backend),
+    ) -> (ValidSet, bool) {
+        let [r, g, b] = bg;
+        let hex = format!("{r:02X}{g:02X}{b:02X}");
+        let backends: &[Backend] = match backend {
+            Backend::Gpu => &[Backend::Gpu, Backend::Cpu],
+            Backend::Cpu => &[Backend::Cpu],
+        };
+        for &backend in backends {
+            if let Some(set) = self.get(key(bg, minimum, count, distinct, backend)) {
+                log::debug(format!("Using the cached sweep for #{hex} from {CACHE_FILE}"));
+                return (set.clone(), false);
+            }
+        }
+
+        log::debug(format!("No cached sweep for #{hex} with these thresholds and {count} colors"));
+        let (set, ran) = sweep();
+        self.insert(key(bg, minimum, count, distinct, ran), set.clone());
+        (set, true)
+    }
+}
+
+/// One cached sweep: what it was run for, where it ran and how many
+/// combinations passed.
+pub struct Entry {
+    pub background: [u8; 3],
+    pub minimum: Thresholds,
+    pub count: i32,
+    pub distinct: Distinct,
+    pub backend: Backend,
+    pub valid: usize,
+}
+
+/// The sweeps in the cache in the working directory, oldest first.
+pub fn entries() -> Vec<Entry> {
+    Cache::load()
+        .entries
+        .iter()
+        .map(|(key, set)| {
+            let bytes = |at: usize| [key[at], key[at + 1], key[at + 2], key[at + 3]];
+            let f32_at = |at: usize| f32::from_le_bytes(bytes(at));
+            let distance = |at: usize| Some(f32_at(at)).filter(|d| *d > 0.0);
+            Entry {
+                background: [key[0], key[1], key[2]],
+                minimum: Thresholds {
+                    wcag: f32_at(3),
+                    apca: f64::from(f32_at(7)),
+                    apca_reverse: f64::from(f32_at(11)),
+                },
+                count: i32::from_le_bytes(bytes(15)),
+                distinct: Distinct { separation: distance(19), cvd: distance(23) },
+                backend: if key[27] == 1 { Backend::Gpu } else { Backend::Cpu },
+                valid: set.len(),
+            }
+        })
+        .collect()
+}
+
+/// Deletes the cache in the working directory. Returns false when there was
+/// none.
+///
+/// # Errors
+///
+/// When the file exists but cannot be removed.
+pub fn clear() -> io::Result<bool> {
+    match fs::remove_file(CACHE_FILE) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn key(
+    bg: [u8; 3],
+    minimum: Thresholds,
+    count: NonZeroU16,
+    distinct: Distinct,
+    backend: Backend,
+) -> [u8; KEY_LEN] {
+    let mut key = [0; KEY_LEN];
+    key[..3].copy_from_slice(&bg);
+    key[3..7].copy_from_slice(&minimum.wcag.to_le_bytes());
+    key[7..11].copy_from_slice(&(minimum.apca as f32).to_le_bytes());
+    key[11..15].copy_from_slice(&(minimum.apca_reverse as f32).to_le_bytes());
+    key[15..19].copy_from_slice(&i32::from(count.get()).to_le_bytes());
+    key[19..23].copy_from_slice(&distinct.separation.unwrap_or_default().to_le_bytes());
+    key[23..27].copy_from_slice(&distinct.cvd.unwrap_or_default().to_le_bytes());
+    key[27] = u8::from(backend == Backend::Gpu);
+    key
+}
+
+/// The combinations valid on every one of `backgrounds`, from the cache in the
+/// working directory or swept and added to it. GPU sweeps are not reused on the
+/// CPU; CPU sweeps are reused when the GPU is found, as its sweep can fall back.
+pub fn load_or_gen_combs(
+    backgrounds: &[[u8; 3]],
+    minimum: Thresholds,
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> Vec<ValidCombination> {
+    let backend = Backend::available();
+    let mut cache = Cache::load();
+    let mut dirty = false;
+    let mut result: Option<ValidSet> = None;
+
+    for &bg in backgrounds {
+        let (set, swept) = cache.get_or_sweep(bg, minimum, count, distinct, backend, || {
+            sweep_on(bg, minimum, count, distinct)
+        });
+        dirty |= swept;
+
+        match &mut result {
+            Some(acc) => acc.intersect(&set),
+            None => result = Some(set),
+        }
+    }
+
+    if dirty && cache.save() {
+        log::debug(format!("Cached {} sweeps to {CACHE_FILE}", cache.entries.len()));
+    }
+
+    let combinations = result.map(|set| set.combinations()).unwrap_or_default();
+    log::info(format!("Loaded {} valid combinations", combinations.len()));
+    combinations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_combination_round_trip() {
+        for idx in 0..CUBE_LEN {
+            assert_eq!(index(&combination(idx)), idx);
+        }
+        let last = combination(CUBE_LEN - 1);
+        assert_eq!((last.lightness, last.saturation, last.offset), (100, 100, 359));
+    }
+
+    #[test]
+    fn sets_keep_their_combinations() {
+        let mut set = ValidSet::new();
+        assert!(set.is_empty());
+        let combos = [(0, 0, 0), (60, 100, 180), (100, 100, 359)];
+        for (lightness, saturation, offset) in combos {
+            set.insert(&ValidCombination { lightness, saturation, offset });
+        }
+        assert_eq!(set.len(), 3);
+
+        let mut bytes = Vec::new();
+        set.to_bytes(&mut bytes);
+        assert_eq!(bytes.len(), SET_BYTES);
+        let read: Vec<_> = ValidSet::from_bytes(&bytes)
+            .combinations()
+            .iter()
+            .map(|c| (c.lightness, c.saturation, c.offset))
+            .collect();
+        assert_eq!(read, combos);
+
+        let mut other = ValidSet::new();
+        other.insert(&ValidCombination { lightness: 60, saturation: 100, offset: 180 });
+        set.intersect(&other);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn a_gpu_falling_back_to_the_cpu_is_cached_once() {
+        let mut cache = Cache { entries: Vec::new() };
+        let minimum = Thresholds { wcag: 4.5, apca: 60.0, apca_reverse: 45.0 };
+        let count = NonZeroU16::new(6).unwrap();
+        let mut sweeps = 0;
+        let swept: Vec<_> = (0..2)
+            .map(|_| {
+                let sweep = || {
+                    sweeps += 1;
+                    (ValidSet::new(), Backend::Cpu)
+                };
+                let bg = [0x1e, 0x1e, 0x2e];
+                cache.get_or_sweep(bg, minimum, count, Distinct::default(), Backend::Gpu, sweep).1
+            })
+            .collect();
+        assert_eq!(swept, [true, false]);
+        assert_eq!(sweeps, 1);
+        assert_eq!(cache.entries.len(), 1);
+
+        let key = key([0x1e, 0x1e, 0x2e], minimum, count, Distinct::default(), Backend::Cpu);
+        let mut set = ValidSet::new();
+        set.insert(&ValidCombination { lightness: 60, saturation: 100, offset: 0 });
+        cache.insert(key, set);
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.get(key).map(ValidSet::len), Some(1));
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs;
+use std::io::{self, Read};
+
+use crate::cli::{self, Flag, flag};
+use crate::context::{Context, Thresholds};
+use crate::json::Json;
+use crate::{ColorizeError, hex_to_rgb_u8};
+
+const CHECK_FLAGS: [Flag; 6] = [
+    flag("--bg", Some("-b"), Some("hex"), "background to check against"),
+    flag("--colors", None, Some("file"), "colors to check, one per line; - for stdin"),
+    flag("--min-wcag", None, Some("ratio"), "WCAG ratio every color has to reach (4.5)"),
+    flag("--min-apca", None, Some("Lc"), "APCA Lc every color has to reach (32)"),
+    flag("--min-apca-reverse", None, Some("Lc"), "APCA Lc for light text on dark"),
+    flag("--json", None, None, "print the results as JSON"),
+];
+
+// `colorize check --bg <hex> [--colors <file|->] [<hex>...]`: audits colors
+// someone else chose against one background, for CI. No swatches, one line
+// per color or `--json`, and the exit status is 1 when any color fails.
+pub fn run(args: &[String]) -> Result<(), ColorizeError> {
+    let mut bg = None;
+    let mut files = Vec::new();
+    let mut thresholds = Thresholds::MINIMUM;
+    let mut reverse = None;
+    let mut json = false;
+
+    let (options, operands) = cli::parse_operands(args, &CHECK_FLAGS)?;
+    for (flag, value) in options {
+        match flag {
+            "--bg" => bg = Some(hex_to_rgb_u8(value)?),
+            "--colors" => files.push(value),
+            "--min-wcag" => thresholds.wcag = cli::number(flag, value, 1.0..=21.0)?,
+            "--min-apca" => thresholds.apca = cli::number(flag, value, 0.0..=108.0)?,
+            "--min-apca-reverse" => reverse = Some(cli::number(flag, value, 0.0..=108.0)?),
+            "--json" => json = true,
+            _ => {}
+        }
+    }
+    let mut colors = operands
+        .into_iter()
+        .map(|color| Ok((String::new(), hex_to_rgb_u8(color)?)))
+        .collect::<Result<Vec<_>, ColorizeError>>()?;
+    thresholds.apca_reverse = reverse.unwrap_or(thresholds.apca);
+    let Some(bg) = bg else {
+        eprintln!("Usage: colorize check --bg <hex> [--colors <file|->] [<hex>...] [--json]");
+        std::process::exit(1);
+    };
+    for file in files {
+        colors.extend(read(file));
+    }
+    if colors.is_empty() {
+        eprintln!("No colors to check; pass them as arguments or with --colors <file>");
+        std::process::exit(1);
+    }
+
+    let ctx = Context::new(bg);
+    let results: Vec<Checked> = colors
+        .into_iter()
+        .map(|(name, fg)| {
+            let (wcag, apca) = (ctx.wcag(fg), ctx.apca(fg));
+            (name, hex(fg), wcag, apca, thresholds.passes(wcag, apca))
+        })
+        .collect();
+    let bg = hex(bg);
+    let passed = results.iter().filter(|result| result.4).count();
+
+    if json {
+        print_json(&bg, thresholds, &results);
+    } else {
+        for (name, hex, wcag, apca, pass) in &results {
+            let mark = if *pass { "pass" } else { "FAIL" };
+            let line = format!("{mark} #{hex}  WCAG {wcag:>5.2}  APCA {apca:>6.1}  {name}");
+            println!("{}", line.trim_end());
+        }
+        println!(
+            "{passed} of {} pass on #{bg} (WCAG {}, Lc {} / {} reversed)",
+            results.len(),
+            thresholds.wcag,
+            thresholds.apca,
+            thresholds.apca_reverse
+        );
+    }
+    if passed < results.len() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// A color's name, hex, WCAG ratio, APCA Lc and whether it passes.
+type Checked = (String, String, f32, f64, bool);
+
+fn print_json(bg: &str, thresholds: Thresholds, results: &[Checked]) {
+    let round = |n: f64, places: i32| (n * 10f64.powi(places)).round() / 10f64.powi(places);
+    let field = |key: &str, value| (key.to_string(), value);
+    let colors = results
+        .iter()
+        .map(|(name, hex, wcag, apca, pass)| {
+            Json::Object(vec![
+                field("name", Json::String(name.clone())),
+                field("hex", Json::String(format!("#{hex}"))),
+                field("wcag", Json::Number(round(f64::from(*wcag), 2))),
+                field("apca", Json::Number(round(*apca, 1))),
+                field("pass", Json::Bool(*pass)),
+            ])
+        })
+        .collect();
+    let passed = results.iter().filter(|result| result.4).count();
+    let report = Json::Object(vec![
+        field("background", Json::String(format!("#{bg}"))),
+        field("min_wcag", Json::Number(f64::from(thresholds.wcag))),
+        field("min_apca", Json::Number(thresholds.apca)),
+        field("min_apca_reverse", Json::Number(thresholds.apca_reverse)),
+        field("passed", Json::Number(passed as f64)),
+        field("failed", Json::Number((results.len() - passed) as f64)),
+        field("pass", Json::Bool(passed == results.len())),
+        field("colors", Json::Array(colors)),
+    ]);
+    println!("{report}");
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("{r:02X}{g:02X}{b:02X}")
+}
+
+// Named colors from a file, or stdin for `-`. Each line holds a color, after
+// a token name if there is one: `fff`, `#1e66f5`, `accent: #1e66f5;` or
+// `--red = "#d20f39"`. Blank lines and `//` or `# ` comments are skipped.
+fn read(file: &str) -> Vec<(String, [u8; 3])> {
+    let text = if file == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(file)
+    };
+    let text = text.unwrap_or_else(|err| {
+        eprintln!("Could not read {file}: {err}");
+        std::process::exit(1);
+    });
+
+    let mut colors = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") || line.starts_with("# ") {
+            continue;
+        }
+        let (name, color) = line.rsplit_once([':', '=', ' ', '\t']).unwrap_or(("", line));
+        let color = color.trim_matches([';', ',', '"', '\'']);
+        let name = name.trim().trim_end_matches([':', '=']).trim().trim_matches(['"', '\'']);
+        match hex_to_rgb_u8(color) {
+            Ok(rgb) => colors.push((name.to_string(), rgb)),
+            Err(err) => {
+                eprintln!("{file}:{}: {err}", n + 1);
+                std::process::exit(1);
+            }
+        }
+    }
+    colors
+}
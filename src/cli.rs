@@ -0,0 +1,394 @@
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::ColorizeError;
+
+// One option of a command, for parsing and for `--help`.
+#[derive(Clone, Copy)]
+pub struct Flag {
+    pub long: &'static str,
+    pub short: Option<&'static str>,
+    // The value's placeholder in the help, or None for a switch.
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+pub const fn flag(
+    long: &'static str,
+    short: Option<&'static str>,
+    value: Option<&'static str>,
+    help: &'static str,
+) -> Flag {
+    Flag { long, short, value, help }
+}
+
+// What the palette is made of and what it has to pass, for every command
+// that generates one.
+pub const PALETTE_FLAGS: [Flag; 17] = [
+    flag("--background", Some("-b"), Some("hex,..."), "background colors, or auto to ask"),
+    flag("--lightness", Some("-l"), Some("0-100"), "okhsl lightness of every color (60)"),
+    flag("--saturation", Some("-s"), Some("0-100"), "okhsl saturation of every color (100)"),
+    flag(
+        "--offset",
+        Some("-o"),
+        Some("0-360"),
+        "hue the first color starts at (0); 360 wraps to 0",
+    ),
+    flag("--count", Some("-c"), Some("1-360"), "number of colors (6)"),
+    flag("--lc", None, Some("Lc"), "solve each hue's lightness for this APCA contrast"),
+    flag("--roles", None, Some("role,..."), "generate each color into a role's APCA band"),
+    flag("--random", Some("-r"), None, "pick a random combination that passes"),
+    flag("--min-wcag", None, Some("ratio"), "WCAG ratio every color has to reach"),
+    flag("--min-apca", None, Some("Lc"), "APCA Lc every color has to reach"),
+    flag("--min-apca-reverse", None, Some("Lc"), "APCA Lc for light text on dark"),
+    flag("--profile", None, Some("name"), "design for low vision: low-contrast or cataract"),
+    flag("--min-separation", None, Some("ΔE"), "drop colors this close to an earlier one"),
+    flag("--sort", None, Some("order"), "order by hue, lightness, apca or role"),
+    flag("--auto-fix", None, None, "replace failing colors with the nearest passing ones"),
+    flag("--ui", None, None, "check as UI accents at the non-text thresholds"),
+    flag("--require-cvd-distinct", None, Some("ΔE"), "fail when colors collide under CVD"),
+];
+
+// What is done with the palette once made: previews, checks, reports and
+// where it is applied. For the commands that print the report.
+pub const REPORT_FLAGS: [Flag; 19] = [
+    flag("--preview", Some("-p"), Some("name,..."), "previews to render (text)"),
+    flag("--simulate", None, Some("name"), "show previews through a color vision deficiency"),
+    flag("--sample-file", None, Some("path"), "text for the text preview"),
+    flag("--sample-text", None, Some("text"), "text for the text preview"),
+    flag("--adjacent", None, Some("ratio"), "list neighbours below this luminance ratio"),
+    flag("--matrix", None, None, "contrast of every color on every other"),
+    flag("--badges", None, None, "use every color as a fill with text on it"),
+    flag("--robustness", None, None, "re-check on displays that drift from sRGB"),
+    flag("--metrics", None, Some("name[:min],..."), "compare under wcag, apca, weber, michelson"),
+    flag("--report", None, Some("format"), "write an audit as html, json or sarif"),
+    flag("--format", None, Some("format"), "print the palette as json, nix or home-manager"),
+    flag("--backend-compat", None, Some("wal"), "write pywal's cache"),
+    flag("--apply", None, None, "set the palette on the running terminal"),
+    flag("--all-ttys", None, None, "with apply, every open terminal too"),
+    flag("--try-it", None, None, "apply until a key is pressed"),
+    flag("--dbus", None, None, "announce the palette on the session bus"),
+    flag("--set-color-scheme", None, None, "with --dbus, switch the desktop's light or dark"),
+    flag("--animate", None, None, "rotate the offset until a key is pressed"),
+    flag("--analyze", Some("-a"), None, "analyze popular colorschemes"),
+];
+
+// Where exports go and how they write colors, for export, build and init.
+pub const EXPORT_FLAGS: [Flag; 3] = [
+    flag("--bundle", None, Some("group,..."), "config groups for export and build"),
+    flag("--out", None, Some("dir"), "where export writes (colorize-bundle)"),
+    flag("--xterm-256", None, None, "export escape codes as xterm-256 instead of 24-bit"),
+];
+
+// Diagnostics and modes every command takes. `--rpc` only counts as the
+// first argument, where main picks it up before anything is parsed.
+pub const OTHER_FLAGS: [Flag; 7] = [
+    flag("--preset", None, Some("name"), "start from a saved preset"),
+    flag("--pager", None, None, "page the output through $PAGER"),
+    flag("--rpc", None, None, "speak JSON-RPC on stdin and stdout"),
+    flag("--verbose", Some("-v"), None, "more diagnostics on stderr; -vv for every check"),
+    flag("--quiet", Some("-q"), None, "only warnings on stderr"),
+    flag("--log-file", None, Some("path"), "append diagnostics to a file too"),
+    flag("--help", Some("-h"), None, "print this help"),
+];
+
+pub const COMMANDS: [(&str, &str); 21] = [
+    ("generate", "the palette and its contrast report (the default)"),
+    ("random", "a random palette whose every color passes, like -r"),
+    ("analyze", "popular colorschemes against the thresholds and --metrics, like -a"),
+    ("material [image]", "Material You tonal palettes from an image or the wallpaper"),
+    ("apply", "generate and set the palette on the running terminal"),
+    ("export [target]", "one program's config on stdout, or many with --bundle"),
+    ("init <name>", "a theme project for the palette"),
+    ("build [dir]", "regenerate a theme from its palette.toml"),
+    ("map", "which lightness and saturation pass, as a heatmap"),
+    ("bench", "time conversion, contrast and the sweep"),
+    ("cache [list|clear]", "the cached sweeps of random mode"),
+    ("contrast <fg> <bg>", "everything about one pair"),
+    ("check --bg <hex>", "audit given colors for CI"),
+    ("compare <preset>...", "several palettes side by side"),
+    ("gallery", "browse and import shared palettes"),
+    ("last", "the colors of the latest run"),
+    ("history", "every generated palette, to show or rerun"),
+    ("term", "what was detected about the terminal"),
+    ("serve", "palettes over HTTP"),
+    ("nvim-server", "msgpack-RPC for a Neovim plugin"),
+    ("help", "print this help"),
+];
+
+pub fn help() {
+    println!("colorize: coherent palettes in okhsl, checked with WCAG 2 and APCA\n");
+    println!("Usage: colorize [command] [options]\n\nCommands:");
+    for (command, help) in &COMMANDS {
+        println!("  {command:<22}{help}");
+    }
+    let groups: [(&str, &[Flag]); 4] = [
+        ("Palette", &PALETTE_FLAGS),
+        ("Report (generate, random, apply, material)", &REPORT_FLAGS),
+        ("Export (export, build, init)", &EXPORT_FLAGS),
+        ("Other", &OTHER_FLAGS),
+    ];
+    for (heading, flags) in groups {
+        println!("\n{heading} options:");
+        for flag in flags {
+            let names = flag.short.map_or_else(
+                || format!("    {}", flag.long),
+                |short| format!("{short}, {}", flag.long),
+            );
+            let names = match flag.value {
+                Some(value) => format!("{names} <{value}>"),
+                None => names,
+            };
+            println!("  {names:<32}{}", flag.help);
+        }
+    }
+}
+
+// The options `command` takes: bench and map only read what their sweep
+// needs, analyze measures fixed schemes against the thresholds, export, build
+// and init write files instead of reporting.
+pub fn flags_for(command: Option<&str>) -> Vec<Flag> {
+    let own: Vec<Flag> = match command {
+        Some("bench") => {
+            PALETTE_FLAGS.iter().filter(|f| f.long == "--background").copied().collect()
+        }
+        Some("map") => {
+            let used = [
+                "--background",
+                "--count",
+                "--min-wcag",
+                "--min-apca",
+                "--min-apca-reverse",
+                "--profile",
+            ];
+            PALETTE_FLAGS.iter().filter(|f| used.contains(&f.long)).copied().collect()
+        }
+        Some("analyze") => {
+            let used = ["--min-wcag", "--min-apca", "--min-apca-reverse", "--profile"];
+            let report = ["--metrics", "--analyze"];
+            PALETTE_FLAGS
+                .iter()
+                .filter(|f| used.contains(&f.long))
+                .chain(REPORT_FLAGS.iter().filter(|f| report.contains(&f.long)))
+                .copied()
+                .collect()
+        }
+        Some("export" | "build" | "init") => [PALETTE_FLAGS.as_slice(), &EXPORT_FLAGS].concat(),
+        _ => [PALETTE_FLAGS.as_slice(), &REPORT_FLAGS].concat(),
+    };
+    let other = OTHER_FLAGS.iter().filter(|f| f.long != "--rpc").copied();
+    own.into_iter().chain(other).collect()
+}
+
+// The options of a command line as (long name, value) pairs, with short names
+// spelled out and switches given an empty value.
+pub type Options<'a> = Vec<(&'static str, &'a str)>;
+
+// The options of `args` for `command`. Unknown options, options of another
+// command, stray words and options missing their value are errors.
+pub fn parse<'a>(args: &'a [String], command: Option<&str>) -> Result<Options<'a>, ColorizeError> {
+    parse_with(args, &flags_for(command))
+}
+
+// Like `parse`, for a command with its own options.
+pub fn parse_with<'a>(args: &'a [String], flags: &[Flag]) -> Result<Options<'a>, ColorizeError> {
+    let (options, operands) = parse_operands(args, flags)?;
+    operands.first().map_or(Ok(options), |arg| {
+        Err(ColorizeError::UnknownArgument { arg: (*arg).to_string(), suggestion: None })
+    })
+}
+
+// Like `parse_with`, for a command that also takes words of its own, such as
+// colors: those are returned in order after the options.
+pub fn parse_operands<'a>(
+    args: &'a [String],
+    flags: &[Flag],
+) -> Result<(Options<'a>, Vec<&'a str>), ColorizeError> {
+    let mut options = Vec::new();
+    let mut operands = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if !arg.starts_with('-') {
+            operands.push(arg);
+            i += 1;
+            continue;
+        }
+        // -vv is -v twice for log::init.
+        let name = if arg == "-vv" { "-v" } else { arg };
+        let Some(flag) = flags.iter().find(|flag| flag.long == name || flag.short == Some(name))
+        else {
+            return Err(ColorizeError::UnknownArgument {
+                arg: arg.to_string(),
+                suggestion: suggest(arg, flags),
+            });
+        };
+        if flag.value.is_some() {
+            let Some(value) = args.get(i + 1) else {
+                return Err(ColorizeError::MissingValue { flag: arg.to_string() });
+            };
+            options.push((flag.long, value.as_str()));
+            i += 2;
+        } else {
+            options.push((flag.long, ""));
+            i += 1;
+        }
+    }
+    Ok((options, operands))
+}
+
+// The first of the words left after a command's own arguments, as an error.
+pub fn reject(rest: &[String]) -> Result<(), ColorizeError> {
+    rest.first().map_or(Ok(()), |arg| {
+        Err(ColorizeError::UnknownArgument { arg: arg.clone(), suggestion: None })
+    })
+}
+
+// A value that has to parse as a number within `range`.
+pub fn number<T>(flag: &str, value: &str, range: RangeInclusive<T>) -> Result<T, ColorizeError>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    value.parse().ok().filter(|n| range.contains(n)).ok_or_else(|| ColorizeError::InvalidValue {
+        flag: flag.to_string(),
+        value: value.to_string(),
+        expected: format!("expected a number from {} to {}", range.start(), range.end()),
+    })
+}
+
+// A value through a parser with its own message.
+pub fn parsed<T: FromStr<Err = String>>(flag: &str, value: &str) -> Result<T, ColorizeError> {
+    value.parse().map_err(|expected| ColorizeError::InvalidValue {
+        flag: flag.to_string(),
+        value: value.to_string(),
+        expected,
+    })
+}
+
+// The closest known option within two edits, for typos.
+fn suggest(arg: &str, flags: &[Flag]) -> Option<String> {
+    if !arg.starts_with('-') {
+        return None;
+    }
+    flags
+        .iter()
+        .map(|flag| (distance(arg, flag.long), flag.long))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, long)| long.to_string())
+}
+
+// Levenshtein distance.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parse_spells_out_short_names() {
+        let args = args("-b 1e1e2e --count 8 -vv --auto-fix");
+        assert_eq!(
+            parse(&args, None),
+            Ok(vec![
+                ("--background", "1e1e2e"),
+                ("--count", "8"),
+                ("--verbose", ""),
+                ("--auto-fix", "")
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_and_incomplete() {
+        assert_eq!(
+            parse(&args("--cuont 8"), None),
+            Err(ColorizeError::UnknownArgument {
+                arg: String::from("--cuont"),
+                suggestion: Some(String::from("--count")),
+            })
+        );
+        assert_eq!(
+            parse(&args("stray"), None),
+            Err(ColorizeError::UnknownArgument { arg: String::from("stray"), suggestion: None })
+        );
+        assert_eq!(
+            parse(&args("-l 60 --offset"), None),
+            Err(ColorizeError::MissingValue { flag: String::from("--offset") })
+        );
+    }
+
+    #[test]
+    fn parse_takes_only_the_command_options() {
+        assert!(parse(&args("--animate"), None).is_ok());
+        assert!(parse(&args("--animate"), Some("export")).is_err());
+        assert!(parse(&args("--xterm-256"), Some("export")).is_ok());
+        assert!(parse(&args("--xterm-256"), None).is_err());
+        assert!(parse(&args("-b 000 -c 8"), Some("map")).is_ok());
+        assert!(parse(&args("-l 50"), Some("bench")).is_err());
+        assert!(parse(&args("--rpc"), None).is_err());
+        assert!(parse(&args("--metrics weber --min-apca 60 -a"), Some("analyze")).is_ok());
+        assert!(parse(&args("--report json"), Some("analyze")).is_err());
+    }
+
+    #[test]
+    fn parse_with_takes_the_command_flags() {
+        let flags = [flag("--port", None, Some("port"), "")];
+        assert_eq!(parse_with(&args("--port 9000"), &flags), Ok(vec![("--port", "9000")]));
+        assert!(parse_with(&args("--count 8"), &flags).is_err());
+        assert_eq!(reject(&[]), Ok(()));
+        assert!(reject(&args("extra")).is_err());
+    }
+
+    #[test]
+    fn parse_operands_keeps_the_words() {
+        let flags = [flag("--bg", Some("-b"), Some("hex"), ""), flag("--json", None, None, "")];
+        assert_eq!(
+            parse_operands(&args("fff -b 000 #333 --json"), &flags),
+            Ok((vec![("--bg", "000"), ("--json", "")], vec!["fff", "#333"]))
+        );
+        assert!(parse_operands(&args("fff --jsno"), &flags).is_err());
+    }
+
+    #[test]
+    fn number_checks_the_range() {
+        assert_eq!(number("--count", "12", 1..=360), Ok(12));
+        assert_eq!(number("--lightness", "62.5", 0.0..=100.0), Ok(62.5));
+        assert_eq!(
+            number("--count", "0", 1..=360),
+            Err(ColorizeError::InvalidValue {
+                flag: String::from("--count"),
+                value: String::from("0"),
+                expected: String::from("expected a number from 1 to 360"),
+            })
+        );
+        assert!(number("--count", "six", 1..=360).is_err());
+    }
+
+    #[test]
+    fn suggest_only_close_options() {
+        assert_eq!(suggest("--lightnes", &flags_for(None)), Some(String::from("--lightness")));
+        assert_eq!(suggest("--background-color", &flags_for(None)), None);
+        assert_eq!(suggest("lightness", &flags_for(None)), None);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::ansi;
+use crate::config::{self, Config};
+use crate::grade;
+use crate::palette::Palette;
+use crate::serve;
+
+// `colorize compare <preset-or-file>...`: one row per palette, its swatches on
+// its background and its grade, so saved candidates can be weighed side by
+// side. A name is a preset; a path is a preset file or a theme directory.
+pub fn run(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("Usage: colorize compare <preset|file|theme>...");
+        std::process::exit(1);
+    }
+    let rows: Vec<(&String, Palette)> = args.iter().map(|arg| (arg, load(arg))).collect();
+
+    let name_width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let longest = rows.iter().map(|(_, palette)| palette.colors.len()).max().unwrap_or(0);
+    println!(
+        "{:<name_width$}  {:<swatches$}  grade  worst Lc  passing  closest ΔE",
+        "",
+        "",
+        swatches = 2 * longest
+    );
+    for (name, palette) in &rows {
+        let grade = grade::compute(palette, &palette.context(), &[]);
+        let mut swatches = ansi::bg(palette.bg);
+        for color in &palette.colors {
+            write!(swatches, "{}██", ansi::fg(color.rgb)).unwrap();
+        }
+        let padding = " ".repeat(2 * (longest - palette.colors.len()));
+        let closest = grade.cvd.map_or_else(String::new, |(deficiency, distance)| {
+            format!("{distance:.1} ({})", deficiency.name())
+        });
+        println!(
+            "{name:<name_width$}  {swatches} \x1b[0m {padding} {} {:>3}  {:>8.0}  {:>7}  {closest}",
+            grade.letter(),
+            grade.score,
+            grade.worst_lc,
+            format!("{}/{}", grade.passing, grade.checks)
+        );
+    }
+}
+
+// The palette a preset or a file describes, with the keys of palette.toml.
+fn load(arg: &str) -> Palette {
+    let path = Path::new(arg);
+    let file = if path.is_dir() {
+        path.join("palette.toml")
+    } else if path.is_file() {
+        path.to_path_buf()
+    } else {
+        let presets = config::dir().map(|dir| dir.join("presets"));
+        presets.map_or_else(|| path.to_path_buf(), |dir| dir.join(format!("{arg}.toml")))
+    };
+    let Some(config) = Config::read(&file) else {
+        eprintln!("No preset or palette file {arg}");
+        std::process::exit(1);
+    };
+
+    let background = config.get("background").unwrap_or_default();
+    let values = [
+        ("bg", background.split(',').next().unwrap_or_default().to_string()),
+        ("l", config.get("lightness").unwrap_or_else(|| String::from("60"))),
+        ("s", config.get("saturation").unwrap_or_else(|| String::from("100"))),
+        ("o", config.get("offset").unwrap_or_else(|| String::from("0"))),
+        ("c", config.get("count").unwrap_or_else(|| String::from("6"))),
+    ];
+    let query: HashMap<&str, &str> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    serve::from_query(&query).unwrap_or_else(|err| {
+        eprintln!("{arg}: {err}");
+        std::process::exit(1);
+    })
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{ColorizeError, cli, log};
+
+// Settings read from `$XDG_CONFIG_HOME/colorize/config.toml`. Only the subset of
+// TOML the settings need is understood: `key = value` lines, arrays of strings
+// (which may span lines), `[table]` headers that prefix the following keys as
+// `table.key`, and `#` comments. Command line flags take precedence over
+// anything set here.
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let text = path()
+            .and_then(|path| {
+                let text = fs::read_to_string(&path).ok()?;
+                log::debug(format!("Read settings from {}", path.display()));
+                Some(text)
+            })
+            .unwrap_or_default();
+        Self::parse(&text)
+    }
+
+    // Another file in the same format, such as a theme's `palette.toml`.
+    pub fn read(path: &Path) -> Option<Self> {
+        Some(Self::parse(&fs::read_to_string(path).ok()?))
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut values = HashMap::new();
+        let mut table = String::new();
+        // An array still waiting for its closing bracket.
+        let mut open: Option<(String, String)> = None;
+
+        for line in text.lines() {
+            let line = strip_comment(line).trim();
+            if let Some((key, mut value)) = open.take() {
+                value.push_str(line);
+                if line.ends_with(']') {
+                    values.insert(key, value);
+                } else {
+                    open = Some((key, value));
+                }
+            } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                table = format!("{}.", name.trim());
+            } else if let Some((key, value)) = line.split_once('=')
+                && value.trim().starts_with('[')
+                && !value.trim().ends_with(']')
+            {
+                open = Some((format!("{table}{}", key.trim()), value.trim().to_string()));
+            } else if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+                let value =
+                    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value);
+                values.insert(format!("{table}{}", key.trim()), value.to_string());
+            }
+        }
+
+        Self { values }
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    // A number within `range`, named by its key when it is not one.
+    pub fn number<T>(&self, key: &str, range: RangeInclusive<T>) -> Result<Option<T>, ColorizeError>
+    where
+        T: FromStr + PartialOrd + Display,
+    {
+        self.values.get(key).map(|value| cli::number(key, value, range)).transpose()
+    }
+
+    // A value through a parser with its own message, named by its key.
+    pub fn parsed<T: FromStr<Err = String>>(&self, key: &str) -> Result<Option<T>, ColorizeError> {
+        self.values.get(key).map(|value| cli::parsed(key, value)).transpose()
+    }
+
+    // The strings of an array such as `["a", "b"]`; empty when unset.
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        let Some(value) = self.values.get(key) else { return Vec::new() };
+        let mut items = Vec::new();
+        let mut item: Option<String> = None;
+        let mut chars = value.chars();
+        while let Some(ch) = chars.next() {
+            match (ch, item.as_mut()) {
+                ('"', None) => item = Some(String::new()),
+                ('"', Some(_)) => items.extend(item.take()),
+                ('\\', Some(item)) => item.extend(chars.next()),
+                (ch, Some(item)) => item.push(ch),
+                _ => {}
+            }
+        }
+        items
+    }
+}
+
+// `$XDG_CONFIG_HOME/colorize`, falling back to `~/.config/colorize`.
+pub fn dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(base.join("colorize"))
+}
+
+fn path() -> Option<PathBuf> {
+    Some(dir()?.join("config.toml"))
+}
+
+// Drops a trailing `# comment`, leaving `#` inside quoted strings alone. A
+// backslash in a string escapes the character after it.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_the_subset() {
+        let config = Config::parse(
+            r##"
+# settings
+background = "#1e1e2e" # the base
+count = 8
+export = ["kitty",
+  "alacritty",
+  "foot"]
+
+[colors]
+red = "ff0000"
+"##,
+        );
+        assert_eq!(config.get("background").as_deref(), Some("#1e1e2e"));
+        assert_eq!(config.number("count", 1..=360), Ok(Some(8)));
+        assert_eq!(config.get_list("export"), ["kitty", "alacritty", "foot"]);
+        assert_eq!(config.get("colors.red").as_deref(), Some("ff0000"));
+        assert_eq!(config.get("red"), None);
+        assert!(config.get_list("missing").is_empty());
+    }
+
+    #[test]
+    fn number_names_the_key() {
+        let config = Config::parse("min_wcag = 40\nmin_apca = \"abc\"\nmin_apca_reverse = 45");
+        let invalid = |key: &str, value: &str, expected: &str| ColorizeError::InvalidValue {
+            flag: key.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+        };
+        assert_eq!(
+            config.number("min_wcag", 1.0..=21.0),
+            Err(invalid("min_wcag", "40", "expected a number from 1 to 21"))
+        );
+        assert_eq!(
+            config.number("min_apca", 0.0..=108.0),
+            Err(invalid("min_apca", "abc", "expected a number from 0 to 108"))
+        );
+        assert_eq!(config.number("min_apca_reverse", 0.0..=108.0), Ok(Some(45.0)));
+        assert_eq!(config.number::<f64>("unset", 0.0..=108.0), Ok(None));
+    }
+
+    #[test]
+    fn get_list_unescapes() {
+        let config = Config::parse(r#"hooks = ["echo \"a\"", "b, c"]"#);
+        assert_eq!(config.get_list("hooks"), [r#"echo "a""#, "b, c"]);
+    }
+
+    #[test]
+    fn strip_comment_keeps_quoted_hashes() {
+        assert_eq!(strip_comment(r##"bg = "#000" # black"##), r##"bg = "#000" "##);
+        assert_eq!(strip_comment("# whole line"), "");
+    }
+
+    #[test]
+    fn strip_comment_skips_escaped_quotes() {
+        let line = r##"hook = "echo \"#1\" done" # run it"##;
+        assert_eq!(strip_comment(line), r##"hook = "echo \"#1\" done" "##);
+        assert_eq!(strip_comment(r##"hook = "a\\" # b"##), r##"hook = "a\\" "##);
+    }
+}
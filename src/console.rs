@@ -0,0 +1,94 @@
+use std::env;
+use std::ffi::c_void;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::apply::Scheme;
+use crate::export;
+use crate::log;
+
+// Windows consoles have neither terminfo nor stty, and only interpret escape
+// sequences once asked to. This is their side of term.rs.
+
+const STD_OUTPUT_HANDLE: u32 = (-11i32).cast_unsigned();
+const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+#[repr(C)]
+#[derive(Default)]
+struct Coord {
+    x: i16,
+    y: i16,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct SmallRect {
+    left: i16,
+    top: i16,
+    right: i16,
+    bottom: i16,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct ScreenBufferInfo {
+    size: Coord,
+    cursor: Coord,
+    attributes: u16,
+    window: SmallRect,
+    maximum: Coord,
+}
+
+#[link(name = "kernel32")]
+unsafe extern "system" {
+    fn GetStdHandle(std_handle: u32) -> *mut c_void;
+    fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+    fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+    fn GetConsoleScreenBufferInfo(console: *mut c_void, info: *mut ScreenBufferInfo) -> i32;
+}
+
+// Turns on VT processing for stdout. From Windows 10 1511 on the console then
+// understands the sequences, 24-bit color included; false on older consoles
+// and when stdout is not a console at all.
+pub fn enable_vt() -> bool {
+    unsafe {
+        let out = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        GetConsoleMode(out, &raw mut mode) != 0
+            && (mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(out, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0)
+    }
+}
+
+// Columns of the console window.
+pub fn width() -> Option<usize> {
+    let mut info = ScreenBufferInfo::default();
+    let ok = unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &raw mut info) };
+    let columns = info.window.right - info.window.left + 1;
+    (ok != 0).then(|| usize::try_from(columns).ok()).flatten()
+}
+
+// Installs the scheme as a Windows Terminal JSON fragment, which the terminal
+// loads on its next start and lists as "colorize" among the color schemes.
+// The running console already has the colors, so a failure only warns.
+pub fn install_scheme(scheme: &Scheme) {
+    let Some(dir) = env::var_os("LOCALAPPDATA").map(|base| {
+        PathBuf::from(base)
+            .join("Microsoft")
+            .join("Windows Terminal")
+            .join("Fragments")
+            .join("colorize")
+    }) else {
+        return;
+    };
+    let fragment =
+        format!("{{\n  \"schemes\": [\n{}\n  ]\n}}\n", export::windows_terminal(scheme, "    "));
+    let path = dir.join("colorize.json");
+    match fs::create_dir_all(&dir).and_then(|()| fs::write(&path, fragment)) {
+        Ok(()) => log::info(format!(
+            "Installed the Windows Terminal scheme \"colorize\" in {}",
+            dir.display()
+        )),
+        Err(err) => log::warn(format!("Could not write {}: {err}", path.display())),
+    }
+}
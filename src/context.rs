@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::{apca_lc, apca_luma, linearize, wcag_contrast};
+
+/// Contrast a color has to reach on the background. `MINIMUM` is what every hue
+/// of a generated palette must clear, `TARGET` is what the pass/fail badges ask for.
+/// `NON_TEXT` is for borders, icons and focus rings: WCAG 1.4.11 asks for 3:1,
+/// and APCA puts Lc 30 as the floor for semantic non-text elements.
+///
+/// APCA is signed: positive for dark text on a lighter background, negative for
+/// light text on a darker one. `apca` is required of the former, `apca_reverse`
+/// of the latter.
+#[derive(Clone, Copy)]
+pub struct Thresholds {
+    pub wcag: f32,
+    pub apca: f64,
+    pub apca_reverse: f64,
+}
+
+impl Thresholds {
+    /// WCAG 4.5:1 and Lc 32 either way.
+    pub const MINIMUM: Self = Self { wcag: 4.5, apca: 32.0, apca_reverse: 32.0 };
+    /// WCAG 7:1 and Lc 50 either way.
+    pub const TARGET: Self = Self { wcag: 7.0, apca: 50.0, apca_reverse: 50.0 };
+    /// WCAG 3:1 and Lc 30 either way.
+    pub const NON_TEXT: Self = Self { wcag: 3.0, apca: 30.0, apca_reverse: 30.0 };
+
+    /// The Lc magnitude asked of a pairing with this polarity.
+    pub const fn required(self, apca: f64) -> f64 {
+        if apca < 0.0 { self.apca_reverse } else { self.apca }
+    }
+
+    /// Whether an APCA Lc of either sign reaches what its polarity asks.
+    pub fn apca_passes(self, apca: f64) -> bool {
+        apca.abs() >= self.required(apca)
+    }
+
+    /// Whether a pairing reaches both the WCAG ratio and the APCA Lc.
+    pub fn passes(self, wcag: f32, apca: f64) -> bool {
+        wcag >= self.wcag && self.apca_passes(apca)
+    }
+}
+
+/// How a signed APCA Lc reads: light text on dark, or dark on light.
+pub const fn polarity(apca: f64) -> &'static str {
+    if apca < 0.0 { "light on dark" } else { "dark on light" }
+}
+
+/// Per-background state shared by generation, preview and analysis. Channel
+/// transfer curves are tabulated once, the background terms are computed once,
+/// and okhsl conversions are memoized since the same colors get converted for
+/// the swatches, the sample text and every export.
+pub struct Context {
+    pub bg: [u8; 3],
+    /// WCAG relative luminance of the background.
+    pub bg_lum: f32,
+    /// APCA luma of the background.
+    pub bg_luma: f64,
+    /// What every color has to reach.
+    pub minimum: Thresholds,
+    /// What the badges ask for.
+    pub target: Thresholds,
+    wcag_lut: [f32; 256],
+    apca_lut: [f64; 256],
+    conversions: HashMap<(u64, u32, u32), Rgb<u8>>,
+}
+
+impl Context {
+    /// The state for `bg`, checked against [`Thresholds::MINIMUM`] and
+    /// [`Thresholds::TARGET`].
+    pub fn new(bg: [u8; 3]) -> Self {
+        let mut wcag_lut = [0.0; 256];
+        let mut apca_lut = [0.0; 256];
+        for (v, (w, a)) in wcag_lut.iter_mut().zip(apca_lut.iter_mut()).enumerate() {
+            *w = linearize(v as f32 / 255.0);
+            *a = (v as f64 / 255.0).powf(2.4);
+        }
+
+        let mut ctx = Self {
+            bg,
+            bg_lum: 0.0,
+            bg_luma: 0.0,
+            minimum: Thresholds::MINIMUM,
+            target: Thresholds::TARGET,
+            wcag_lut,
+            apca_lut,
+            conversions: HashMap::new(),
+        };
+        ctx.bg_lum = ctx.luminance(bg);
+        ctx.bg_luma = ctx.luma(bg);
+        ctx
+    }
+
+    /// The same context checked against other thresholds.
+    pub const fn with_thresholds(mut self, minimum: Thresholds, target: Thresholds) -> Self {
+        self.minimum = minimum;
+        self.target = target;
+        self
+    }
+
+    /// `okhsl` as 8-bit sRGB, converted once per color.
+    pub fn srgb(&mut self, okhsl: Okhsl) -> Rgb<u8> {
+        let key = (okhsl.h.to_bits(), okhsl.s.to_bits(), okhsl.l.to_bits());
+        *self.conversions.entry(key).or_insert_with(|| okhsl.to_srgb())
+    }
+
+    /// WCAG relative luminance of `rgb`, from 0 to 1.
+    pub fn luminance(&self, rgb: [u8; 3]) -> f32 {
+        let [r, g, b] = rgb.map(|c| self.wcag_lut[usize::from(c)]);
+        0.072_2_f32.mul_add(b, 0.212_6_f32.mul_add(r, 0.715_2 * g))
+    }
+
+    fn luma(&self, rgb: [u8; 3]) -> f64 {
+        let [r, g, b] = rgb.map(|c| self.apca_lut[usize::from(c)]);
+        apca_luma(r, g, b)
+    }
+
+    /// WCAG 2 contrast ratio of `fg` on the background, from 1 to 21.
+    pub fn wcag(&self, fg: [u8; 3]) -> f32 {
+        wcag_contrast(self.bg_lum, self.luminance(fg))
+    }
+
+    /// APCA Lc of `fg` text on the background, signed by polarity.
+    pub fn apca(&self, fg: [u8; 3]) -> f64 {
+        apca_lc(self.luma(fg), self.bg_luma)
+    }
+
+    /// The okhsl lightness at which hue `h` and saturation `s` reach APCA Lc
+    /// `lc` on the background, by bisection. Text goes lighter than the
+    /// background when white reaches more contrast on it than black, and Lc
+    /// grows steadily with the distance from the background in that direction.
+    /// None when even white (or black) stays below `lc`.
+    pub fn lightness_for_lc(&self, h: f64, s: f32, lc: f64) -> Option<f32> {
+        let contrast = |l: f32| {
+            let rgb = Okhsl { h, s, l }.to_srgb();
+            self.apca([rgb.r, rgb.g, rgb.b]).abs()
+        };
+        let lighter = contrast(1.0) >= contrast(0.0);
+        let (mut near, mut far) = if lighter { (0.0, 1.0) } else { (1.0, 0.0) };
+        if contrast(far) < lc {
+            return None;
+        }
+
+        // Start from the background's side so the opposite polarity is skipped.
+        let reaches = |l: f32| {
+            let rgb = Okhsl { h, s, l }.to_srgb();
+            let apca = self.apca([rgb.r, rgb.g, rgb.b]);
+            if lighter { -apca >= lc } else { apca >= lc }
+        };
+        for _ in 0..24 {
+            let mid = f32::midpoint(near, far);
+            if reaches(mid) {
+                far = mid;
+            } else {
+                near = mid;
+            }
+        }
+        Some(far)
+    }
+}
+
+/// The closest okhsl color to `start` that reaches `goal` on every surface.
+/// Lightness may move either way; saturation is only ever reduced, and counts
+/// half as much as lightness since it changes the look less.
+pub fn nearest_passing(
+    start: Okhsl,
+    surfaces: &[&Context],
+    goal: impl Fn(&Context) -> Thresholds,
+) -> Option<Okhsl> {
+    // With the `gpu` feature every candidate is scored at once on the GPU.
+    #[cfg(feature = "gpu")]
+    let scored = crate::gpu::passing(start, surfaces, &goal);
+    #[cfg(not(feature = "gpu"))]
+    let scored: Option<Vec<bool>> = None;
+
+    let mut best: Option<(f32, Okhsl)> = None;
+    for l in 0..=100_usize {
+        for k in 0..=10_usize {
+            let candidate =
+                Okhsl { h: start.h, s: start.s * (1.0 - k as f32 / 10.0), l: l as f32 / 100.0 };
+            let cost = 0.5f32.mul_add(start.s - candidate.s, (candidate.l - start.l).abs());
+            if best.is_some_and(|(best_cost, _)| best_cost <= cost) {
+                continue;
+            }
+            let passes = scored.as_ref().map_or_else(
+                || {
+                    let rgb = candidate.to_srgb();
+                    let fg = [rgb.r, rgb.g, rgb.b];
+                    surfaces.iter().all(|ctx| goal(ctx).passes(ctx.wcag(fg), ctx.apca(fg)))
+                },
+                |scored| scored[l * 11 + k],
+            );
+            if passes {
+                best = Some((cost, candidate));
+            }
+        }
+    }
+    best.map(|(_, okhsl)| okhsl)
+}
+
+/// The okhsl color sRGB actually shows for `requested`, when clipping the
+/// channels into gamut (or rounding them to 8 bits) moved it by more than two
+/// points of lightness or saturation. Saturation is meaningless at black and
+/// white, so only lightness counts there.
+pub fn clipped(requested: Okhsl, rgb: Rgb<u8>) -> Option<Okhsl> {
+    let shown = Okhsl::from(Oklab::from(rgb));
+    let extreme = !(0.01..=0.99).contains(&requested.l);
+    let moved =
+        (shown.l - requested.l).abs() > 0.02 || (!extreme && (shown.s - requested.s).abs() > 0.02);
+    moved.then_some(shown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lightness_for_lc_reaches_the_contrast() {
+        for (bg, lc) in [([0, 0, 0], 60.0), ([0x1e, 0x1e, 0x2e], 45.0), ([255, 255, 255], 75.0)] {
+            let ctx = Context::new(bg);
+            for h in [0.0, 0.3, 0.7] {
+                let l = ctx.lightness_for_lc(h, 0.8, lc).unwrap();
+                let rgb = Okhsl { h, s: 0.8, l }.to_srgb();
+                let reached = ctx.apca([rgb.r, rgb.g, rgb.b]).abs();
+                assert!((lc..lc + 2.0).contains(&reached), "{bg:?} h={h}: Lc {reached}");
+            }
+        }
+    }
+
+    #[test]
+    fn lightness_for_lc_gives_up_out_of_reach() {
+        let ctx = Context::new([128, 128, 128]);
+        assert_eq!(ctx.lightness_for_lc(0.5, 0.5, 100.0), None);
+    }
+
+    #[test]
+    fn thresholds_follow_polarity() {
+        let thresholds = Thresholds { wcag: 4.5, apca: 60.0, apca_reverse: 45.0 };
+        assert!(thresholds.passes(5.0, -50.0));
+        assert!(!thresholds.passes(5.0, 50.0));
+        assert!(!thresholds.passes(4.0, 70.0));
+    }
+}
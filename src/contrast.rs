@@ -0,0 +1,60 @@
+use crate::cli;
+use crate::context::{Context, Thresholds, polarity};
+use crate::cvd::delta_e;
+use crate::{ColorizeError, colorize_output, hex_to_rgb_u8, wcag_levels};
+
+// What an APCA Lc magnitude is enough for, after the APCA bronze simple mode.
+fn apca_level(lc: f64) -> &'static str {
+    match lc.abs() {
+        90.0.. => "preferred for body text",
+        75.0.. => "body text",
+        60.0.. => "content text",
+        45.0.. => "large text, headlines",
+        30.0.. => "spot text, placeholders",
+        15.0.. => "non-text only",
+        _ => "invisible",
+    }
+}
+
+// `colorize contrast <fg> <bg>`: everything about one pair of colors.
+pub fn run(args: &[String]) -> Result<(), ColorizeError> {
+    if args.len() < 2 {
+        eprintln!("Usage: colorize contrast <fg> <bg>, e.g. colorize contrast d8d8d8 1e1e2e");
+        std::process::exit(1);
+    }
+    cli::reject(&args[2..])?;
+    let [fg_rgb, bg_rgb] = [("<fg>", &args[0]), ("<bg>", &args[1])].map(|(name, color)| {
+        hex_to_rgb_u8(color).unwrap_or_else(|err| {
+            eprintln!("Invalid {name} argument: {err}");
+            std::process::exit(1);
+        })
+    });
+    let [fg, bg] = [fg_rgb, bg_rgb].map(|[r, g, b]| format!("{r:02X}{g:02X}{b:02X}"));
+
+    let background = Context::new(bg_rgb);
+    let swapped = Context::new(fg_rgb);
+    let wcag = background.wcag(fg_rgb);
+    let apca = background.apca(fg_rgb);
+    let reversed = swapped.apca(bg_rgb);
+
+    println!("{} on #{bg}", colorize_output(fg_rgb, &format!("#{fg}"), bg_rgb));
+    println!("WCAG:      {wcag:.2}:1  {}", wcag_levels(wcag));
+    println!("APCA:      Lc {apca:.0}  {}, {}", polarity(apca), apca_level(apca));
+    println!(
+        "Reversed:  Lc {reversed:.0}  {} on #{fg}, {}",
+        colorize_output(bg_rgb, &format!("#{bg}"), fg_rgb),
+        apca_level(reversed)
+    );
+    println!("ΔE:        {:.1}", delta_e(fg_rgb, bg_rgb));
+
+    for (name, thresholds) in [("Minimum", Thresholds::MINIMUM), ("Target", Thresholds::TARGET)] {
+        let mark = if thresholds.passes(wcag, apca) { "✅" } else { "❌" };
+        println!(
+            "{:<11}{mark} WCAG {} and Lc {}",
+            format!("{name}:"),
+            thresholds.wcag,
+            thresholds.apca
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use okhsl::{Oklab, Rgb};
+
+use crate::context::Thresholds;
+use crate::linearize;
+
+/// A way of seeing colors that previews can simulate and profiles can design
+/// for.
+#[derive(Clone, Copy)]
+pub enum Deficiency {
+    Protan,
+    Deutan,
+    Tritan,
+    Achroma,
+    /// Low vision rather than color vision: reduced contrast sensitivity, and
+    /// the yellowed, scattering lens of a cataract.
+    LowContrast,
+    Cataract,
+}
+
+impl Deficiency {
+    /// Colors of equal lightness, as generated, all merge in achromatopsia, so
+    /// distinguishability is judged on the dichromacies.
+    pub const DICHROMACIES: [Self; 3] = [Self::Protan, Self::Deutan, Self::Tritan];
+
+    /// The name `--simulate` and `--profile` take.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Protan => "protan",
+            Self::Deutan => "deutan",
+            Self::Tritan => "tritan",
+            Self::Achroma => "achroma",
+            Self::LowContrast => "low-contrast",
+            Self::Cataract => "cataract",
+        }
+    }
+
+    /// Machado, Oliveira & Fernandes (2009) at full severity, applied to linear
+    /// RGB. Achromatopsia keeps only the relative luminance. Reduced contrast
+    /// sensitivity pulls everything halfway to mid gray; a cataract cuts blue and
+    /// some green and adds a veil of scattered light over the whole view.
+    pub fn simulate(self, rgb: [u8; 3]) -> [u8; 3] {
+        let linear = rgb.map(|c| linearize(f32::from(c) / 255.0));
+        let shown = match self {
+            Self::Protan => transform(PROTAN, linear),
+            Self::Deutan => transform(DEUTAN, linear),
+            Self::Tritan => transform(TRITAN, linear),
+            Self::Achroma => transform([[0.2126, 0.7152, 0.0722]; 3], linear),
+            Self::LowContrast => linear.map(|c| 0.5f32.mul_add(c, 0.09)),
+            Self::Cataract => {
+                let tint = [1.0, 0.92, 0.7];
+                [0, 1, 2].map(|i| linear[i].mul_add(tint[i], 0.1) / 1.1)
+            }
+        };
+        shown.map(|v| (delinearize(v.clamp(0.0, 1.0)) * 255.0).round() as u8)
+    }
+
+    /// The contrast a profile asks for to read as comfortably as the plain
+    /// thresholds do with typical vision. Color vision deficiencies leave
+    /// luminance contrast alone. WCAG's AAA step from 4.5 to 7 is the same
+    /// allowance for 20/80 vision; APCA suggests adding about Lc 15.
+    pub fn tighten(self, thresholds: Thresholds) -> Thresholds {
+        let (factor, lc) = match self {
+            Self::LowContrast => (1.5, 15.0),
+            Self::Cataract => (2.0, 25.0),
+            _ => return thresholds,
+        };
+        Thresholds {
+            wcag: (thresholds.wcag * factor).min(21.0),
+            apca: (thresholds.apca + lc).min(106.0),
+            apca_reverse: (thresholds.apca_reverse + lc).min(106.0),
+        }
+    }
+
+    /// The two colors that end up closest together with this deficiency, as
+    /// indices into `colors`, and their distance.
+    pub fn closest_pair(self, colors: &[[u8; 3]]) -> Option<(usize, usize, f32)> {
+        let seen: Vec<[u8; 3]> = colors.iter().map(|&rgb| self.simulate(rgb)).collect();
+        let mut closest: Option<(usize, usize, f32)> = None;
+        for i in 0..seen.len() {
+            for j in i + 1..seen.len() {
+                let distance = delta_e(seen[i], seen[j]);
+                if closest.is_none_or(|(_, _, d)| distance < d) {
+                    closest = Some((i, j, distance));
+                }
+            }
+        }
+        closest
+    }
+}
+
+impl FromStr for Deficiency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "protan" | "protanopia" => Ok(Self::Protan),
+            "deutan" | "deuteranopia" => Ok(Self::Deutan),
+            "tritan" | "tritanopia" => Ok(Self::Tritan),
+            "achroma" | "achromatopsia" => Ok(Self::Achroma),
+            "low-contrast" => Ok(Self::LowContrast),
+            "cataract" => Ok(Self::Cataract),
+            _ => Err(format!(
+                "unknown simulation '{s}' (protan, deutan, tritan, achroma, low-contrast, cataract)"
+            )),
+        }
+    }
+}
+
+const PROTAN: [[f32; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+const DEUTAN: [[f32; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+const TRITAN: [[f32; 3]; 3] = [
+    [1.255_528, -0.076_749, -0.178_779],
+    [-0.078_411, 0.930_809, 0.147_602],
+    [0.004_733, 0.691_367, 0.303_900],
+];
+
+fn transform(matrix: [[f32; 3]; 3], linear: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[2].mul_add(linear[2], row[0].mul_add(linear[0], row[1] * linear[1])))
+}
+
+/// The sRGB transfer curve, from linear light back to a 0 to 1 channel.
+pub fn delinearize(v: f32) -> f32 {
+    if v <= 0.003_130_8 { v * 12.92 } else { 1.055f32.mul_add(v.powf(1.0 / 2.4), -0.055) }
+}
+
+/// Euclidean Oklab distance times 100; about 2 is a just noticeable difference.
+pub fn delta_e(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let a = Oklab::from(Rgb { r: a[0], g: a[1], b: a[2] });
+    let b = Oklab::from(Rgb { r: b[0], g: b[1], b: b[2] });
+    let (dl, da, db) = (a.l - b.l, a.a - b.a, a.b - b.b);
+    db.mul_add(db, dl.mul_add(dl, da * da)).sqrt() * 100.0
+}
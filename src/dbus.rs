@@ -0,0 +1,46 @@
+use std::process::{Command, Stdio};
+
+use crate::log;
+use crate::palette::Palette;
+use crate::preview::is_dark;
+
+const PATH: &str = "/io/github/emrakyz/colorize";
+const SIGNAL: &str = "io.github.emrakyz.colorize.PaletteChanged";
+
+// Broadcasts `PaletteChanged(background, colors)` on the session bus through
+// `dbus-send`, so listeners can restyle without watching files. With
+// `color_scheme` the desktop's dark/light preference follows the background
+// too; the freedesktop settings portal reports the GNOME key to every app.
+pub fn announce(palette: &Palette, color_scheme: bool) {
+    let bg = palette.bg;
+    let [r, g, b] = bg;
+    let colors: Vec<String> =
+        palette.colors.iter().map(|color| format!("#{}", color.hex())).collect();
+    run(
+        "dbus-send",
+        &[
+            "--session",
+            "--type=signal",
+            PATH,
+            SIGNAL,
+            &format!("string:#{r:02X}{g:02X}{b:02X}"),
+            &format!("array:string:{}", colors.join(",")),
+        ],
+    );
+
+    if color_scheme {
+        // A light foreground is chosen for dark backgrounds.
+        let dark = is_dark(bg);
+        let preference = if dark { "prefer-dark" } else { "prefer-light" };
+        run("gsettings", &["set", "org.gnome.desktop.interface", "color-scheme", preference]);
+    }
+}
+
+fn run(program: &str, args: &[&str]) {
+    let status = Command::new(program).args(args).stdout(Stdio::null()).status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => log::warn(format!("{program} failed: {status}")),
+        Err(err) => log::warn(format!("Could not run {program}: {err}")),
+    }
+}
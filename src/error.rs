@@ -0,0 +1,54 @@
+use std::fmt;
+
+/// What went wrong with input from the command line, a file or a caller. The
+/// messages are meant to be shown as they are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColorizeError {
+    /// A hex color with a character that is not a hex digit.
+    HexDigit { input: String, digit: char },
+    /// A hex color with other than 3 or 6 digits.
+    HexLength { input: String, len: usize },
+    /// A flag at the end of the command line, without its value.
+    MissingValue { flag: String },
+    /// A flag or config setting whose value does not parse or is out of range.
+    InvalidValue { flag: String, value: String, expected: String },
+    /// An option or word the command does not take, with the closest option
+    /// when it looks like a typo.
+    UnknownArgument { arg: String, suggestion: Option<String> },
+    /// A command without the words it needs, with how to call it.
+    Usage { usage: String },
+    /// A file, program or socket that could not be used: what was being done
+    /// and why it failed.
+    Io { action: String, reason: String },
+}
+
+impl fmt::Display for ColorizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::HexDigit { input, digit } => {
+                write!(f, "`{input}` is not a hex color: `{digit}` is not a hex digit")
+            }
+            Self::HexLength { input, len } => write!(
+                f,
+                "`{input}` is not a hex color: expected 3 or 6 hex digits, got {len}, e.g. #fff or 1e1e2e"
+            ),
+            Self::MissingValue { flag } => write!(f, "{flag} needs a value"),
+            Self::InvalidValue { flag, value, expected } => {
+                write!(f, "Invalid {flag} argument `{value}`: {expected}")
+            }
+            Self::UnknownArgument { arg, suggestion: Some(suggestion) } => {
+                write!(
+                    f,
+                    "Unknown argument `{arg}`, did you mean {suggestion}? See colorize --help"
+                )
+            }
+            Self::UnknownArgument { arg, suggestion: None } => {
+                write!(f, "Unknown argument `{arg}`, see colorize --help")
+            }
+            Self::Usage { usage } => write!(f, "Usage: {usage}"),
+            Self::Io { action, reason } => write!(f, "Could not {action}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ColorizeError {}
@@ -0,0 +1,1770 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::ansi;
+use crate::apply::Scheme;
+use crate::context::Thresholds;
+use crate::grade::Grade;
+use crate::log;
+use crate::palette::Palette;
+use crate::preview::{is_dark, mix};
+use crate::{ColorizeError, apca_contrast, image, write_file};
+
+const PLIST_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE plist PUBLIC \
+                            \"-//Apple//DTD PLIST 1.0//EN\" \
+                            \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n";
+
+const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+// One generated config fragment: the group it belongs to, where it goes under
+// `~/.config` (or under the home directory for a `~/` path), how the program is
+// told to use it and how a running instance picks it up.
+struct Target {
+    name: &'static str,
+    group: &'static str,
+    install: &'static str,
+    hint: &'static str,
+    reload: &'static str,
+    render: fn(&Scheme) -> String,
+}
+
+const TARGETS: [Target; 35] = [
+    Target {
+        name: "kitty",
+        group: "terminal",
+        install: "kitty/colorize.conf",
+        hint: "add `include colorize.conf` to kitty.conf",
+        reload: "pkill -USR1 -x kitty",
+        render: kitty,
+    },
+    Target {
+        name: "alacritty",
+        group: "terminal",
+        install: "alacritty/colorize.toml",
+        hint: "add ~/.config/alacritty/colorize.toml to `general.import` in alacritty.toml",
+        reload: "",
+        render: alacritty,
+    },
+    Target {
+        name: "foot",
+        group: "terminal",
+        install: "foot/colorize.ini",
+        hint: "add `include=~/.config/foot/colorize.ini` to foot.ini",
+        reload: "",
+        render: foot,
+    },
+    Target {
+        name: "wezterm",
+        group: "terminal",
+        install: "wezterm/colors/colorize.toml",
+        hint: "set `config.color_scheme = 'colorize'` in wezterm.lua",
+        reload: "",
+        render: wezterm,
+    },
+    Target {
+        name: "ghostty",
+        group: "terminal",
+        install: "ghostty/themes/colorize",
+        hint: "add `theme = colorize` to ghostty's config",
+        reload: "",
+        render: ghostty,
+    },
+    Target {
+        name: "windows-terminal",
+        group: "terminal",
+        install: "colorize/windows-terminal.json",
+        hint: "paste it into the `schemes` list of Windows Terminal's settings.json",
+        reload: "",
+        render: windows_terminal_scheme,
+    },
+    Target {
+        name: "konsole",
+        group: "terminal",
+        install: "~/.local/share/konsole/colorize.colorscheme",
+        hint: "pick colorize under Appearance in Konsole's profile",
+        reload: "",
+        render: konsole,
+    },
+    Target {
+        name: "st",
+        group: "terminal",
+        install: "colorize/st-colors.h",
+        hint: "replace colorname and the default indices in st's config.h with it and rebuild st",
+        reload: "",
+        render: st,
+    },
+    Target {
+        name: "zellij",
+        group: "terminal",
+        install: "zellij/themes/colorize.kdl",
+        hint: "add `theme \"colorize\"` to zellij's config.kdl",
+        reload: "",
+        render: zellij,
+    },
+    Target {
+        name: "iterm2",
+        group: "terminal",
+        install: "~/Library/Application Support/iTerm2/DynamicProfiles/colorize.json",
+        hint: "pick the colorize profile in iTerm2's settings",
+        reload: "",
+        render: iterm2,
+    },
+    Target {
+        name: "itermcolors",
+        group: "terminal",
+        install: "colorize/colorize.itermcolors",
+        hint: "run `open ~/.config/colorize/colorize.itermcolors` to import it as a color preset",
+        reload: "",
+        render: itermcolors,
+    },
+    Target {
+        name: "terminal-app",
+        group: "terminal",
+        install: "~/Library/Application Support/colorize/colorize.terminal",
+        hint: "run `open ~/Library/Application\\ Support/colorize/colorize.terminal` to import it into \
+               Terminal.app",
+        reload: "",
+        render: terminal_app,
+    },
+    Target {
+        name: "starship",
+        group: "terminal",
+        install: "colorize/starship.toml",
+        hint: "copy its lines into starship.toml, which cannot include other files",
+        reload: "",
+        render: starship,
+    },
+    Target {
+        name: "dircolors",
+        group: "shell",
+        install: "colorize/dircolors",
+        hint: "add `eval \"$(dircolors ~/.config/colorize/dircolors)\"` to your shell's rc file",
+        reload: "",
+        render: dircolors,
+    },
+    Target {
+        name: "ls-colors",
+        group: "shell",
+        install: "colorize/ls-colors.sh",
+        hint: "add `. ~/.config/colorize/ls-colors.sh` to your shell's rc file",
+        reload: "",
+        render: ls_colors,
+    },
+    Target {
+        name: "nvim",
+        group: "editor",
+        install: "nvim/colors/colorize.lua",
+        hint: "run `:colorscheme colorize` in Neovim",
+        reload: "",
+        render: nvim,
+    },
+    Target {
+        name: "helix",
+        group: "editor",
+        install: "helix/themes/colorize.toml",
+        hint: "add `theme = \"colorize\"` to helix's config.toml",
+        reload: "pkill -USR1 -x hx",
+        render: helix,
+    },
+    Target {
+        name: "vscode",
+        group: "editor",
+        install: "colorize/colorize-color-theme.json",
+        hint: "list it under `contributes.themes` of a VS Code extension, or copy its `colors` into \
+               `workbench.colorCustomizations`",
+        reload: "",
+        render: vscode,
+    },
+    Target {
+        name: "jetbrains",
+        group: "editor",
+        install: "colorize/colorize.icls",
+        hint: "import it in a JetBrains IDE under Settings > Editor > Color Scheme > Import Scheme",
+        reload: "",
+        render: jetbrains,
+    },
+    Target {
+        name: "tmtheme",
+        group: "editor",
+        install: "bat/themes/colorize.tmTheme",
+        hint: "use `bat --theme colorize`, or `syntax-theme = colorize` in delta's git config",
+        reload: "bat cache --build",
+        render: tmtheme,
+    },
+    Target {
+        name: "gtk",
+        group: "desktop",
+        install: "gtk-4.0/colorize.css",
+        hint: "add `@import 'colorize.css';` to gtk.css in ~/.config/gtk-4.0 (and a copy in gtk-3.0)",
+        reload: "",
+        render: gtk,
+    },
+    Target {
+        name: "kde",
+        group: "desktop",
+        install: "~/.local/share/color-schemes/colorize.colors",
+        hint: "pick colorize in System Settings > Colors",
+        reload: "plasma-apply-colorscheme colorize",
+        render: kde,
+    },
+    Target {
+        name: "hyprland",
+        group: "wm",
+        install: "hypr/colorize.conf",
+        hint: "add `source = ~/.config/hypr/colorize.conf` to hyprland.conf",
+        reload: "hyprctl reload",
+        render: hyprland,
+    },
+    Target {
+        name: "sway",
+        group: "wm",
+        install: "sway/colorize",
+        hint: "add `include ~/.config/sway/colorize` to the sway config after its bar block",
+        reload: "swaymsg reload",
+        render: sway,
+    },
+    Target {
+        name: "i3",
+        group: "wm",
+        install: "i3/colorize",
+        hint: "add `include ~/.config/i3/colorize` to the i3 config (i3 4.20 or later) in place of \
+               its bar block",
+        reload: "i3-msg reload",
+        render: i3,
+    },
+    Target {
+        name: "waybar",
+        group: "bar",
+        install: "waybar/colorize.css",
+        hint: "add `@import \"colorize.css\";` to the top of waybar's style.css",
+        reload: "pkill -USR2 -x waybar",
+        render: waybar,
+    },
+    Target {
+        name: "polybar",
+        group: "bar",
+        install: "polybar/colorize.ini",
+        hint: "add `include-file = ~/.config/polybar/colorize.ini` to polybar's config.ini",
+        reload: "polybar-msg cmd restart",
+        render: polybar,
+    },
+    Target {
+        name: "rofi",
+        group: "launcher",
+        install: "rofi/colorize.rasi",
+        hint: "add `@import \"colorize.rasi\"` to config.rasi",
+        reload: "",
+        render: rofi,
+    },
+    Target {
+        name: "dunst",
+        group: "notifications",
+        install: "dunst/dunstrc.d/90-colorize.conf",
+        hint: "",
+        reload: "dunstctl reload",
+        render: dunst,
+    },
+    Target {
+        name: "mako",
+        group: "notifications",
+        install: "mako/colorize",
+        hint: "add `include=~/.config/mako/colorize` to mako's config",
+        reload: "makoctl reload",
+        render: mako,
+    },
+    Target {
+        name: "zathura",
+        group: "viewer",
+        install: "zathura/colorize",
+        hint: "add `include colorize` to zathurarc",
+        reload: "",
+        render: zathura,
+    },
+    Target {
+        name: "css",
+        group: "web",
+        install: "colorize/colorize.css",
+        hint: "add `@import \"colorize.css\";` to a stylesheet next to a copy of it",
+        reload: "",
+        render: css,
+    },
+    Target {
+        name: "scss",
+        group: "web",
+        install: "colorize/_colorize.scss",
+        hint: "add `@use \"colorize\" as *;` to a Sass file next to a copy of it",
+        reload: "",
+        render: scss,
+    },
+    Target {
+        name: "base16",
+        group: "schemes",
+        install: "colorize/base16-colorize.yaml",
+        hint: "point base16 templates (tinty, base16-builder) at ~/.config/colorize/base16-colorize.yaml",
+        reload: "",
+        render: base16,
+    },
+    Target {
+        name: "base24",
+        group: "schemes",
+        install: "colorize/base24-colorize.yaml",
+        hint: "point base24 templates at ~/.config/colorize/base24-colorize.yaml",
+        reload: "",
+        render: base24,
+    },
+];
+
+// The groups named in a `--bundle` list, or all of them for `all`. Anything
+// else is an error listing the known groups.
+pub fn groups(list: &str) -> Result<Vec<&'static str>, ColorizeError> {
+    let known: Vec<&'static str> = TARGETS.iter().fold(Vec::new(), |mut groups, target| {
+        if !groups.contains(&target.group) {
+            groups.push(target.group);
+        }
+        groups
+    });
+    if list == "all" {
+        return Ok(known);
+    }
+    list.split(',')
+        .map(|group| {
+            known.iter().copied().find(|known| *known == group.trim()).ok_or_else(|| {
+                ColorizeError::InvalidValue {
+                    flag: String::from("--bundle"),
+                    value: group.to_string(),
+                    expected: format!("expected all or one of {}", known.join(", ")),
+                }
+            })
+        })
+        .collect()
+}
+
+// How the run asked for its exports to be written.
+#[derive(Clone, Copy)]
+pub struct Options {
+    // `--xterm-256`: escape codes written into configs use the nearest
+    // xterm-256 color instead of 24-bit RGB, for terminals and tools without
+    // truecolor.
+    pub xterm256: bool,
+    // The thresholds of the run. Colors a target derives rather than takes
+    // from the palette, such as comments and selections, are kept above them.
+    pub minimum: Thresholds,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { xterm256: false, minimum: Thresholds::MINIMUM }
+    }
+}
+
+fn sgr(rgb: [u8; 3], options: Options) -> String {
+    if options.xterm256 {
+        format!("38;5;{}", ansi::xterm256(rgb))
+    } else {
+        format!("38;2;{};{};{}", rgb[0], rgb[1], rgb[2])
+    }
+}
+
+// `color` mixed towards `toward` in small steps until `passes` holds, or
+// `toward` itself.
+fn lift(color: [u8; 3], toward: [u8; 3], passes: impl Fn([u8; 3]) -> bool) -> [u8; 3] {
+    (0..=20)
+        .map(|step| mix(color, toward, step as f32 / 20.0))
+        .find(|c| passes(*c))
+        .unwrap_or(toward)
+}
+
+// `export <target>`: the one fragment on stdout, in place of the report, to
+// be redirected wherever it is wanted. An unknown name is an error listing
+// the targets.
+pub fn target(name: &str) -> Result<&'static str, ColorizeError> {
+    TARGETS.iter().find(|target| target.name == name).map(|target| target.name).ok_or_else(|| {
+        let known: Vec<&str> = TARGETS.iter().map(|target| target.name).collect();
+        ColorizeError::InvalidValue {
+            flag: String::from("export"),
+            value: name.to_string(),
+            expected: format!("expected one of {}", known.join(", ")),
+        }
+    })
+}
+
+// The target on stdout, under a comment with the palette's grade when the
+// format takes comments.
+pub fn print(name: &str, palette: &Palette, options: Options, grade: &Grade) {
+    if let Some(target) = TARGETS.iter().find(|target| target.name == name) {
+        let config = (target.render)(&Scheme::new(palette).with_options(options));
+        print!("{}{config}", grade_comment(name, grade).unwrap_or_default());
+    }
+}
+
+// The grade as one comment line in the syntax of target `name`. JSON and
+// property lists have no comments before their root, so they get none.
+fn grade_comment(name: &str, grade: &Grade) -> Option<String> {
+    let (open, close) = match name {
+        "windows-terminal" | "iterm2" | "vscode" | "itermcolors" | "terminal-app" | "tmtheme" => {
+            return None;
+        }
+        "nvim" => ("--", ""),
+        "zellij" | "scss" => ("//", ""),
+        "st" | "gtk" | "waybar" | "rofi" | "css" => ("/*", " */"),
+        "jetbrains" => ("<!--", " -->"),
+        _ => ("#", ""),
+    };
+    Some(format!(
+        "{open} colorize grade {} ({}/100): worst APCA Lc {:.0}, {} of {} checks pass{close}\n",
+        grade.letter(),
+        grade.score,
+        grade.worst_lc,
+        grade.passing,
+        grade.checks
+    ))
+}
+
+// Writes `dir/<group>/<program>/<file>` for every target in `groups`, plus
+// `dir/apply.sh` copying them into `~/.config` and reloading whatever is
+// running. Existing configs are never touched; the script lists the one
+// line each needs to pull its fragment in.
+pub fn bundle(groups: &[&str], dir: &Path, palette: &Palette, options: Options) {
+    let scheme = &Scheme::new(palette).with_options(options);
+    let mut script = String::from(
+        "#!/bin/sh\n# Generated by colorize: installs this bundle into ~/.config and reloads\n\
+         # the programs that are running.\nset -e\ncd \"$(dirname \"$0\")\"\n\
+         config=\"${XDG_CONFIG_HOME:-$HOME/.config}\"\n\n\
+         put() {\n    mkdir -p \"$(dirname \"$2\")\"\n    cp \"$1\" \"$2\"\n    echo \"$2\"\n}\n",
+    );
+    let mut written = 0;
+    for target in TARGETS.iter().filter(|target| groups.contains(&target.group)) {
+        let file = Path::new(target.group)
+            .join(target.name)
+            .join(Path::new(target.install).file_name().unwrap_or_default());
+        write_file(&dir.join(&file), (target.render)(scheme));
+        log::debug(format!("Wrote {}", dir.join(&file).display()));
+        written += 1;
+
+        let destination = target.install.strip_prefix("~/").map_or_else(
+            || format!("\"$config/{}\"", target.install),
+            |path| format!("\"$HOME/{path}\""),
+        );
+        writeln!(script, "\nput {} {destination}", file.display()).unwrap();
+        if !target.hint.is_empty() {
+            writeln!(script, "echo '    {}'", target.hint.replace('\'', "'\\''")).unwrap();
+        }
+        if !target.reload.is_empty() {
+            writeln!(script, "{} 2>/dev/null || true", target.reload).unwrap();
+        }
+    }
+
+    let apply = dir.join("apply.sh");
+    write_file(&apply, script);
+    #[cfg(unix)]
+    {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(err) = fs::set_permissions(&apply, fs::Permissions::from_mode(0o755)) {
+            log::warn(format!("Could not make {} executable: {err}", apply.display()));
+        }
+    }
+    log::info(format!(
+        "Wrote {written} config fragments to {}; run {} to install them",
+        dir.display(),
+        apply.display()
+    ));
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// Custom properties on `:root`: background, foreground and the generated
+// colors numbered from 1.
+fn css(scheme: &Scheme) -> String {
+    let mut out = String::from(":root {\n");
+    for (name, rgb) in variables(scheme) {
+        writeln!(out, "  --{name}: {};", hex(rgb)).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn scss(scheme: &Scheme) -> String {
+    let mut out = String::new();
+    for (name, rgb) in variables(scheme) {
+        writeln!(out, "${name}: {};", hex(rgb)).unwrap();
+    }
+    out
+}
+
+fn variables(scheme: &Scheme) -> Vec<(String, [u8; 3])> {
+    let mut variables = vec![
+        (String::from("background"), scheme.background),
+        (String::from("foreground"), scheme.foreground),
+    ];
+    for (i, rgb) in scheme.palette.iter().enumerate() {
+        variables.push((format!("color-{}", i + 1), *rgb));
+    }
+    variables
+}
+
+fn base16(scheme: &Scheme) -> String {
+    tinted(scheme, "base16")
+}
+
+fn base24(scheme: &Scheme) -> String {
+    tinted(scheme, "base24")
+}
+
+// A scheme in the tinted-theming YAML format. base00 to base07 are a ramp from
+// the background through the scheme's surfaces to past the foreground, base08 to base0F the accents in
+// the order the spec gives them: red, orange, yellow, green, cyan, blue,
+// magenta and brown, with orange and brown mixed from their neighbours.
+// Base24 adds two deeper backgrounds and the bright accents.
+fn tinted(scheme: &Scheme, system: &str) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let dark = is_dark(bg);
+    let (far, near) = if dark { ([255; 3], [0; 3]) } else { ([0; 3], [255; 3]) };
+    let orange = mix(c[1], c[3], 0.5);
+    let mut slots = vec![
+        bg,
+        scheme.surface,
+        scheme.surface_variant,
+        mix(bg, fg, 0.45),
+        mix(bg, fg, 0.7),
+        fg,
+        mix(fg, far, 0.4),
+        mix(fg, far, 0.75),
+        c[1],
+        orange,
+        c[3],
+        c[2],
+        c[6],
+        c[4],
+        c[5],
+        mix(orange, bg, 0.4),
+    ];
+    if system == "base24" {
+        slots.extend([mix(bg, near, 0.3), mix(bg, near, 0.6)]);
+        slots.extend([c[9], c[11], c[10], c[14], c[12], c[13]]);
+    }
+    let mut out = format!(
+        "system: \"{system}\"\nname: \"colorize\"\nauthor: \"colorize\"\nvariant: \"{}\"\npalette:\n",
+        if dark { "dark" } else { "light" }
+    );
+    for (i, rgb) in slots.iter().enumerate() {
+        writeln!(out, "  base{i:02X}: \"{}\"", hex(*rgb)).unwrap();
+    }
+    out
+}
+
+// Besides the palette, the cursor text, URLs, window borders and tabs are
+// derived from it so that nothing keeps kitty's defaults.
+fn kitty(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let mut out = String::new();
+    for (key, rgb) in [
+        ("background", bg),
+        ("foreground", fg),
+        ("cursor", scheme.cursor),
+        ("cursor_text_color", bg),
+        ("selection_background", c[8]),
+        ("selection_foreground", fg),
+        ("url_color", c[4]),
+        ("active_border_color", c[4]),
+        ("inactive_border_color", c[8]),
+        ("active_tab_background", c[4]),
+        ("active_tab_foreground", bg),
+        ("inactive_tab_background", c[0]),
+        ("inactive_tab_foreground", fg),
+        ("tab_bar_background", bg),
+    ] {
+        writeln!(out, "{key} {}", hex(rgb)).unwrap();
+    }
+    for (i, rgb) in c.iter().enumerate() {
+        writeln!(out, "color{i} {}", hex(*rgb)).unwrap();
+    }
+    out
+}
+
+fn alacritty(scheme: &Scheme) -> String {
+    let mut out = format!(
+        "[colors.primary]\nbackground = \"{}\"\nforeground = \"{}\"\n\n[colors.cursor]\n\
+         cursor = \"{}\"\ntext = \"{}\"\n\n[colors.selection]\nbackground = \"{}\"\ntext = \"{}\"\n",
+        hex(scheme.background),
+        hex(scheme.foreground),
+        hex(scheme.cursor),
+        hex(scheme.background),
+        hex(scheme.ansi[8]),
+        hex(scheme.foreground)
+    );
+    for (table, colors) in [("normal", &scheme.ansi[..8]), ("bright", &scheme.ansi[8..])] {
+        writeln!(out, "\n[colors.{table}]").unwrap();
+        for (name, rgb) in NAMES.iter().zip(colors) {
+            writeln!(out, "{name} = \"{}\"", hex(*rgb)).unwrap();
+        }
+    }
+    out
+}
+
+fn foot(scheme: &Scheme) -> String {
+    let bare = |rgb: [u8; 3]| hex(rgb)[1..].to_string();
+    // foot's cursor takes the text color under it first.
+    let mut out = format!(
+        "[colors]\nbackground={}\nforeground={}\ncursor={} {}\nselection-background={}\n\
+         selection-foreground={}\n",
+        bare(scheme.background),
+        bare(scheme.foreground),
+        bare(scheme.background),
+        bare(scheme.cursor),
+        bare(scheme.ansi[8]),
+        bare(scheme.foreground)
+    );
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        let slot = if i < 8 { format!("regular{i}") } else { format!("bright{}", i - 8) };
+        writeln!(out, "{slot}={}", bare(*rgb)).unwrap();
+    }
+    out
+}
+
+fn ghostty(scheme: &Scheme) -> String {
+    let mut out = String::new();
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "palette = {i}={}", hex(*rgb)).unwrap();
+    }
+    for (key, rgb) in [
+        ("background", scheme.background),
+        ("foreground", scheme.foreground),
+        ("cursor-color", scheme.cursor),
+        ("cursor-text", scheme.background),
+        ("selection-background", scheme.ansi[8]),
+        ("selection-foreground", scheme.foreground),
+    ] {
+        writeln!(out, "{key} = {}", hex(rgb)).unwrap();
+    }
+    out
+}
+
+// Every slot has Intense and Faint variants: the bright colors and a mix
+// towards the background.
+fn konsole(scheme: &Scheme) -> String {
+    let rgb = |[r, g, b]: [u8; 3]| format!("{r},{g},{b}");
+    let (bg, fg) = (scheme.background, scheme.foreground);
+    let mut slots =
+        vec![(String::from("Background"), bg, bg), (String::from("Foreground"), fg, fg)];
+    for i in 0..8 {
+        slots.push((format!("Color{i}"), scheme.ansi[i], scheme.ansi[i + 8]));
+    }
+    let mut out = String::new();
+    for (name, normal, intense) in slots {
+        writeln!(
+            out,
+            "[{name}]\nColor={}\n\n[{name}Intense]\nColor={}\n\n[{name}Faint]\nColor={}\n",
+            rgb(normal),
+            rgb(intense),
+            rgb(mix(normal, bg, 0.4))
+        )
+        .unwrap();
+    }
+    out.push_str("[General]\nDescription=colorize\nOpacity=1\n");
+    out
+}
+
+// st is configured in C: the colorname array with the 16 slots and cursor,
+// reverse cursor, foreground and background after 255, and the indices
+// that pick them.
+fn st(scheme: &Scheme) -> String {
+    let mut out = String::from("static const char *colorname[] = {\n");
+    for rgb in scheme.ansi {
+        writeln!(out, "\t\"{}\",", hex(rgb)).unwrap();
+    }
+    writeln!(
+        out,
+        "\n\t[255] = 0,\n\n\t\"{}\",\n\t\"{}\",\n\t\"{}\",\n\t\"{}\",\n}};\n\n\
+         unsigned int defaultfg = 258;\nunsigned int defaultbg = 259;\n\
+         unsigned int defaultcs = 256;\nstatic unsigned int defaultrcs = 257;",
+        hex(scheme.cursor),
+        hex(scheme.ansi[8]),
+        hex(scheme.foreground),
+        hex(scheme.background)
+    )
+    .unwrap();
+    out
+}
+
+// Zellij draws its frames and the selected tab in `bg`, so that is the
+// selection gray rather than the terminal's background.
+fn zellij(scheme: &Scheme) -> String {
+    let c = scheme.ansi;
+    let mut out = String::from("themes {\n    colorize {\n");
+    for (name, rgb) in [
+        ("fg", scheme.foreground),
+        ("bg", c[8]),
+        ("black", c[0]),
+        ("red", c[1]),
+        ("green", c[2]),
+        ("yellow", c[3]),
+        ("blue", c[4]),
+        ("magenta", c[5]),
+        ("cyan", c[6]),
+        ("white", c[7]),
+        ("orange", mix(c[1], c[3], 0.5)),
+    ] {
+        writeln!(out, "        {name} \"{}\"", hex(rgb)).unwrap();
+    }
+    out.push_str("    }\n}\n");
+    out
+}
+
+// A palette named after the ANSI slots, so styles read as usual, and a style
+// per common module. A module whose color misses the minimum APCA contrast on
+// the background is given the foreground instead.
+fn starship(scheme: &Scheme) -> String {
+    let mut out =
+        String::from("# Generated by colorize\npalette = \"colorize\"\n\n[palettes.colorize]\n");
+    writeln!(out, "background = \"{}\"", hex(scheme.background)).unwrap();
+    writeln!(out, "foreground = \"{}\"", hex(scheme.foreground)).unwrap();
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        let name = if i < 8 { NAMES[i].to_string() } else { format!("bright_{}", NAMES[i - 8]) };
+        writeln!(out, "{name} = \"{}\"", hex(*rgb)).unwrap();
+    }
+
+    let minimum = scheme.options.minimum;
+    let mut last = "";
+    for (module, key, slot, format) in [
+        ("directory", "style", 4, "bold {}"),
+        ("git_branch", "style", 5, "bold {}"),
+        ("git_status", "style", 1, "bold {}"),
+        ("cmd_duration", "style", 3, "bold {}"),
+        ("username", "style_user", 3, "bold {}"),
+        ("hostname", "style", 2, "bold {}"),
+        ("character", "success_symbol", 2, "[❯](bold {})"),
+        ("character", "error_symbol", 1, "[❯](bold {})"),
+    ] {
+        let rgb = scheme.ansi[slot];
+        let lc = apca_contrast(rgb, scheme.background);
+        let (name, note) = if minimum.apca_passes(lc) {
+            (NAMES[slot], format!("Lc {lc:.0}"))
+        } else {
+            ("foreground", format!("{} only reaches Lc {lc:.1}", NAMES[slot]))
+        };
+        if module != last {
+            write!(out, "\n[{module}]\n").unwrap();
+            last = module;
+        }
+        writeln!(out, "{key} = \"{}\" # {note}", format.replace("{}", name)).unwrap();
+    }
+    out
+}
+
+// File types and extensions with the SGR codes `ls` colors them with:
+// directories blue, links cyan, executables green, archives red, images
+// magenta, audio and video bright cyan, documents yellow.
+fn file_colors(scheme: &Scheme) -> Vec<(&'static str, String)> {
+    let c = scheme.ansi;
+    let sgr = |rgb| sgr(rgb, scheme.options);
+    let bold = |rgb| format!("01;{}", sgr(rgb));
+    let mut colors = vec![
+        ("DIR", bold(c[4])),
+        ("LINK", sgr(c[6])),
+        ("ORPHAN", bold(c[1])),
+        ("EXEC", bold(c[2])),
+        ("FIFO", sgr(c[3])),
+        ("SOCK", bold(c[5])),
+        ("BLK", bold(c[3])),
+        ("CHR", bold(c[3])),
+    ];
+    for (extensions, rgb) in [
+        (&["tar", "tgz", "gz", "xz", "zst", "bz2", "zip", "7z", "rar", "deb", "rpm"][..], c[1]),
+        (&["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "tiff", "ppm"][..], c[5]),
+        (&["mp3", "flac", "ogg", "opus", "wav", "mp4", "mkv", "webm", "mov", "avi"][..], c[14]),
+        (&["pdf", "epub", "md", "txt"][..], c[3]),
+    ] {
+        colors.extend(extensions.iter().map(|extension| (*extension, sgr(rgb))));
+    }
+    colors
+}
+
+// A database for `dircolors`, which turns it into LS_COLORS for the shell.
+fn dircolors(scheme: &Scheme) -> String {
+    let mut out = String::from("# Generated by colorize\nTERM *\n\n");
+    for (key, code) in file_colors(scheme) {
+        let key =
+            if key.chars().all(char::is_uppercase) { key.to_string() } else { format!(".{key}") };
+        writeln!(out, "{key} {code}").unwrap();
+    }
+    out
+}
+
+// The same as an LS_COLORS assignment, for systems without `dircolors`.
+fn ls_colors(scheme: &Scheme) -> String {
+    let short = |key| match key {
+        "DIR" => "di",
+        "LINK" => "ln",
+        "ORPHAN" => "or",
+        "EXEC" => "ex",
+        "FIFO" => "pi",
+        "SOCK" => "so",
+        "BLK" => "bd",
+        "CHR" => "cd",
+        _ => "",
+    };
+    let entries: Vec<String> = file_colors(scheme)
+        .into_iter()
+        .map(|(key, code)| match short(key) {
+            "" => format!("*.{key}={code}"),
+            short => format!("{short}={code}"),
+        })
+        .collect();
+    format!("export LS_COLORS='{}'\n", entries.join(":"))
+}
+
+// A color scheme file, which WezTerm finds by its metadata name in
+// `colors/` next to its config and reloads on change.
+fn wezterm(scheme: &Scheme) -> String {
+    let list = |colors: &[[u8; 3]]| {
+        let colors: Vec<String> = colors.iter().map(|rgb| format!("\"{}\"", hex(*rgb))).collect();
+        format!("[{}]", colors.join(", "))
+    };
+    format!(
+        "[colors]\nbackground = \"{}\"\nforeground = \"{}\"\ncursor_bg = \"{}\"\n\
+         cursor_fg = \"{}\"\ncursor_border = \"{}\"\nselection_bg = \"{}\"\n\
+         selection_fg = \"{}\"\nansi = {}\nbrights = {}\n\n[metadata]\nname = \"colorize\"\n",
+        hex(scheme.background),
+        hex(scheme.foreground),
+        hex(scheme.cursor),
+        hex(scheme.background),
+        hex(scheme.cursor),
+        hex(scheme.ansi[8]),
+        hex(scheme.foreground),
+        list(&scheme.ansi[..8]),
+        list(&scheme.ansi[8..])
+    )
+}
+
+// A dynamic profile, which iTerm2 picks up as soon as it appears in the
+// folder and updates on every change.
+fn iterm2(scheme: &Scheme) -> String {
+    let mut out = String::from(
+        "{\n  \"Profiles\": [\n    {\n      \"Name\": \"colorize\",\n      \"Guid\": \"colorize\"",
+    );
+    for (key, [r, g, b]) in iterm2_colors(scheme) {
+        write!(
+            out,
+            ",\n      \"{key}\": {{ \"Color Space\": \"sRGB\", \"Red Component\": {:.6}, \
+             \"Green Component\": {:.6}, \"Blue Component\": {:.6}, \"Alpha Component\": 1 }}",
+            f64::from(r) / 255.0,
+            f64::from(g) / 255.0,
+            f64::from(b) / 255.0
+        )
+        .unwrap();
+    }
+    out.push_str("\n    }\n  ]\n}\n");
+    out
+}
+
+// The same colors as a color preset, for Import in iTerm2's Colors settings.
+fn itermcolors(scheme: &Scheme) -> String {
+    let mut out = format!("{PLIST_HEADER}<dict>\n");
+    for (key, [r, g, b]) in iterm2_colors(scheme) {
+        writeln!(
+            out,
+            "\t<key>{key}</key>\n\t<dict>\n\t\t<key>Alpha Component</key>\n\t\t<real>1</real>\n\t\t\
+             <key>Blue Component</key>\n\t\t<real>{:.6}</real>\n\t\t<key>Color Space</key>\n\t\t\
+             <string>sRGB</string>\n\t\t<key>Green Component</key>\n\t\t<real>{:.6}</real>\n\t\t\
+             <key>Red Component</key>\n\t\t<real>{:.6}</real>\n\t</dict>",
+            f64::from(b) / 255.0,
+            f64::from(g) / 255.0,
+            f64::from(r) / 255.0
+        )
+        .unwrap();
+    }
+    out.push_str("</dict>\n</plist>\n");
+    out
+}
+
+fn iterm2_colors(scheme: &Scheme) -> Vec<(String, [u8; 3])> {
+    let mut colors = vec![
+        (String::from("Background Color"), scheme.background),
+        (String::from("Foreground Color"), scheme.foreground),
+        (String::from("Bold Color"), scheme.foreground),
+        (String::from("Cursor Color"), scheme.cursor),
+        (String::from("Cursor Text Color"), scheme.background),
+        (String::from("Selection Color"), scheme.ansi[8]),
+        (String::from("Selected Text Color"), scheme.foreground),
+    ];
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        colors.push((format!("Ansi {i} Color"), *rgb));
+    }
+    colors
+}
+
+// A Terminal.app settings file. Terminal stores every color as an archived
+// NSColor, so each one is a keyed archive of its own, base64 encoded inside
+// the outer plist.
+fn terminal_app(scheme: &Scheme) -> String {
+    let archive = |[r, g, b]: [u8; 3]| {
+        let components = format!(
+            "{:.6} {:.6} {:.6}\0",
+            f64::from(r) / 255.0,
+            f64::from(g) / 255.0,
+            f64::from(b) / 255.0
+        );
+        let color = format!(
+            "{PLIST_HEADER}<dict>\n<key>$archiver</key><string>NSKeyedArchiver</string>\n\
+             <key>$objects</key>\n<array>\n<string>$null</string>\n<dict>\n\
+             <key>$class</key><dict><key>CF$UID</key><integer>2</integer></dict>\n\
+             <key>NSColorSpace</key><integer>1</integer>\n<key>NSRGB</key><data>{}</data>\n\
+             </dict>\n<dict>\n<key>$classes</key><array><string>NSColor</string>\
+             <string>NSObject</string></array>\n<key>$classname</key><string>NSColor</string>\n\
+             </dict>\n</array>\n<key>$top</key><dict><key>root</key><dict><key>CF$UID</key>\
+             <integer>1</integer></dict></dict>\n<key>$version</key><integer>100000</integer>\n\
+             </dict>\n</plist>\n",
+            image::base64(components.as_bytes())
+        );
+        image::base64(color.as_bytes())
+    };
+
+    let mut colors = vec![
+        ("BackgroundColor", scheme.background),
+        ("TextColor", scheme.foreground),
+        ("TextBoldColor", scheme.foreground),
+        ("CursorColor", scheme.cursor),
+        ("SelectionColor", scheme.ansi[8]),
+    ];
+    let slots = ["Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White"];
+    let names: Vec<String> = (0..16)
+        .map(|i| format!("ANSI{}{}Color", if i < 8 { "" } else { "Bright" }, slots[i % 8]))
+        .collect();
+    colors.extend(names.iter().map(String::as_str).zip(scheme.ansi));
+
+    let mut out = format!(
+        "{PLIST_HEADER}<dict>\n\t<key>name</key>\n\t<string>colorize</string>\n\t<key>type</key>\n\t\
+         <string>Window Settings</string>\n\t<key>ProfileCurrentVersion</key>\n\t<real>2.07</real>\n"
+    );
+    for (key, rgb) in colors {
+        writeln!(out, "\t<key>{key}</key>\n\t<data>{}</data>", archive(rgb)).unwrap();
+    }
+    out.push_str("</dict>\n</plist>\n");
+    out
+}
+
+// A small colorscheme: the terminal palette for `:terminal` and the common
+// highlight groups on top of it.
+fn nvim(scheme: &Scheme) -> String {
+    let mut out = String::from(
+        "-- Generated by colorize\nvim.cmd('highlight clear')\nvim.g.colors_name = 'colorize'\n\
+         vim.o.termguicolors = true\n\n",
+    );
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "vim.g.terminal_color_{i} = '{}'", hex(*color)).unwrap();
+    }
+    out.push('\n');
+    for (group, fg, bg) in highlights(scheme) {
+        let fields: Vec<String> = [("fg", fg), ("bg", bg)]
+            .iter()
+            .filter_map(|(key, color)| Some(format!("{key} = '{}'", hex((*color)?))))
+            .collect();
+        writeln!(out, "vim.api.nvim_set_hl(0, '{group}', {{ {} }})", fields.join(", ")).unwrap();
+    }
+    out
+}
+
+// A color theme with the workbench, the integrated terminal and token colors,
+// comments and selections kept above the minimum contrast as for Helix.
+fn vscode(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let mut colors = vec![
+        (String::from("editor.background"), bg),
+        (String::from("editor.foreground"), fg),
+        (String::from("editorCursor.foreground"), scheme.cursor),
+        (String::from("editor.selectionBackground"), selection(scheme)),
+        (String::from("editor.lineHighlightBackground"), c[0]),
+        (String::from("editorLineNumber.foreground"), comment(scheme)),
+        (String::from("editorLineNumber.activeForeground"), c[3]),
+        (String::from("editorError.foreground"), c[1]),
+        (String::from("editorWarning.foreground"), c[3]),
+        (String::from("editorInfo.foreground"), c[4]),
+        (String::from("sideBar.background"), bg),
+        (String::from("sideBar.foreground"), fg),
+        (String::from("activityBar.background"), bg),
+        (String::from("activityBar.foreground"), fg),
+        (String::from("titleBar.activeBackground"), bg),
+        (String::from("titleBar.activeForeground"), fg),
+        (String::from("tab.activeBackground"), bg),
+        (String::from("tab.inactiveBackground"), c[0]),
+        (String::from("statusBar.background"), c[0]),
+        (String::from("statusBar.foreground"), fg),
+        (String::from("focusBorder"), c[4]),
+        (String::from("button.background"), c[4]),
+        (String::from("button.foreground"), bg),
+        (String::from("errorForeground"), c[1]),
+        (String::from("terminal.background"), bg),
+        (String::from("terminal.foreground"), fg),
+        (String::from("terminalCursor.foreground"), scheme.cursor),
+    ];
+    for (i, rgb) in c.iter().enumerate() {
+        let name = NAMES[i % 8];
+        let name = format!("{}{}", name[..1].to_uppercase(), &name[1..]);
+        let bright = if i < 8 { "" } else { "Bright" };
+        colors.push((format!("terminal.ansi{bright}{name}"), *rgb));
+    }
+    let colors: Vec<String> =
+        colors.iter().map(|(key, rgb)| format!("    \"{key}\": \"{}\"", hex(*rgb))).collect();
+
+    let tokens: Vec<String> = [
+        ("comment", comment(scheme), "italic"),
+        ("string", c[2], ""),
+        ("constant.numeric, constant.language, constant.character", c[3], ""),
+        ("keyword, storage", c[5], ""),
+        ("entity.name.function, support.function", c[4], ""),
+        ("entity.name.type, entity.name.class, support.type, storage.type", c[3], ""),
+        ("variable.parameter, variable.other.property", c[6], ""),
+        ("entity.name.tag", c[1], ""),
+        ("entity.other.attribute-name", c[6], ""),
+        ("invalid", c[1], ""),
+    ]
+    .iter()
+    .map(|(scope, rgb, style)| {
+        let style =
+            if style.is_empty() { String::new() } else { format!(", \"fontStyle\": \"{style}\"") };
+        format!(
+            "    {{ \"scope\": \"{scope}\", \"settings\": {{ \"foreground\": \"{}\"{style} }} }}",
+            hex(*rgb)
+        )
+    })
+    .collect();
+
+    let kind = if is_dark(bg) { "dark" } else { "light" };
+    format!(
+        "{{\n  \"name\": \"colorize\",\n  \"type\": \"{kind}\",\n  \"colors\": {{\n{}\n  }},\n  \
+         \"tokenColors\": [\n{}\n  ]\n}}\n",
+        colors.join(",\n"),
+        tokens.join(",\n")
+    )
+}
+
+// An editor color scheme for IntelliJ and the other JetBrains IDEs, on top of
+// their bundled dark or light scheme for everything not set here.
+fn jetbrains(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let bare = |rgb: [u8; 3]| hex(rgb)[1..].to_string();
+    let parent = if is_dark(bg) { "Darcula" } else { "Default" };
+    let mut out = format!(
+        "<scheme name=\"colorize\" version=\"142\" parent_scheme=\"{parent}\">\n  <colors>\n"
+    );
+    for (name, rgb) in [
+        ("CARET_COLOR", scheme.cursor),
+        ("CARET_ROW_COLOR", c[0]),
+        ("LINE_NUMBERS_COLOR", comment(scheme)),
+        ("SELECTION_BACKGROUND", selection(scheme)),
+        ("SELECTION_FOREGROUND", fg),
+        ("CONSOLE_BACKGROUND_KEY", bg),
+    ] {
+        writeln!(out, "    <option name=\"{name}\" value=\"{}\" />", bare(rgb)).unwrap();
+    }
+    out.push_str("  </colors>\n  <attributes>\n");
+
+    // Font type 2 is italic.
+    let mut attributes = vec![
+        ("TEXT", fg, Some(bg), 0),
+        ("DEFAULT_LINE_COMMENT", comment(scheme), None, 2),
+        ("DEFAULT_BLOCK_COMMENT", comment(scheme), None, 2),
+        ("DEFAULT_DOC_COMMENT", comment(scheme), None, 2),
+        ("DEFAULT_STRING", c[2], None, 0),
+        ("DEFAULT_KEYWORD", c[5], None, 0),
+        ("DEFAULT_NUMBER", c[3], None, 0),
+        ("DEFAULT_CONSTANT", c[3], None, 0),
+        ("DEFAULT_FUNCTION_DECLARATION", c[4], None, 0),
+        ("DEFAULT_CLASS_NAME", c[3], None, 0),
+        ("DEFAULT_PARAMETER", c[6], None, 0),
+        ("CONSOLE_NORMAL_OUTPUT", fg, None, 0),
+        ("CONSOLE_ERROR_OUTPUT", c[1], None, 0),
+    ];
+    let slots = [
+        "BLACK",
+        "RED",
+        "GREEN",
+        "YELLOW",
+        "BLUE",
+        "MAGENTA",
+        "CYAN",
+        "GRAY",
+        "DARKGRAY",
+        "RED_BRIGHT",
+        "GREEN_BRIGHT",
+        "YELLOW_BRIGHT",
+        "BLUE_BRIGHT",
+        "MAGENTA_BRIGHT",
+        "CYAN_BRIGHT",
+        "WHITE",
+    ];
+    let names: Vec<String> = slots.iter().map(|slot| format!("CONSOLE_{slot}_OUTPUT")).collect();
+    attributes.extend(names.iter().zip(c).map(|(name, rgb)| (name.as_str(), rgb, None, 0)));
+    for (name, foreground, background, font) in attributes {
+        write!(
+            out,
+            "    <option name=\"{name}\">\n      <value>\n        \
+             <option name=\"FOREGROUND\" value=\"{}\" />\n",
+            bare(foreground)
+        )
+        .unwrap();
+        if let Some(background) = background {
+            writeln!(out, "        <option name=\"BACKGROUND\" value=\"{}\" />", bare(background))
+                .unwrap();
+        }
+        if font != 0 {
+            writeln!(out, "        <option name=\"FONT_TYPE\" value=\"{font}\" />").unwrap();
+        }
+        out.push_str("      </value>\n    </option>\n");
+    }
+    out.push_str("  </attributes>\n</scheme>\n");
+    out
+}
+
+// A TextMate theme, which bat builds into its cache and delta then uses for
+// the code in diffs.
+fn tmtheme(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let comment = comment(scheme);
+    let mut out = format!(
+        "{PLIST_HEADER}<dict>\n\t<key>name</key>\n\t<string>colorize</string>\n\t<key>settings</key>\n\t<array>\n\
+         \t\t<dict>\n\t\t\t<key>settings</key>\n\t\t\t<dict>\n"
+    );
+    for (key, rgb) in [
+        ("background", bg),
+        ("foreground", fg),
+        ("caret", scheme.cursor),
+        ("selection", selection(scheme)),
+        ("lineHighlight", c[0]),
+        ("gutterForeground", comment),
+    ] {
+        writeln!(out, "\t\t\t\t<key>{key}</key>\n\t\t\t\t<string>{}</string>", hex(rgb)).unwrap();
+    }
+    out.push_str("\t\t\t</dict>\n\t\t</dict>\n");
+    for (name, scope, rgb) in [
+        ("Comment", "comment, punctuation.definition.comment", comment),
+        ("String", "string", c[2]),
+        ("Number", "constant.numeric", c[3]),
+        ("Constant", "constant.language, constant.character, support.constant", c[3]),
+        ("Keyword", "keyword, storage", c[5]),
+        ("Function", "entity.name.function, support.function", c[4]),
+        ("Type", "entity.name.type, entity.name.class, support.type, storage.type", c[3]),
+        ("Variable", "variable.parameter, variable.other.member", c[6]),
+        ("Tag", "entity.name.tag", c[1]),
+        ("Attribute", "entity.other.attribute-name", c[6]),
+        ("Inserted", "markup.inserted", c[2]),
+        ("Deleted", "markup.deleted", c[1]),
+        ("Changed", "markup.changed", c[3]),
+        ("Invalid", "invalid", c[1]),
+    ] {
+        writeln!(
+            out,
+            "\t\t<dict>\n\t\t\t<key>name</key>\n\t\t\t<string>{name}</string>\n\t\t\t<key>scope</key>\n\t\t\t\
+             <string>{scope}</string>\n\t\t\t<key>settings</key>\n\t\t\t<dict>\n\t\t\t\t\
+             <key>foreground</key>\n\t\t\t\t<string>{}</string>\n\t\t\t</dict>\n\t\t</dict>",
+            hex(rgb)
+        )
+        .unwrap();
+    }
+    out.push_str("\t</array>\n</dict>\n</plist>\n");
+    out
+}
+
+// The comment gray, raised until it keeps the minimum APCA contrast on the
+// background.
+fn comment(scheme: &Scheme) -> [u8; 3] {
+    let minimum = scheme.options.minimum;
+    lift(scheme.ansi[8], scheme.foreground, |rgb| {
+        minimum.apca_passes(apca_contrast(rgb, scheme.background))
+    })
+}
+
+// The selection gray, lowered until the foreground keeps the minimum APCA
+// contrast on it.
+fn selection(scheme: &Scheme) -> [u8; 3] {
+    let minimum = scheme.options.minimum;
+    lift(scheme.ansi[8], scheme.background, |rgb| {
+        minimum.apca_passes(apca_contrast(scheme.foreground, rgb))
+    })
+}
+
+// Scopes map to the palette's ANSI names, with the comment and selection
+// colors kept above the minimum contrast.
+fn helix(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let (comment, selection) = (comment(scheme), selection(scheme));
+
+    let mut out = String::from("# Generated by colorize\n");
+    for (scope, style) in [
+        ("ui.background", "{ bg = \"background\" }"),
+        ("ui.text", "\"foreground\""),
+        ("ui.selection", "{ bg = \"selection\" }"),
+        ("ui.cursor", "{ fg = \"background\", bg = \"foreground\" }"),
+        ("ui.cursor.match", "{ fg = \"yellow\", modifiers = [\"bold\"] }"),
+        ("ui.linenr", "\"comment\""),
+        ("ui.linenr.selected", "\"yellow\""),
+        ("ui.statusline", "{ fg = \"foreground\", bg = \"black\" }"),
+        ("ui.popup", "{ fg = \"foreground\", bg = \"black\" }"),
+        ("ui.menu", "{ fg = \"foreground\", bg = \"black\" }"),
+        ("ui.menu.selected", "{ fg = \"background\", bg = \"blue\" }"),
+        ("ui.virtual", "\"comment\""),
+        ("comment", "{ fg = \"comment\", modifiers = [\"italic\"] }"),
+        ("keyword", "\"magenta\""),
+        ("string", "\"green\""),
+        ("constant", "\"yellow\""),
+        ("function", "\"blue\""),
+        ("type", "\"yellow\""),
+        ("variable.parameter", "\"cyan\""),
+        ("tag", "\"red\""),
+        ("error", "\"red\""),
+        ("warning", "\"yellow\""),
+        ("info", "\"blue\""),
+        ("hint", "\"cyan\""),
+        ("diagnostic.error", "{ underline = { color = \"red\", style = \"curl\" } }"),
+        ("diagnostic.warning", "{ underline = { color = \"yellow\", style = \"curl\" } }"),
+        ("diagnostic.info", "{ underline = { color = \"blue\", style = \"curl\" } }"),
+        ("diagnostic.hint", "{ underline = { color = \"cyan\", style = \"curl\" } }"),
+        ("diff.plus", "\"green\""),
+        ("diff.minus", "\"red\""),
+        ("diff.delta", "\"yellow\""),
+    ] {
+        writeln!(out, "\"{scope}\" = {style}").unwrap();
+    }
+    out.push_str("\n[palette]\n");
+    let mut palette = vec![("background", bg), ("foreground", fg)];
+    palette.extend(NAMES.iter().copied().zip(c));
+    palette.extend([("comment", comment), ("selection", selection)]);
+    for (name, rgb) in palette {
+        writeln!(out, "{name} = \"{}\"", hex(rgb)).unwrap();
+    }
+    out
+}
+
+// A Neovim highlight group with its fg and bg, None leaving the color unset.
+pub type Highlight = (&'static str, Option<[u8; 3]>, Option<[u8; 3]>);
+
+pub fn highlights(scheme: &Scheme) -> Vec<Highlight> {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let comment = comment(scheme);
+    vec![
+        ("Normal", Some(fg), Some(bg)),
+        ("NormalFloat", Some(fg), Some(c[0])),
+        ("Visual", None, Some(selection(scheme))),
+        ("CursorLine", None, Some(c[0])),
+        ("LineNr", Some(comment), None),
+        ("CursorLineNr", Some(c[3]), None),
+        ("StatusLine", Some(fg), Some(c[0])),
+        ("Pmenu", Some(fg), Some(c[0])),
+        ("PmenuSel", Some(bg), Some(c[4])),
+        ("Comment", Some(comment), None),
+        ("String", Some(c[2]), None),
+        ("Constant", Some(c[3]), None),
+        ("Identifier", Some(c[6]), None),
+        ("Function", Some(c[4]), None),
+        ("Statement", Some(c[5]), None),
+        ("Type", Some(c[3]), None),
+        ("Special", Some(c[6]), None),
+        ("Error", Some(c[1]), None),
+        ("DiagnosticError", Some(c[1]), None),
+        ("DiagnosticWarn", Some(c[3]), None),
+        ("DiagnosticInfo", Some(c[4]), None),
+        ("DiagnosticHint", Some(c[6]), None),
+    ]
+}
+
+fn windows_terminal_scheme(scheme: &Scheme) -> String {
+    windows_terminal(scheme, "") + "\n"
+}
+
+// A Windows Terminal color scheme object named "colorize", each line
+// indented by `indent`.
+pub fn windows_terminal(scheme: &Scheme, indent: &str) -> String {
+    let names = NAMES.map(|name| if name == "magenta" { "purple" } else { name });
+    let mut fields = vec![
+        (String::from("background"), scheme.background),
+        (String::from("foreground"), scheme.foreground),
+        (String::from("cursorColor"), scheme.cursor),
+        (String::from("selectionBackground"), scheme.ansi[8]),
+    ];
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        let name = names[i % 8];
+        let name = if i < 8 {
+            name.to_string()
+        } else {
+            format!("bright{}{}", name[..1].to_uppercase(), &name[1..])
+        };
+        fields.push((name, *rgb));
+    }
+    let mut out = format!("{indent}{{\n{indent}  \"name\": \"colorize\"");
+    for (name, rgb) in fields {
+        write!(out, ",\n{indent}  \"{name}\": \"{}\"", hex(rgb)).unwrap();
+    }
+    write!(out, "\n{indent}}}").unwrap();
+    out
+}
+
+// The background or the foreground, whichever has more APCA contrast as text
+// on `fill`.
+fn text_on(fill: [u8; 3], scheme: &Scheme) -> [u8; 3] {
+    let lc = |text| apca_contrast(text, fill).abs();
+    if lc(scheme.background) > lc(scheme.foreground) {
+        scheme.background
+    } else {
+        scheme.foreground
+    }
+}
+
+// libadwaita's named colors, which GTK 4 apps and themes following it draw
+// with: blue as the accent, red for destructive actions, the background for
+// windows and views and its first gray for header bars, popovers and cards.
+fn gtk(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let mut out = String::from("/* Generated by colorize */\n");
+    for (name, rgb) in [
+        ("accent_color", c[4]),
+        ("accent_bg_color", c[4]),
+        ("accent_fg_color", text_on(c[4], scheme)),
+        ("destructive_color", c[1]),
+        ("destructive_bg_color", c[1]),
+        ("destructive_fg_color", text_on(c[1], scheme)),
+        ("success_color", c[2]),
+        ("warning_color", c[3]),
+        ("error_color", c[1]),
+        ("window_bg_color", bg),
+        ("window_fg_color", fg),
+        ("view_bg_color", bg),
+        ("view_fg_color", fg),
+        ("headerbar_bg_color", c[0]),
+        ("headerbar_fg_color", fg),
+        ("headerbar_backdrop_color", bg),
+        ("popover_bg_color", c[0]),
+        ("popover_fg_color", fg),
+        ("card_bg_color", c[0]),
+        ("card_fg_color", fg),
+        ("sidebar_bg_color", c[0]),
+        ("sidebar_fg_color", fg),
+    ] {
+        writeln!(out, "@define-color {name} {};", hex(rgb)).unwrap();
+    }
+    out
+}
+
+// A Plasma color scheme. Every section has the same foreground roles, over
+// the background for views, its first gray for windows, buttons and
+// tooltips, and the accent for selections.
+fn kde(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let rgb = |[r, g, b]: [u8; 3]| format!("{r},{g},{b}");
+    let mut out = String::from("[General]\nColorScheme=colorize\nName=colorize\n");
+    for (section, background, alternate, text) in [
+        ("View", bg, c[0], fg),
+        ("Window", c[0], bg, fg),
+        ("Button", c[0], bg, fg),
+        ("Tooltip", c[0], bg, fg),
+        ("Selection", c[4], c[12], text_on(c[4], scheme)),
+    ] {
+        write!(
+            out,
+            "\n[Colors:{section}]\nBackgroundNormal={}\nBackgroundAlternate={}\n\
+             ForegroundNormal={}\nForegroundInactive={}\nForegroundActive={}\nForegroundLink={}\n\
+             ForegroundVisited={}\nForegroundNegative={}\nForegroundNeutral={}\n\
+             ForegroundPositive={}\nDecorationFocus={}\nDecorationHover={}\n",
+            rgb(background),
+            rgb(alternate),
+            rgb(text),
+            rgb(mix(text, background, 0.4)),
+            rgb(c[4]),
+            rgb(c[4]),
+            rgb(c[5]),
+            rgb(c[1]),
+            rgb(c[3]),
+            rgb(c[2]),
+            rgb(c[4]),
+            rgb(c[12])
+        )
+        .unwrap();
+    }
+    out
+}
+
+// Variables in Hyprland's rgba() syntax: the terminal slots as $color0 to
+// $color15 and the generated colors as $palette0 onwards, as in templates.
+fn hyprland(scheme: &Scheme) -> String {
+    let rgba = |rgb: [u8; 3]| format!("rgba({}ff)", &hex(rgb)[1..]);
+    let mut out = format!(
+        "$background = {}\n$foreground = {}\n",
+        rgba(scheme.background),
+        rgba(scheme.foreground)
+    );
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "$color{i} = {}", rgba(*color)).unwrap();
+    }
+    for (i, color) in scheme.palette.iter().enumerate() {
+        writeln!(out, "$palette{i} = {}", rgba(*color)).unwrap();
+    }
+    out.push_str(
+        "\ngeneral {\n    col.active_border = $color4 $color5 45deg\n    \
+         col.inactive_border = $color8\n}\n",
+    );
+    out
+}
+
+// Window colors for sway and i3, which share the syntax. Title text is the
+// background or the foreground, whichever reads better on each class's color,
+// and a comment gives its APCA Lc and the border's against the background,
+// which should reach the non-text minimum of Lc 30.
+fn window_colors(scheme: &Scheme) -> String {
+    let (bg, fg) = (scheme.background, scheme.foreground);
+    let mut out = format!("set $background {}\nset $foreground {}\n", hex(bg), hex(fg));
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "set $color{i} {}", hex(*color)).unwrap();
+    }
+
+    out.push_str(
+        "\n# class                 border    bg          text        indicator child_border\n",
+    );
+    let slot = |i: usize| (format!("$color{i}"), scheme.ansi[i]);
+    let background = || (String::from("$background"), bg);
+    let classes = [
+        ("focused", slot(4), slot(4), slot(6)),
+        ("unfocused", slot(0), background(), slot(0)),
+        ("focused_inactive", slot(8), background(), slot(8)),
+        ("urgent", slot(1), slot(1), slot(1)),
+    ];
+    for (class, (border, _), (fill, rgb), (indicator, _)) in &classes {
+        let (text, _) = bar_text(*rgb, scheme);
+        writeln!(
+            out,
+            "client.{class:<16} {border:<9} {fill:<11} {text:<11} {indicator:<9} {border}"
+        )
+        .unwrap();
+    }
+    out.push('\n');
+    for (class, (_, border), (_, fill), _) in &classes {
+        let (_, title) = bar_text(*fill, scheme);
+        writeln!(
+            out,
+            "# {class}: title Lc {:.0}, border Lc {:.0} on the background",
+            apca_contrast(title, *fill),
+            apca_contrast(*border, bg)
+        )
+        .unwrap();
+    }
+    out
+}
+
+// The variable and color of the text that reads better on `fill`.
+fn bar_text(fill: [u8; 3], scheme: &Scheme) -> (&'static str, [u8; 3]) {
+    if text_on(fill, scheme) == scheme.background {
+        ("$background", scheme.background)
+    } else {
+        ("$foreground", scheme.foreground)
+    }
+}
+
+// The lines of a bar's `colors` block at `indent`, with each workspace button's
+// text picked like the titles, then the same Lc comments for the buttons: the
+// text on the button, and the button on the bar.
+fn bar_colors(scheme: &Scheme, indent: &str) -> String {
+    let workspaces =
+        [("focused", 4), ("active", 8), ("inactive", 0), ("urgent", 1)].map(|(name, slot)| {
+            let fill = scheme.ansi[slot];
+            (format!("{name}_workspace"), format!("$color{slot}"), fill, bar_text(fill, scheme))
+        });
+    let mut out = format!(
+        "{indent}background $background\n{indent}statusline $foreground\n\
+         {indent}separator  $color8\n{indent}#                  border  bg      text\n"
+    );
+    for (name, color, _, (text, _)) in &workspaces {
+        writeln!(out, "{indent}{name:<18} {color} {color} {text}").unwrap();
+    }
+    out.push('\n');
+    for (name, _, fill, (_, text)) in &workspaces {
+        writeln!(
+            out,
+            "{indent}# {name}: text Lc {:.0}, button Lc {:.0} on the bar",
+            apca_contrast(*text, *fill),
+            apca_contrast(*fill, scheme.background)
+        )
+        .unwrap();
+    }
+    out
+}
+
+// sway applies a block to a bar defined elsewhere by its id, so the colors go
+// to the first bar, `bar-0` unless it has an `id`, without adding another.
+fn sway(scheme: &Scheme) -> String {
+    format!("{}\nbar bar-0 colors {{\n{}}}\n", window_colors(scheme), bar_colors(scheme, "    "))
+}
+
+// i3 cannot change a bar defined elsewhere, so the include brings a whole bar
+// to replace the config's own.
+fn i3(scheme: &Scheme) -> String {
+    format!(
+        "{}\nbar {{\n    status_command i3status\n    colors {{\n{}    }}\n}}\n",
+        window_colors(scheme),
+        bar_colors(scheme, "        ")
+    )
+}
+
+fn waybar(scheme: &Scheme) -> String {
+    let mut out = format!(
+        "@define-color background {};\n@define-color foreground {};\n",
+        hex(scheme.background),
+        hex(scheme.foreground)
+    );
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "@define-color color{i} {};", hex(*color)).unwrap();
+    }
+    out
+}
+
+// A `[colors]` section with the keys of polybar's default config, so that
+// config picks it up as it is, then module settings underlining each module
+// in the next palette color, left commented for the modules of the main config.
+fn polybar(scheme: &Scheme) -> String {
+    let c = scheme.ansi;
+    let mut out = String::from("[colors]\n");
+    for (key, color) in [
+        ("background", scheme.background),
+        ("background-alt", selection(scheme)),
+        ("foreground", scheme.foreground),
+        ("primary", c[4]),
+        ("secondary", c[6]),
+        ("alert", c[1]),
+        ("disabled", comment(scheme)),
+    ] {
+        writeln!(out, "{key} = {}", hex(color)).unwrap();
+    }
+    for (i, color) in c.iter().enumerate() {
+        writeln!(out, "color{i} = {}", hex(*color)).unwrap();
+    }
+    for (i, color) in scheme.palette.iter().enumerate() {
+        writeln!(out, "palette{i} = {}", hex(*color)).unwrap();
+    }
+
+    out.push_str("\n; Module colors to merge into the main config:\n");
+    let modules = ["xworkspaces", "xwindow", "pulseaudio", "memory", "cpu", "wlan", "date"];
+    let palette = (0..scheme.palette.len()).cycle();
+    for (module, i) in modules.iter().zip(palette) {
+        writeln!(
+            out,
+            "; [module/{module}]\n; format-foreground = ${{colors.foreground}}\n\
+             ; format-underline = ${{colors.palette{i}}}\n"
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn rofi(scheme: &Scheme) -> String {
+    let mut out = format!(
+        "* {{\n    background: {};\n    foreground: {};\n    selected: {};\n",
+        hex(scheme.background),
+        hex(scheme.foreground),
+        hex(scheme.ansi[4])
+    );
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "    color{i}: {};", hex(*color)).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Urgency is told apart by the frame and the progress bar: gray, blue and red.
+const URGENCIES: [(&str, usize); 3] = [("low", 8), ("normal", 4), ("critical", 1)];
+
+fn dunst(scheme: &Scheme) -> String {
+    let (bg, fg) = (hex(scheme.background), hex(scheme.foreground));
+    let mut out = String::new();
+    for (urgency, frame) in URGENCIES {
+        let frame = hex(scheme.ansi[frame]);
+        writeln!(
+            out,
+            "[urgency_{urgency}]\n    background = \"{bg}\"\n    foreground = \"{fg}\"\n    \
+             frame_color = \"{frame}\"\n    highlight = \"{frame}\"\n"
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn mako(scheme: &Scheme) -> String {
+    let mut out = format!(
+        "background-color={}\ntext-color={}\n",
+        hex(scheme.background),
+        hex(scheme.foreground)
+    );
+    // The normal colors go first, outside any section, as mako's defaults.
+    let mut urgencies = URGENCIES;
+    urgencies.sort_by_key(|(urgency, _)| *urgency != "normal");
+    for (urgency, frame) in urgencies {
+        let frame = hex(scheme.ansi[frame]);
+        if urgency != "normal" {
+            writeln!(out, "\n[urgency={urgency}]").unwrap();
+        }
+        writeln!(out, "border-color={frame}\nprogress-color=over {frame}").unwrap();
+    }
+    out
+}
+
+// Zathura's interface on the background, with text on colored fills picked by
+// APCA contrast. Recoloring (`:set recolor`) maps the page's white to the
+// background and its black to the foreground.
+fn zathura(scheme: &Scheme) -> String {
+    let (bg, fg, c) = (scheme.background, scheme.foreground, scheme.ansi);
+    let bar = selection(scheme);
+    let mut out = String::new();
+    for (key, color) in [
+        ("default-bg", bg),
+        ("default-fg", fg),
+        ("statusbar-bg", bar),
+        ("statusbar-fg", text_on(bar, scheme)),
+        ("inputbar-bg", bg),
+        ("inputbar-fg", fg),
+        ("notification-bg", bg),
+        ("notification-fg", fg),
+        ("notification-error-bg", c[1]),
+        ("notification-error-fg", text_on(c[1], scheme)),
+        ("notification-warning-bg", c[3]),
+        ("notification-warning-fg", text_on(c[3], scheme)),
+        ("highlight-color", c[3]),
+        ("highlight-active-color", c[4]),
+        ("completion-bg", bg),
+        ("completion-fg", fg),
+        ("completion-group-bg", bar),
+        ("completion-group-fg", text_on(bar, scheme)),
+        ("completion-highlight-bg", c[4]),
+        ("completion-highlight-fg", text_on(c[4], scheme)),
+        ("index-bg", bg),
+        ("index-fg", fg),
+        ("index-active-bg", c[4]),
+        ("index-active-fg", text_on(c[4], scheme)),
+        ("recolor-lightcolor", bg),
+        ("recolor-darkcolor", fg),
+    ] {
+        writeln!(out, "set {key:<24} \"{}\"", hex(color)).unwrap();
+    }
+    writeln!(out, "\n# Recolored text reaches Lc {:.0}", apca_contrast(fg, bg)).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU16;
+
+    use super::*;
+    use crate::context::Context;
+    use crate::json::Json;
+
+    const BG: [u8; 3] = [0x1e, 0x1e, 0x2e];
+
+    fn scheme() -> Scheme {
+        Scheme::new(&Palette::generate(
+            &Context::new(BG),
+            60.0,
+            100.0,
+            0.0,
+            NonZeroU16::new(6).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn every_target_renders_a_whole_file() {
+        let scheme = scheme();
+        for target in &TARGETS {
+            let out = (target.render)(&scheme);
+            assert!(out.ends_with('\n'), "{} ends mid-line", target.name);
+            assert!(!target.install.is_empty(), "{} has nowhere to go", target.name);
+        }
+    }
+
+    #[test]
+    fn the_grade_comment_follows_each_format() {
+        let grade =
+            Grade { worst_lc: 62.0, mean_margin: 5.0, passing: 5, checks: 6, cvd: None, score: 84 };
+        assert_eq!(
+            grade_comment("kitty", &grade).as_deref(),
+            Some("# colorize grade B (84/100): worst APCA Lc 62, 5 of 6 checks pass\n")
+        );
+        assert!(grade_comment("css", &grade).unwrap().starts_with("/* colorize grade B"));
+        // Only files that open with a JSON or XML root go without one.
+        let scheme = scheme();
+        for target in TARGETS.iter().filter(|t| grade_comment(t.name, &grade).is_none()) {
+            let out = (target.render)(&scheme);
+            assert!(
+                out.starts_with('{') || out.starts_with("<?xml"),
+                "{} takes comments",
+                target.name
+            );
+        }
+    }
+
+    #[test]
+    fn bundle_groups_are_checked() {
+        let all = groups("all").unwrap();
+        assert!(all.contains(&"terminal") && all.contains(&"wm"));
+        assert_eq!(groups("bar, terminal"), Ok(vec!["bar", "terminal"]));
+        assert!(matches!(
+            groups("terminal,shells"),
+            Err(ColorizeError::InvalidValue { value, .. }) if value == "shells"
+        ));
+        assert_eq!(target("kitty"), Ok("kitty"));
+        assert!(target("kity").is_err());
+    }
+
+    #[test]
+    fn terminals_name_every_ansi_slot() {
+        let scheme = scheme();
+        let out = hyprland(&scheme);
+        for (slot, rgb) in scheme.ansi.iter().enumerate() {
+            let [r, g, b] = *rgb;
+            assert!(out.contains(&format!("$color{slot} = rgba({r:02x}{g:02x}{b:02x}ff)")));
+        }
+        assert!(kitty(&scheme).starts_with("background #1e1e2e\n"));
+    }
+
+    #[test]
+    fn iterm2_profiles_carry_every_color() {
+        let scheme = scheme();
+        let profile = Json::parse(&iterm2(&scheme)).unwrap();
+        let Some(Json::Array(profiles)) = profile.get("Profiles") else { panic!("no profiles") };
+        let [r, g, b] = scheme.ansi[15].map(|c| f64::from(c) / 255.0);
+        let ansi15 = profiles[0].get("Ansi 15 Color").unwrap();
+        for (key, value) in [("Red Component", r), ("Green Component", g), ("Blue Component", b)] {
+            let Some(Json::Number(n)) = ansi15.get(key) else { panic!("no {key}") };
+            assert!((n - value).abs() < 1e-6);
+        }
+
+        let settings = terminal_app(&scheme);
+        assert_eq!(settings.matches("<data>").count(), 5 + 16);
+        assert!(settings.contains("<key>ANSIBrightWhiteColor</key>"));
+    }
+
+    #[test]
+    fn css_numbers_the_palette_from_one() {
+        let scheme = scheme();
+        let css = css(&scheme);
+        assert!(css.starts_with(":root {\n  --background: #1e1e2e;\n"));
+        assert!(css.ends_with("}\n"));
+        assert_eq!(css.matches("--color-").count(), scheme.palette.len());
+        let first = format!("--color-1: {};", hex(scheme.palette[0]));
+        assert!(css.contains(&first));
+        assert_eq!(scss(&scheme).lines().count(), 2 + scheme.palette.len());
+        assert!(scss(&scheme).contains(&format!("${}", &first[2..])));
+    }
+
+    #[test]
+    fn sway_and_i3_color_the_bar_and_check_its_workspaces() {
+        let scheme = scheme();
+        let sway = sway(&scheme);
+        assert!(sway.contains("\nbar bar-0 colors {\n    background $background\n"));
+        assert_eq!(sway.matches("    focused_workspace  $color4 $color4 $").count(), 1);
+        assert_eq!(sway.matches("_workspace: text Lc ").count(), 4);
+        assert!(!sway.contains("# bar {"));
+
+        let i3 = i3(&scheme);
+        assert!(i3.contains("\nbar {\n    status_command i3status\n    colors {\n"));
+        assert!(i3.contains("        urgent_workspace   $color1 $color1 $"));
+        assert!(i3.ends_with("    }\n}\n"));
+        assert_eq!(i3.matches("client.").count(), 4);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::colorize_output;
+use crate::context::Context;
+use crate::palette::Palette;
+
+const WEIGHTS: [u16; 3] = [300, 400, 700];
+
+// Minimum font size in px per APCA Lc (rows, in steps of 5) and weight (columns
+// 300, 400, 700), from the APCA-W3 font lookup table. 777 marks contrast only
+// fit for non-text elements, 999 contrast that should not be used at all.
+const LOOKUP: [[f32; 3]; 26] = [
+    [999.0, 999.0, 999.0],
+    [999.0, 999.0, 999.0],
+    [999.0, 999.0, 999.0],
+    [777.0, 777.0, 777.0],
+    [777.0, 777.0, 777.0],
+    [777.0, 120.0, 96.0],
+    [120.0, 108.0, 72.0],
+    [108.0, 96.0, 48.0],
+    [96.0, 60.0, 32.0],
+    [72.0, 42.0, 24.0],
+    [60.0, 32.0, 21.0],
+    [48.0, 28.0, 18.0],
+    [42.0, 24.0, 16.0],
+    [32.0, 21.75, 15.0],
+    [28.0, 19.5, 14.5],
+    [24.0, 18.0, 14.0],
+    [23.0, 17.25, 14.0],
+    [22.0, 16.5, 14.0],
+    [21.0, 16.0, 14.0],
+    [19.5, 15.5, 13.5],
+    [18.5, 15.0, 13.0],
+    [18.0, 14.5, 12.0],
+    [18.0, 14.0, 11.0],
+    [17.5, 13.5, 10.0],
+    [17.0, 13.0, 10.0],
+    [16.5, 12.5, 10.0],
+];
+
+// The row at or below the achieved |Lc|, so the guidance never overstates it.
+fn min_size(lc: f64, column: usize) -> f32 {
+    let row = ((lc.abs() / 5.0).floor() as usize).min(LOOKUP.len() - 1);
+    LOOKUP[row][column]
+}
+
+fn describe(size: f32) -> String {
+    if size >= 999.0 {
+        String::from("avoid")
+    } else if size >= 777.0 {
+        String::from("non-text")
+    } else {
+        format!("{size}px")
+    }
+}
+
+// Turns each color's APCA Lc into the smallest font size it supports at a light,
+// regular and bold weight.
+pub fn print_guidance(palette: &Palette, ctx: &Context) {
+    println!("\nAPCA minimum font size:");
+    print!("{:<15}", "");
+    for weight in WEIGHTS {
+        print!("{:>10}", format!("w{weight}"));
+    }
+    println!();
+
+    for color in &palette.colors {
+        let lc = ctx.apca(color.rgb);
+        let swatch = colorize_output(color.rgb, &format!("#{}", color.hex()), ctx.bg);
+        print!("{swatch} Lc {:>3.0} ", lc.abs());
+        for column in 0..WEIGHTS.len() {
+            print!("{:>10}", describe(min_size(lc, column)));
+        }
+        println!();
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt::Write;
+
+use crate::apply::Scheme;
+use crate::context::{Thresholds, polarity};
+use crate::grade::Grade;
+use crate::json::Json;
+use crate::palette::Palette;
+
+pub const FORMATS: [&str; 3] = ["json", "nix", "home-manager"];
+
+// `--format`: the palette on stdout in a form other programs read, in place
+// of the contrast report, with its grade.
+pub fn print(format: &str, palette: &Palette, target: Thresholds, grade: &Grade) {
+    let scheme = Scheme::new(palette);
+    let out = match format {
+        "json" => json(palette, target, grade).to_string() + "\n",
+        "nix" => nix(&scheme, grade, "") + "\n",
+        _ => home_manager(&scheme, grade),
+    };
+    print!("{out}");
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// Every color with its channels, okhsl coordinates and each metric's score
+// and verdict against the target, and the grade with the keys of the JSON
+// report, for scripts.
+fn json(palette: &Palette, target: Thresholds, grade: &Grade) -> Json {
+    let round = |n: f64, places: i32| (n * 10f64.powi(places)).round() / 10f64.powi(places);
+    let field = |key: &str, value| (key.to_string(), value);
+    let colors = palette
+        .colors
+        .iter()
+        .map(|color| {
+            let rgb = color.rgb.map(|c| Json::Number(f64::from(c)));
+            let okhsl = color.okhsl;
+            Json::Object(vec![
+                field("hex", Json::String(format!("#{}", color.hex()))),
+                field("rgb", Json::Array(rgb.to_vec())),
+                field(
+                    "okhsl",
+                    Json::Object(vec![
+                        field("h", Json::Number(round(okhsl.h, 1))),
+                        field("s", Json::Number(round(f64::from(okhsl.s), 3))),
+                        field("l", Json::Number(round(f64::from(okhsl.l), 3))),
+                    ]),
+                ),
+                field(
+                    "wcag",
+                    Json::Object(vec![
+                        field("ratio", Json::Number(round(f64::from(color.wcag), 2))),
+                        field("pass", Json::Bool(color.wcag >= target.wcag)),
+                    ]),
+                ),
+                field(
+                    "apca",
+                    Json::Object(vec![
+                        field("lc", Json::Number(round(color.apca, 1))),
+                        field("polarity", Json::String(polarity(color.apca).to_string())),
+                        field("pass", Json::Bool(target.apca_passes(color.apca))),
+                    ]),
+                ),
+                field("pass", Json::Bool(color.pass)),
+            ])
+        })
+        .collect();
+    Json::Object(vec![
+        field("background", Json::String(format!("#{}", palette.background()))),
+        field(
+            "target",
+            Json::Object(vec![
+                field("wcag", Json::Number(f64::from(target.wcag))),
+                field("apca", Json::Number(target.apca)),
+                field("apca_reverse", Json::Number(target.apca_reverse)),
+            ]),
+        ),
+        field(
+            "grade",
+            Json::Object(vec![
+                field("letter", Json::String(grade.letter().to_string())),
+                field("score", Json::Number(f64::from(grade.score))),
+                field("worst_apca", Json::Number(round(grade.worst_lc, 1))),
+                field("mean_margin", Json::Number(round(grade.mean_margin, 1))),
+                field("passing", Json::Number(grade.passing as f64)),
+                field("checks", Json::Number(grade.checks as f64)),
+                field(
+                    "closest_cvd",
+                    grade.cvd.map_or(Json::Null, |(deficiency, distance)| {
+                        Json::Object(vec![
+                            field("deficiency", Json::String(deficiency.name().to_string())),
+                            field("delta_e", Json::Number(round(f64::from(distance), 1))),
+                        ])
+                    }),
+                ),
+            ]),
+        ),
+        field("colors", Json::Array(colors)),
+    ])
+}
+
+// The palette as a Nix attribute set: the generated colors in order, the
+// terminal scheme derived from them and the grade, indented by `indent`.
+fn nix(scheme: &Scheme, grade: &Grade, indent: &str) -> String {
+    let mut out = String::from("{\n");
+    for (name, rgb) in [
+        ("background", scheme.background),
+        ("foreground", scheme.foreground),
+        ("cursor", scheme.cursor),
+    ] {
+        writeln!(out, "{indent}  {name} = \"{}\";", hex(rgb)).unwrap();
+    }
+    let colors: Vec<String> =
+        scheme.palette.iter().map(|rgb| format!("\"{}\"", hex(*rgb))).collect();
+    writeln!(out, "{indent}  colors = [ {} ];", colors.join(" ")).unwrap();
+    writeln!(out, "{indent}  ansi = {{").unwrap();
+    for (i, rgb) in scheme.ansi.iter().enumerate() {
+        writeln!(out, "{indent}    color{i} = \"{}\";", hex(*rgb)).unwrap();
+    }
+    writeln!(out, "{indent}  }};").unwrap();
+    writeln!(
+        out,
+        "{indent}  grade = {{ letter = \"{}\"; score = {}; worst_apca = {:.1}; passing = {}; checks = {}; }};",
+        grade.letter(),
+        grade.score,
+        grade.worst_lc,
+        grade.passing,
+        grade.checks
+    )
+    .unwrap();
+    write!(out, "{indent}}}").unwrap();
+    out
+}
+
+// A Home Manager module: the attribute set goes to every other module as the
+// `colorize` argument, and kitty and foot are themed straight away.
+fn home_manager(scheme: &Scheme, grade: &Grade) -> String {
+    format!(
+        "{{ lib, ... }}:\n\nlet\n  colorize = {};\n  bare = lib.removePrefix \"#\";\nin\n{{\n  \
+         _module.args.colorize = colorize;\n\n  \
+         programs.kitty.settings = {{\n    inherit (colorize) background foreground cursor;\n  \
+         }} // colorize.ansi;\n\n  \
+         programs.foot.settings.colors = {{\n    background = bare colorize.background;\n    \
+         foreground = bare colorize.foreground;\n  }} // lib.mapAttrs' (name: value: {{\n    \
+         name = let i = lib.toInt (lib.removePrefix \"color\" name); in\n      \
+         if i < 8 then \"regular${{toString i}}\" else \"bright${{toString (i - 8)}}\";\n    \
+         value = bare value;\n  }}) colorize.ansi;\n}}\n",
+        nix(scheme, grade, "  ")
+    )
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::process::Command;
+
+use crate::config::{self, Config};
+use crate::json::Json;
+use crate::palette::Palette;
+use crate::serve;
+use crate::{ColorizeError, ansi, color_name, log, print_contrast};
+
+// The index shipped with colorize; `gallery = "<url>"` in config.toml points
+// at another one.
+const INDEX: &str = "https://raw.githubusercontent.com/emrakyz/colorize/main/gallery.json";
+
+// One shared palette. Entries carry the parameters rather than the colors, so
+// they are regenerated here and imported presets stay editable.
+struct Entry {
+    name: String,
+    author: String,
+    background: String,
+    lightness: f64,
+    saturation: f64,
+    offset: f64,
+    count: f64,
+}
+
+impl Entry {
+    // Control characters are dropped from the text, which ends up in the
+    // terminal and in the comment line of an imported preset.
+    fn from_json(value: &Json) -> Option<Self> {
+        let text = |key| match value.get(key) {
+            Some(Json::String(s)) => Some(s.chars().filter(|c| !c.is_control()).collect()),
+            _ => None,
+        };
+        let number = |key, default| match value.get(key) {
+            Some(Json::Number(n)) => *n,
+            _ => default,
+        };
+        Some(Self {
+            name: text("name")?,
+            author: text("author").unwrap_or_default(),
+            background: text("background")?.trim_start_matches('#').to_string(),
+            lightness: number("lightness", 60.0),
+            saturation: number("saturation", 100.0),
+            offset: number("offset", 0.0),
+            count: number("count", 6.0),
+        })
+    }
+
+    fn palette(&self) -> Result<Palette, String> {
+        let values = [
+            ("bg", self.background.clone()),
+            ("l", self.lightness.to_string()),
+            ("s", self.saturation.to_string()),
+            ("o", self.offset.to_string()),
+            ("c", self.count.to_string()),
+        ];
+        let query: HashMap<&str, &str> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        serve::from_query(&query).map_err(|err| err.to_string())
+    }
+
+    // The preset file name: the palette's name in lowercase with dashes.
+    fn slug(&self) -> String {
+        let words: Vec<String> = self
+            .name
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect();
+        words.join("-")
+    }
+
+    fn preset(&self) -> String {
+        format!(
+            "# {} by {}, from the colorize gallery\nbackground = \"{}\"\nlightness = {}\n\
+             saturation = {}\noffset = {}\ncount = {}\n",
+            self.name,
+            self.author,
+            self.background,
+            self.lightness,
+            self.saturation,
+            self.offset,
+            self.count
+        )
+    }
+}
+
+// `colorize gallery [show|import <n|name>]`: lists the shared palettes with
+// swatches, previews one against its background, or imports one as a preset
+// for `--preset`.
+pub fn run(args: &[String]) -> Result<(), ColorizeError> {
+    let command = args.first().map(String::as_str);
+    if let Some(other) = command.filter(|cmd| !matches!(*cmd, "show" | "import")) {
+        return Err(ColorizeError::UnknownArgument { arg: other.to_string(), suggestion: None });
+    }
+    let url = Config::load().get("gallery").unwrap_or_else(|| INDEX.to_string());
+    let entries = fetch(&url)?;
+    let Some(cmd) = command else {
+        list(&entries);
+        return Ok(());
+    };
+    let Some(entry) = args.get(1).and_then(|query| find(&entries, query)) else {
+        return Err(ColorizeError::Usage {
+            usage: format!("colorize gallery {cmd} <number|name>; see colorize gallery"),
+        });
+    };
+    if cmd == "show" { show(entry) } else { import(entry) }
+}
+
+fn fetch(url: &str) -> Result<Vec<Entry>, ColorizeError> {
+    log::debug(format!("Fetching the gallery index from {url}"));
+    let failed = |reason: String| ColorizeError::Io { action: format!("fetch {url}"), reason };
+    let output = Command::new("curl").args(["-fsSL", "--max-time", "15", url]).output();
+    let body = match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        Ok(output) => {
+            return Err(failed(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+        }
+        Err(err) => return Err(failed(format!("curl did not run: {err}"))),
+    };
+    let Some(Json::Array(palettes)) =
+        Json::parse(&body).and_then(|index| index.get("palettes").cloned())
+    else {
+        return Err(failed(String::from("not a colorize gallery index")));
+    };
+    Ok(palettes.iter().filter_map(Entry::from_json).collect())
+}
+
+// An entry by its number in the list or by name, ignoring case.
+fn find<'a>(entries: &'a [Entry], query: &str) -> Option<&'a Entry> {
+    match query.parse::<usize>() {
+        Ok(n) => entries.get(n.checked_sub(1)?),
+        Err(_) => entries.iter().find(|entry| {
+            entry.name.eq_ignore_ascii_case(query) || entry.slug() == query.to_lowercase()
+        }),
+    }
+}
+
+// Entries whose parameters are out of range are left out; they keep their
+// number so the others can still be picked by it.
+fn list(entries: &[Entry]) {
+    for (i, entry) in entries.iter().enumerate() {
+        let Ok(palette) = entry.palette() else { continue };
+        let mut swatches = String::new();
+        for color in &palette.colors {
+            write!(swatches, "{}██", ansi::fg(color.rgb)).unwrap();
+        }
+        println!(
+            "{:>2} {:<22} {:<16} {} {swatches} \x1b[0m",
+            i + 1,
+            entry.name,
+            entry.author,
+            ansi::bg(palette.bg)
+        );
+    }
+    println!(
+        "\nPreview one with colorize gallery show <n>, keep it with colorize gallery import <n>"
+    );
+}
+
+// Why `entry` cannot be used, when its parameters are out of range.
+fn invalid(entry: &Entry, reason: String) -> ColorizeError {
+    ColorizeError::Io { action: format!("use {} from the gallery", entry.name), reason }
+}
+
+fn show(entry: &Entry) -> Result<(), ColorizeError> {
+    let palette = entry.palette().map_err(|err| invalid(entry, err))?;
+    println!(
+        "{} by {}: -b {} -l {} -s {} -o {} -c {}\n",
+        entry.name,
+        entry.author,
+        entry.background,
+        entry.lightness,
+        entry.saturation,
+        entry.offset,
+        entry.count
+    );
+    let ctx = palette.context();
+    for (i, color) in palette.colors.iter().enumerate() {
+        let label = format!("{:>2} #{} {}", i + 1, color.hex(), color_name(color.rgb));
+        print_contrast(&ctx, color.rgb, &label, 18, false);
+    }
+    Ok(())
+}
+
+fn import(entry: &Entry) -> Result<(), ColorizeError> {
+    entry.palette().map_err(|err| invalid(entry, err))?;
+    let Some(dir) = config::dir().map(|dir| dir.join("presets")) else {
+        return Err(ColorizeError::Io {
+            action: format!("import {}", entry.name),
+            reason: String::from("neither XDG_CONFIG_HOME nor HOME is set"),
+        });
+    };
+    let path = dir.join(format!("{}.toml", entry.slug()));
+    fs::create_dir_all(&dir).and_then(|()| fs::write(&path, entry.preset())).map_err(|err| {
+        ColorizeError::Io { action: format!("write {}", path.display()), reason: err.to_string() }
+    })?;
+    log::debug(format!("Wrote {}", path.display()));
+    println!("Imported {}; use it with colorize --preset {}", entry.name, entry.slug());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_cannot_add_preset_keys() {
+        let index = r##"{"name": "Dusk\nbackground = \"ffffff\"", "author": "me\r\ncount = 64",
+            "background": "#1e1e2e", "count": 8}"##;
+        let entry = Entry::from_json(&Json::parse(index).unwrap()).unwrap();
+        assert_eq!(entry.name, "Duskbackground = \"ffffff\"");
+        let preset = entry.preset();
+        assert_eq!(preset.lines().count(), 6);
+        assert_eq!(preset.lines().filter(|line| line.starts_with("background")).count(), 1);
+        assert!(preset.contains("background = \"1e1e2e\"\n"));
+        assert!(preset.ends_with("count = 8\n"));
+    }
+}
@@ -0,0 +1,219 @@
+use std::num::NonZeroU16;
+use std::sync::OnceLock;
+use std::sync::mpsc;
+
+use okhsl::Okhsl;
+use wgpu::util::DeviceExt;
+
+use crate::Distinct;
+use crate::cache::ValidSet;
+use crate::context::{Context, Thresholds};
+use crate::log;
+
+const SHADER: &str = include_str!("sweep.wgsl");
+const CUBE_LEN: u32 = 101 * 101 * 360;
+// 101 lightness steps times 11 saturation steps, as `nearest_passing` tries.
+const CANDIDATES: u32 = 101 * 11;
+const WORKGROUP_SIZE: u32 = 64;
+
+// The adapter's device and both pipelines, set up once per run since auto-fix
+// scores every failing color of the palette.
+struct Gpu {
+    name: String,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    sweep: wgpu::ComputePipeline,
+    score: wgpu::ComputePipeline,
+}
+
+static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+
+fn gpu() -> Option<&'static Gpu> {
+    GPU.get_or_init(Gpu::new).as_ref()
+}
+
+// Whether an adapter was found, so the work below runs on it rather than on
+// the CPU.
+pub fn available() -> bool {
+    gpu().is_some()
+}
+
+impl Gpu {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+                .ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sweep"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+        let sweep = pipeline("sweep");
+        let score = pipeline("score_candidates");
+
+        Some(Self { name: adapter.get_info().name, device, queue, sweep, score })
+    }
+
+    fn buffer(&self, label: &str, contents: &[u8], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage,
+        })
+    }
+
+    // Binds `buffers` at their binding numbers, runs one invocation per item
+    // and reads `output` back. None when the readback could not be mapped.
+    fn dispatch(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        buffers: &[(u32, &wgpu::Buffer)],
+        items: u32,
+        output: &wgpu::Buffer,
+    ) -> Option<Vec<u8>> {
+        let entries: Vec<wgpu::BindGroupEntry> = buffers
+            .iter()
+            .map(|&(binding, buffer)| wgpu::BindGroupEntry {
+                binding,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: output.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder =
+            self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(items.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(output, 0, &readback, 0, output.size());
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |mapped| {
+            let _ = sender.send(mapped);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+
+        let data = slice.get_mapped_range().to_vec();
+        readback.unmap();
+        Some(data)
+    }
+}
+
+fn bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+// Runs the valid-combination sweep as a compute shader. Returns `None` when no
+// adapter is available so the caller can fall back to the CPU sweep.
+pub fn gen_valid_combs(
+    bg: [u8; 3],
+    minimum: Thresholds,
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> Option<ValidSet> {
+    let gpu = gpu()?;
+
+    let [r, g, b] = bg;
+    log::info(format!(
+        "Computing valid combinations for #{r:02X}{g:02X}{b:02X} on {}...",
+        gpu.name
+    ));
+
+    let ctx = Context::new(bg);
+    let params = bytes(&[
+        ctx.bg_lum,
+        ctx.bg_luma as f32,
+        minimum.wcag,
+        minimum.apca as f32,
+        minimum.apca_reverse as f32,
+        f32::from(count.get()),
+        distinct.separation.unwrap_or_default(),
+        distinct.cvd.unwrap_or_default(),
+    ]);
+
+    let words = CUBE_LEN.div_ceil(32) as usize;
+    let params = gpu.buffer("params", &params, wgpu::BufferUsages::UNIFORM);
+    let valid = gpu.buffer(
+        "valid",
+        &vec![0; words * 4],
+        wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+    );
+    let data = gpu.dispatch(&gpu.sweep, &[(0, &params), (1, &valid)], CUBE_LEN, &valid)?;
+
+    let bits = data
+        .chunks(8)
+        .map(|c| {
+            let mut word = [0; 8];
+            word[..c.len()].copy_from_slice(c);
+            u64::from_le_bytes(word)
+        })
+        .collect();
+    Some(ValidSet::from_words(bits))
+}
+
+// Which candidates of `nearest_passing` reach `goal` on every surface, in its
+// order: lightness 0 to 100, and for each the saturation cut by 0 to 100% in
+// tenths. None when no adapter is available.
+pub fn passing(
+    start: Okhsl,
+    surfaces: &[&Context],
+    goal: &impl Fn(&Context) -> Thresholds,
+) -> Option<Vec<bool>> {
+    let gpu = gpu()?;
+
+    let mut score = bytes(&[start.h as f32, start.s]);
+    score.extend_from_slice(&(surfaces.len() as u32).to_le_bytes());
+    score.extend_from_slice(&0_u32.to_le_bytes());
+    let thresholds: Vec<f32> = surfaces
+        .iter()
+        .flat_map(|ctx| {
+            let goal = goal(ctx);
+            [ctx.bg_lum, ctx.bg_luma as f32, goal.wcag, goal.apca as f32, goal.apca_reverse as f32]
+        })
+        .collect();
+
+    let score = gpu.buffer("score", &score, wgpu::BufferUsages::UNIFORM);
+    let surfaces = gpu.buffer("surfaces", &bytes(&thresholds), wgpu::BufferUsages::STORAGE);
+    let passing = gpu.buffer(
+        "passing",
+        &vec![0; CANDIDATES as usize * 4],
+        wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+    );
+    let data = gpu.dispatch(
+        &gpu.score,
+        &[(2, &score), (3, &surfaces), (4, &passing)],
+        CANDIDATES,
+        &passing,
+    )?;
+
+    Some(data.chunks_exact(4).map(|c| c != [0; 4]).collect())
+}
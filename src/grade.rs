@@ -0,0 +1,101 @@
+use crate::context::Context;
+use crate::cvd::Deficiency;
+use crate::palette::Palette;
+
+// Oklab distance at which two colors stay easy to tell apart at a glance.
+const DISTINCT: f32 = 10.0;
+
+/// One number for the whole palette so palettes can be compared and gated. Half
+/// of the score is the worst APCA contrast against the target, a quarter the
+/// share of color and surface pairs that pass, a quarter how well the colors
+/// stay apart under the hardest dichromacy.
+pub struct Grade {
+    /// The lowest APCA Lc magnitude of any color on any surface, 0 without
+    /// colors.
+    pub worst_lc: f64,
+    /// How far above the target APCA Lc the colors are on average, negative
+    /// when they fall short.
+    pub mean_margin: f64,
+    /// The color and surface pairs that reach the target.
+    pub passing: usize,
+    /// Every color and surface pair.
+    pub checks: usize,
+    /// The dichromacy under which two colors come closest, and their Oklab
+    /// distance there. `None` with fewer than two colors.
+    pub cvd: Option<(Deficiency, f32)>,
+    /// From 0 to 100.
+    pub score: u32,
+}
+
+/// The grade of `palette` on `surface` and `others`, measured against each
+/// one's target. The contrast half is scored against the target of `surface`.
+/// A palette without colors passes no checks and scores 25 at most.
+pub fn compute(palette: &Palette, surface: &Context, others: &[&Context]) -> Grade {
+    let rgbs = palette.rgbs();
+
+    let mut worst_lc: Option<f64> = None;
+    let mut margins = 0.0;
+    let mut passing = 0;
+    for ctx in std::iter::once(surface).chain(others.iter().copied()) {
+        for &fg in &rgbs {
+            let apca = ctx.apca(fg);
+            worst_lc = Some(worst_lc.map_or(apca.abs(), |worst| worst.min(apca.abs())));
+            margins += apca.abs() - ctx.target.required(apca);
+            passing += usize::from(ctx.target.passes(ctx.wcag(fg), apca));
+        }
+    }
+    let worst_lc = worst_lc.unwrap_or(0.0);
+    let checks = rgbs.len() * (1 + others.len());
+    let mean_margin = margins / checks.max(1) as f64;
+
+    let cvd = Deficiency::DICHROMACIES
+        .iter()
+        .filter_map(|&d| d.closest_pair(&rgbs).map(|(_, _, distance)| (d, distance)))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+
+    let target = surface.target.apca;
+    let contrast = (worst_lc / target).min(1.0) * 50.0;
+    let share = passing as f64 / checks.max(1) as f64 * 25.0;
+    let distinct = cvd.map_or(1.0, |(_, distance)| (distance / DISTINCT).min(1.0)) * 25.0;
+    let score = (contrast + share + f64::from(distinct)).round() as u32;
+
+    Grade { worst_lc, mean_margin, passing, checks, cvd, score }
+}
+
+impl Grade {
+    /// A for 90 and up, then B, C and D every ten points, F below 60.
+    pub const fn letter(&self) -> char {
+        match self.score {
+            90.. => 'A',
+            80..=89 => 'B',
+            70..=79 => 'C',
+            60..=69 => 'D',
+            _ => 'F',
+        }
+    }
+
+    /// Prints the grade and what it is made of to stdout.
+    pub fn print(&self) {
+        println!("\nGrade: {} ({}/100)", self.letter(), self.score);
+        println!("  worst APCA Lc:   {:.0}", self.worst_lc);
+        println!("  mean margin:     {:+.1}", self.mean_margin);
+        println!("  passing:         {} of {}", self.passing, self.checks);
+        if let Some((deficiency, distance)) = self.cvd {
+            println!("  closest colors:  ΔE {distance:.1} ({})", deficiency.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_palette_without_colors_grades_as_failing() {
+        let ctx = Context::new([0x1e, 0x1e, 0x2e]);
+        let grade = compute(&Palette::new(&ctx, []), &ctx, &[]);
+        assert_eq!((grade.worst_lc, grade.passing, grade.checks), (0.0, 0, 0));
+        assert_eq!(grade.score, 25);
+        assert_eq!(grade.letter(), 'F');
+    }
+}
@@ -0,0 +1,166 @@
+use std::f64::consts::PI;
+use std::sync::OnceLock;
+
+// HCT, the color space of Material You: hue and chroma from CAM16 under the
+// default viewing conditions (D65, 200/π·Y(50) cd/m² adapting luminance, a
+// mid-gray background, average surround), tone as CIE L*.
+#[derive(Clone, Copy)]
+pub struct Hct {
+    pub hue: f64,
+    pub chroma: f64,
+    pub tone: f64,
+}
+
+struct Viewing {
+    rgb_d: [f64; 3],
+    fl: f64,
+    n: f64,
+    nbb: f64,
+    c: f64,
+    nc: f64,
+    z: f64,
+    aw: f64,
+}
+
+const M16: [[f64; 3]; 3] = [
+    [0.401_288, 0.650_173, -0.051_461],
+    [-0.250_268, 1.204_414, 0.045_854],
+    [-0.002_079, 0.048_952, 0.953_127],
+];
+const SRGB_TO_XYZ: [[f64; 3]; 3] = [
+    [0.412_338_95, 0.357_620_64, 0.180_510_42],
+    [0.212_6, 0.715_2, 0.072_2],
+    [0.019_321_41, 0.119_163_82, 0.950_344_78],
+];
+const WHITE: [f64; 3] = [95.047, 100.0, 108.883];
+
+fn mul(m: [[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    m.map(|row| row[2].mul_add(v[2], row[0].mul_add(v[0], row[1] * v[1])))
+}
+
+fn y_from_lstar(lstar: f64) -> f64 {
+    let ft = (lstar + 16.0) / 116.0;
+    100.0 * if ft.powi(3) > 216.0 / 24389.0 { ft.powi(3) } else { lstar / (24389.0 / 27.0) }
+}
+
+fn lstar_from_y(y: f64) -> f64 {
+    let y = y / 100.0;
+    if y <= 216.0 / 24389.0 { y * 24389.0 / 27.0 } else { 116.0f64.mul_add(y.cbrt(), -16.0) }
+}
+
+fn adapt(component: f64, fl: f64) -> f64 {
+    let af = (fl * component.abs() / 100.0).powf(0.42);
+    component.signum() * 400.0 * af / (af + 27.13)
+}
+
+fn viewing() -> &'static Viewing {
+    static VIEWING: OnceLock<Viewing> = OnceLock::new();
+    VIEWING.get_or_init(|| {
+        let la = 200.0 / PI * y_from_lstar(50.0) / 100.0;
+        // Average surround: F = 1, c = 0.69, Nc = 1.
+        let (c, nc) = (0.69, 1.0);
+        let d = (1.0 / 3.6f64).mul_add(-((-la - 42.0) / 92.0).exp(), 1.0).clamp(0.0, 1.0);
+        let rgb_w = mul(M16, WHITE);
+        let rgb_d = rgb_w.map(|w| d * (100.0 / w) + 1.0 - d);
+        let k = 1.0 / 5.0f64.mul_add(la, 1.0);
+        let k4 = k.powi(4);
+        let fl = (0.1 * (1.0 - k4).powi(2)).mul_add((5.0 * la).cbrt(), k4 * la);
+        let n = y_from_lstar(50.0) / WHITE[1];
+        let nbb = 0.725 / n.powf(0.2);
+        let [ra, ga, ba] = [0, 1, 2].map(|i| adapt(rgb_d[i] * rgb_w[i], fl));
+        let aw = 0.05f64.mul_add(ba, 2.0f64.mul_add(ra, ga)) * nbb;
+        Viewing { rgb_d, fl, n, nbb, c, nc, z: 1.48 + n.sqrt(), aw }
+    })
+}
+
+impl Hct {
+    pub fn from_rgb(rgb: [u8; 3]) -> Self {
+        let linear = rgb.map(|c| f64::from(crate::linearize(f32::from(c) / 255.0)));
+        let (hue, chroma) = cam16(linear);
+        let y = 100.0 * SRGB_TO_XYZ[1].iter().zip(linear).map(|(m, c)| m * c).sum::<f64>();
+        Self { hue, chroma, tone: lstar_from_y(y) }
+    }
+
+    // The sRGB color with this hue and tone, and this chroma or as much of it
+    // as fits in gamut. Searches Oklab: lightness to hit the tone, chroma to
+    // hit the CAM16 chroma, then corrects the Oklab hue for CAM16's.
+    pub fn to_rgb(self) -> [u8; 3] {
+        let y = y_from_lstar(self.tone) / 100.0;
+        let mut angle = self.hue.to_radians();
+        let mut linear = [y; 3];
+        for _ in 0..6 {
+            let (mut low, mut high) = (0.0, 0.4);
+            for _ in 0..20 {
+                let k = f64::midpoint(low, high);
+                let candidate = with_luminance(angle, k, y);
+                let fits = candidate.iter().all(|c| (0.0..=1.0).contains(c));
+                if fits && cam16(candidate).1 <= self.chroma {
+                    low = k;
+                    linear = candidate;
+                } else {
+                    high = k;
+                }
+            }
+            let error = (self.hue - cam16(linear).0 + 540.0) % 360.0 - 180.0;
+            angle += error.to_radians();
+        }
+        linear.map(|c| (crate::cvd::delinearize(c.clamp(0.0, 1.0) as f32) * 255.0).round() as u8)
+    }
+}
+
+fn cam16(linear: [f64; 3]) -> (f64, f64) {
+    let vc = viewing();
+    let xyz = mul(SRGB_TO_XYZ, linear.map(|c| c * 100.0));
+    let cone = mul(M16, xyz);
+    let [ra, ga, ba] = [0, 1, 2].map(|i| adapt(vc.rgb_d[i] * cone[i], vc.fl));
+
+    let a = (11.0f64.mul_add(ra, -12.0 * ga) + ba) / 11.0;
+    let b = 2.0f64.mul_add(-ba, ra + ga) / 9.0;
+    let u = 21.0f64.mul_add(ba, 20.0 * (ra + ga)) / 20.0;
+    let p2 = (40.0f64.mul_add(ra, 20.0 * ga) + ba) / 20.0;
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+    let lightness = 100.0 * (p2 * vc.nbb / vc.aw).max(0.0).powf(vc.c * vc.z);
+
+    let hue_prime = if hue < 20.14 { hue + 360.0 } else { hue };
+    let e_hue = 0.25 * ((hue_prime.to_radians() + 2.0).cos() + 3.8);
+    let p1 = 50000.0 / 13.0 * e_hue * vc.nc * vc.nbb;
+    let magnitude = p1 * a.hypot(b) / (u + 0.305);
+    let alpha = magnitude.powf(0.9) * (1.64 - 0.29f64.powf(vc.n)).powf(0.73);
+    (hue, alpha * (lightness / 100.0).sqrt())
+}
+
+// Linear sRGB of the Oklab color with Oklab chroma `chroma` at `angle` whose relative
+// luminance is `y`, by bisecting the Oklab lightness.
+fn with_luminance(angle: f64, chroma: f64, y: f64) -> [f64; 3] {
+    let (a, b) = (chroma * angle.cos(), chroma * angle.sin());
+    let (mut low, mut high) = (0.0, 1.0);
+    let mut linear = [0.0; 3];
+    for _ in 0..24 {
+        let l = f64::midpoint(low, high);
+        linear = oklab_to_linear(l, a, b);
+        let luminance: f64 = SRGB_TO_XYZ[1].iter().zip(linear).map(|(m, c)| m * c).sum();
+        if luminance < y {
+            low = l;
+        } else {
+            high = l;
+        }
+    }
+    linear
+}
+
+fn oklab_to_linear(lightness: f64, a: f64, b: f64) -> [f64; 3] {
+    let [long, medium, short] = [
+        0.215_803_757_3f64.mul_add(b, 0.396_337_777_4f64.mul_add(a, lightness)),
+        (-0.063_854_172_8f64).mul_add(b, (-0.105_561_345_8f64).mul_add(a, lightness)),
+        (-1.291_485_548_0f64).mul_add(b, (-0.089_484_177_5f64).mul_add(a, lightness)),
+    ]
+    .map(|cone: f64| cone.powi(3));
+    [
+        0.230_969_929_2f64
+            .mul_add(short, 4.076_741_662_1f64.mul_add(long, -3.307_711_591_3 * medium)),
+        (-0.341_319_396_5f64)
+            .mul_add(short, (-1.268_438_004_6f64).mul_add(long, 2.609_757_401_1 * medium)),
+        1.707_614_701_0f64
+            .mul_add(short, (-0.004_196_086_3f64).mul_add(long, -0.703_418_614_7 * medium)),
+    ]
+}
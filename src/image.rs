@@ -0,0 +1,224 @@
+use std::fmt::Write;
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::palette::Palette;
+use crate::preview::foreground_for;
+
+const SWATCH_W: usize = 96;
+const SWATCH_H: usize = 64;
+const RAMP_H: usize = 16;
+const MARGIN: usize = 8;
+const SCALE: usize = 2;
+
+// 5×7 glyphs, one row per byte with the leftmost pixel in bit 4.
+const GLYPHS: [(char, [u8; 7]); 17] = [
+    ('0', [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+    ('1', [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('2', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+    ('3', [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+    ('4', [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+    ('5', [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+    ('6', [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+    ('7', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+    ('9', [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+    ('A', [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('B', [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+    ('C', [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+    ('D', [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C]),
+    ('E', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+    ('F', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+    ('#', [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A]),
+];
+
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, fill: [u8; 3]) -> Self {
+        Self { width, height, pixels: vec![fill; width * height] }
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for row in y..(y + h).min(self.height) {
+            for col in x..(x + w).min(self.width) {
+                self.pixels[row * self.width + col] = color;
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: usize, y: usize, text: &str, color: [u8; 3]) {
+        for (i, ch) in text.chars().enumerate() {
+            let Some((_, rows)) = GLYPHS.iter().find(|(c, _)| *c == ch) else { continue };
+            let gx = x + i * 6 * SCALE;
+            for (r, bits) in rows.iter().enumerate() {
+                for c in 0..5 {
+                    if bits & (0x10 >> c) != 0 {
+                        self.fill_rect(gx + c * SCALE, y + r * SCALE, SCALE, SCALE, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // Binary PPM, the simplest format image viewers open.
+    pub fn ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+}
+
+// Draws one labelled swatch per color with its okhsl lightness ramp underneath.
+pub fn palette_image(palette: &Palette) -> Image {
+    let width = palette.colors.len() * (SWATCH_W + MARGIN) + MARGIN;
+    let height = MARGIN + SWATCH_H + MARGIN / 2 + RAMP_H + MARGIN;
+    let mut image = Image::new(width, height, palette.bg);
+
+    for (i, color) in palette.colors.iter().enumerate() {
+        let rgb = color.rgb;
+        let x = MARGIN + i * (SWATCH_W + MARGIN);
+        image.fill_rect(x, MARGIN, SWATCH_W, SWATCH_H, rgb);
+
+        let label = format!("#{}", color.hex());
+        let label_w = label.len() * 6 * SCALE - SCALE;
+        let label_x = x + SWATCH_W.saturating_sub(label_w) / 2;
+        let label_y = MARGIN + (SWATCH_H - 7 * SCALE) / 2;
+        image.draw_text(label_x, label_y, &label, foreground_for(rgb));
+
+        let okhsl = Okhsl::from(Oklab::from(Rgb { r: rgb[0], g: rgb[1], b: rgb[2] }));
+        let ramp_y = MARGIN + SWATCH_H + MARGIN / 2;
+        for col in 0..SWATCH_W {
+            let l = col as f32 / (SWATCH_W - 1) as f32;
+            let step = Okhsl { h: okhsl.h, s: okhsl.s, l }.to_srgb();
+            image.fill_rect(x + col, ramp_y, 1, RAMP_H, [step.r, step.g, step.b]);
+        }
+    }
+
+    image
+}
+
+// Transmits raw 24-bit pixels with the kitty graphics protocol, split into the
+// 4096-byte base64 chunks the protocol requires.
+pub fn kitty(image: &Image) -> String {
+    let raw: Vec<u8> = image.pixels.iter().flatten().copied().collect();
+    let data = base64(&raw);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let payload = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},m={more};{payload}\x1b\\",
+                image.width, image.height
+            )
+            .unwrap();
+        } else {
+            write!(out, "\x1b_Gm={more};{payload}\x1b\\").unwrap();
+        }
+    }
+    out
+}
+
+// Encodes the image as DEC sixel. Sixel terminals offer at most 256 color
+// registers, so channel precision is dropped until the distinct colors fit.
+pub fn sixel(image: &Image) -> String {
+    let mut mask = 0xFF_u8;
+    let mut palette: Vec<[u8; 3]>;
+    loop {
+        palette = image.pixels.iter().map(|p| p.map(|c| c & mask)).collect();
+        palette.sort_unstable();
+        palette.dedup();
+        if palette.len() <= 256 {
+            break;
+        }
+        mask <<= 1;
+    }
+    let indices: Vec<usize> =
+        image.pixels.iter().map(|p| palette.binary_search(&p.map(|c| c & mask)).unwrap()).collect();
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", image.width, image.height);
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let pct = |c: u8| u32::from(c) * 100 / 255;
+        write!(out, "#{i};2;{};{};{}", pct(*r), pct(*g), pct(*b)).unwrap();
+    }
+
+    for band in (0..image.height).step_by(6) {
+        let rows = band..(band + 6).min(image.height);
+        let mut used = vec![false; palette.len()];
+        for row in rows.clone() {
+            for col in 0..image.width {
+                used[indices[row * image.width + col]] = true;
+            }
+        }
+
+        for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            write!(out, "#{color}").unwrap();
+            let sixels = (0..image.width).map(|col| {
+                let bits = rows
+                    .clone()
+                    .filter(|row| indices[row * image.width + col] == color)
+                    .fold(0, |bits, row| bits | 1 << (row - band));
+                char::from(0x3F + bits)
+            });
+            push_runs(&mut out, sixels);
+            out.push('$');
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_runs(out: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut current = None;
+    let mut run = 0;
+    for ch in sixels {
+        if current == Some(ch) {
+            run += 1;
+        } else {
+            if let Some(prev) = current {
+                push_run(out, prev, run);
+            }
+            current = Some(ch);
+            run = 1;
+        }
+    }
+    if let Some(prev) = current {
+        push_run(out, prev, run);
+    }
+}
+
+fn push_run(out: &mut String, ch: char, run: usize) {
+    if run > 3 {
+        write!(out, "!{run}{ch}").unwrap();
+    } else {
+        out.extend(std::iter::repeat_n(ch, run));
+    }
+}
+
+pub fn base64(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(TABLE[(n >> (18 - 6 * i) & 0x3F) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
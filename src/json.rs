@@ -0,0 +1,221 @@
+use std::fmt::Write as _;
+
+// Just enough JSON for RPC requests and the gallery index: parsing any value,
+// and printing one back out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Self>),
+    Object(Vec<(String, Self)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut chars = text.trim().chars().peekable();
+        let value = Self::value(&mut chars)?;
+        chars.next().is_none().then_some(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        let Self::Object(fields) = self else { return None };
+        fields.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+    }
+
+    fn value(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Self> {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let value = match chars.peek()? {
+            '{' => {
+                chars.next();
+                let mut fields = Vec::new();
+                loop {
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    if fields.is_empty() && chars.next_if_eq(&'}').is_some() {
+                        break;
+                    }
+                    let Self::String(key) = Self::value(chars)? else { return None };
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    chars.next_if_eq(&':')?;
+                    fields.push((key, Self::value(chars)?));
+                    match chars.next()? {
+                        ',' => {}
+                        '}' => break,
+                        _ => return None,
+                    }
+                }
+                Self::Object(fields)
+            }
+            '[' => {
+                chars.next();
+                let mut items = Vec::new();
+                loop {
+                    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    if items.is_empty() && chars.next_if_eq(&']').is_some() {
+                        break;
+                    }
+                    items.push(Self::value(chars)?);
+                    match chars.next()? {
+                        ',' => {}
+                        ']' => break,
+                        _ => return None,
+                    }
+                }
+                Self::Array(items)
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            'n' => s.push('\n'),
+                            't' => s.push('\t'),
+                            'r' => s.push('\r'),
+                            'b' => s.push('\u{8}'),
+                            'f' => s.push('\u{c}'),
+                            'u' => {
+                                let mut code = Self::code_unit(chars)?;
+                                // Outside the BMP: a high surrogate, then `\u` and the low one.
+                                if (0xd800..0xdc00).contains(&code) {
+                                    chars.next_if_eq(&'\\')?;
+                                    chars.next_if_eq(&'u')?;
+                                    let low = Self::code_unit(chars)?;
+                                    if !(0xdc00..0xe000).contains(&low) {
+                                        return None;
+                                    }
+                                    code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                                }
+                                s.push(char::from_u32(code)?);
+                            }
+                            escaped => s.push(escaped),
+                        },
+                        c => s.push(c),
+                    }
+                }
+                Self::String(s)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) =
+                    chars.next_if(|c| !matches!(c, ',' | '}' | ']') && !c.is_whitespace())
+                {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "null" => Self::Null,
+                    "true" => Self::Bool(true),
+                    "false" => Self::Bool(false),
+                    number => Self::Number(number.parse().ok()?),
+                }
+            }
+        };
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        Some(value)
+    }
+
+    // The four hex digits after `\u`.
+    fn code_unit(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<u32> {
+        let code: String = chars.by_ref().take(4).collect();
+        u32::from_str_radix(&code, 16).ok()
+    }
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::String(s) => {
+                f.write_char('"')?;
+                for c in s.chars() {
+                    match c {
+                        '"' => f.write_str("\\\"")?,
+                        '\\' => f.write_str("\\\\")?,
+                        '\n' => f.write_str("\\n")?,
+                        c if u32::from(c) < 0x20 => write!(f, "\\u{:04x}", u32::from(c))?,
+                        c => f.write_char(c)?,
+                    }
+                }
+                f.write_char('"')
+            }
+            Self::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}:{value}", Self::String(key.clone()))?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_nested_values() {
+        let json =
+            Json::parse(r#" {"bg": "1e1e2e", "l": 65.5, "colors": [true, null, -1e2], "o": {}} "#)
+                .unwrap();
+        assert_eq!(json.get("bg"), Some(&Json::String(String::from("1e1e2e"))));
+        assert_eq!(json.get("l"), Some(&Json::Number(65.5)));
+        assert_eq!(
+            json.get("colors"),
+            Some(&Json::Array(vec![Json::Bool(true), Json::Null, Json::Number(-100.0)]))
+        );
+        assert_eq!(json.get("o"), Some(&Json::Object(Vec::new())));
+        assert_eq!(json.get("missing"), None);
+    }
+
+    #[test]
+    fn parse_rejects_malformed() {
+        for text in ["", "{", "[1,]", r#"{"a" 1}"#, r#""open"#, "[1] 2", "nul"] {
+            assert_eq!(Json::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn strings_round_trip_through_escapes() {
+        let text = "quote \" backslash \\ newline \n tab \t bell \u{7} é";
+        let printed = Json::String(String::from(text)).to_string();
+        assert_eq!(printed, r#""quote \" backslash \\ newline \n tab \u0009 bell \u0007 é""#);
+        assert_eq!(Json::parse(&printed), Some(Json::String(String::from(text))));
+        assert_eq!(Json::parse(r#""\u00e9\/""#), Some(Json::String(String::from("é/"))));
+    }
+
+    #[test]
+    fn surrogate_pairs_make_one_character() {
+        assert_eq!(Json::parse(r#""\ud83d\ude00!""#), Some(Json::String(String::from("😀!"))));
+        assert_eq!(Json::parse(r#""\uD83D\uDE00""#), Some(Json::String(String::from("😀"))));
+        for lone in [r#""\ud83d""#, r#""\ud83dx""#, r#""\ud83d\u0041""#, r#""\ude00""#] {
+            assert_eq!(Json::parse(lone), None, "{lone}");
+        }
+    }
+
+    #[test]
+    fn display_is_compact() {
+        let json = Json::Object(vec![
+            (String::from("a"), Json::Array(vec![Json::Number(1.0), Json::Bool(false)])),
+            (String::from("b"), Json::Null),
+        ]);
+        assert_eq!(json.to_string(), r#"{"a":[1,false],"b":null}"#);
+    }
+}
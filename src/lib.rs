@@ -0,0 +1,448 @@
+//! Contrast-checked color palettes in okhsl.
+//!
+//! A palette is `count` colors of one okhsl lightness and saturation with hues
+//! spread evenly from an offset. [`context::Context`] holds a background and
+//! measures WCAG 2 and APCA contrast against it, [`Palette`] is a generated
+//! palette with each color measured, [`gen_valid_combs`] finds
+//! every lightness, saturation and offset whose palette clears a
+//! [`context::Thresholds`] on that background, and [`cache`] keeps those sweeps
+//! on disk.
+//!
+//! ```
+//! use colorize::context::{Context, Thresholds};
+//!
+//! let bg = colorize::hex_to_rgb_u8("#1e1e2e").unwrap();
+//! let ctx = Context::new(bg);
+//! let count = std::num::NonZeroU16::new(6).unwrap();
+//! let palette = colorize::Palette::generate(&ctx, 70.0, 80.0, 0.0, count);
+//! for color in &palette.colors {
+//!     assert!(Thresholds::MINIMUM.passes(color.wcag, color.apca));
+//! }
+//! ```
+
+/// Bit sets of valid combinations and their cache file, `valid_combs.bin`.
+pub mod cache;
+/// Contrast against one background and the thresholds a color must clear.
+pub mod context;
+/// Color vision deficiency simulation and perceptual distance.
+pub mod cvd;
+/// The error parsing returns.
+pub mod error;
+#[cfg(feature = "gpu")]
+mod gpu;
+/// One score for a whole palette.
+pub mod grade;
+/// Material's HCT color space.
+pub mod hct;
+/// The leveled stderr log the sweep reports its progress to.
+pub mod log;
+/// Contrast metrics behind one trait, to compare with or to generate by.
+pub mod metric;
+/// Generated palettes with each color measured, serializable with the `serde`
+/// feature.
+pub mod palette;
+
+pub use error::ColorizeError;
+pub use palette::Palette;
+
+use std::num::NonZeroU16;
+
+use cache::ValidSet;
+use context::{Context, Thresholds};
+use metric::ContrastMetric;
+use okhsl::{Okhsl, Oklab, Rgb};
+
+/// A color as typed: `#fff`, `FFF`, `#1e1e2e` or `1E1E2E`, in any case, as the
+/// six uppercase digits without `#` the rest of the crate works with.
+///
+/// # Errors
+///
+/// [`ColorizeError::HexDigit`] or [`ColorizeError::HexLength`] naming the input.
+pub fn parse_hex(input: &str) -> Result<String, ColorizeError> {
+    let trimmed = input.trim();
+    let hex = trimmed.strip_prefix('#').unwrap_or(trimmed);
+    if let Some(digit) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(ColorizeError::HexDigit { input: input.to_string(), digit });
+    }
+    match hex.len() {
+        3 => Ok(hex.chars().flat_map(|c| [c, c]).collect::<String>().to_uppercase()),
+        6 => Ok(hex.to_uppercase()),
+        len => Err(ColorizeError::HexLength { input: input.to_string(), len }),
+    }
+}
+
+/// The channels of a hex color as typed, from 0 to 1.
+///
+/// # Errors
+///
+/// Like [`parse_hex`] when `hex` is not 3 or 6 hex digits.
+pub fn hex_to_rgb(hex: &str) -> Result<(f32, f32, f32), ColorizeError> {
+    let [r, g, b] = hex_to_rgb_u8(hex)?;
+    Ok((f32::from(r) / 255.0, f32::from(g) / 255.0, f32::from(b) / 255.0))
+}
+
+/// The channels of a hex color as typed.
+///
+/// # Errors
+///
+/// Like [`parse_hex`] when `hex` is not 3 or 6 hex digits.
+pub fn hex_to_rgb_u8(hex: &str) -> Result<[u8; 3], ColorizeError> {
+    let hex = parse_hex(hex)?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default();
+    Ok([channel(0), channel(2), channel(4)])
+}
+
+/// Six uppercase hex digits without `#`.
+pub fn rgb_to_hex(rgb: Rgb<u8>) -> String {
+    format!("{:02X}{:02X}{:02X}", rgb.r, rgb.g, rgb.b)
+}
+
+/// An sRGB channel from 0 to 1 with the transfer curve undone.
+pub fn linearize(v: f32) -> f32 {
+    if v <= 0.040_45 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+/// WCAG 2 relative luminance of sRGB channels from 0 to 1.
+pub fn relative_luminance(rgb: (f32, f32, f32)) -> f32 {
+    let r = linearize(rgb.0);
+    let g = linearize(rgb.1);
+    let b = linearize(rgb.2);
+    0.072_2_f32.mul_add(b, 0.212_6_f32.mul_add(r, 0.715_2 * g))
+}
+
+/// WCAG 2 contrast ratio of two relative luminances, from 1 to 21.
+pub fn wcag_contrast(l1: f32, l2: f32) -> f32 {
+    let (lighter, darker) = if l1 > l2 { (l1, l2) } else { (l2, l1) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// WCAG 2 success criteria met by a contrast ratio: 4.5 (AA) and 7 (AAA) for body
+/// text, 3 and 4.5 for large text (18pt, or 14pt bold).
+pub fn wcag_levels(ratio: f32) -> &'static str {
+    if ratio >= 7.0 {
+        "AAA"
+    } else if ratio >= 4.5 {
+        "AA, AAA large"
+    } else if ratio >= 3.0 {
+        "AA large"
+    } else {
+        "fail"
+    }
+}
+
+/// APCA Lc of `fg` text on `bg`: positive for dark on light, negative for
+/// light on dark.
+pub fn apca_contrast(fg: [u8; 3], bg: [u8; 3]) -> f64 {
+    let [fg_r, fg_g, fg_b] = fg.map(|c| (f64::from(c) / 255.0).powf(2.4));
+    let [bg_r, bg_g, bg_b] = bg.map(|c| (f64::from(c) / 255.0).powf(2.4));
+
+    apca_lc(apca_luma(fg_r, fg_g, fg_b), apca_luma(bg_r, bg_g, bg_b))
+}
+
+/// APCA screen luminance of channels already raised to the 2.4 exponent.
+pub fn apca_luma(r: f64, g: f64, b: f64) -> f64 {
+    const B_EXP: f64 = 1.414;
+    const B_THRESH: f64 = 0.022;
+
+    let y = b.mul_add(0.072_175_0, r.mul_add(0.212_672_9, g * 0.715_152_2));
+    if y >= B_THRESH { y } else { y + (B_THRESH - y).powf(B_EXP) }
+}
+
+/// APCA Lc from the luminances of text and background.
+pub fn apca_lc(fg_luma: f64, bg_luma: f64) -> f64 {
+    const R_SCALE: f64 = 1.14;
+    const W_OFFSET: f64 = 0.027;
+    const P_IN: f64 = 0.0005;
+    const P_OUT: f64 = 0.1;
+
+    let s_norm = bg_luma.powf(0.56) - fg_luma.powf(0.57);
+    let s_rev = bg_luma.powf(0.65) - fg_luma.powf(0.62);
+
+    let c = if (bg_luma - fg_luma).abs() < P_IN {
+        0.0
+    } else if fg_luma < bg_luma {
+        s_norm * R_SCALE
+    } else {
+        s_rev * R_SCALE
+    };
+
+    let s_apc = if c.abs() < P_OUT {
+        0.0
+    } else if c > 0.0 {
+        c - W_OFFSET
+    } else {
+        c + W_OFFSET
+    };
+
+    s_apc * 100.0
+}
+
+/// A rough name for the color's hue family from okhsl hue bins, so palettes are
+/// easier to talk about. Near-neutral colors are gray, black or white instead.
+pub fn color_name(rgb: [u8; 3]) -> &'static str {
+    const NAMES: [(f64, &str); 16] = [
+        (10.0, "rose"),
+        (40.0, "red"),
+        (62.0, "orange"),
+        (85.0, "amber"),
+        (112.0, "yellow"),
+        (132.0, "lime"),
+        (165.0, "green"),
+        (185.0, "teal"),
+        (220.0, "cyan"),
+        (248.0, "azure"),
+        (275.0, "blue"),
+        (305.0, "violet"),
+        (318.0, "purple"),
+        (338.0, "magenta"),
+        (355.0, "pink"),
+        (360.0, "rose"),
+    ];
+
+    let oklab = Oklab::from(Rgb { r: rgb[0], g: rgb[1], b: rgb[2] });
+    if oklab.a.hypot(oklab.b) < 0.03 {
+        return match oklab.l {
+            ..0.2 => "black",
+            0.95.. => "white",
+            _ => "gray",
+        };
+    }
+    let hue = Okhsl::from(oklab).h * 360.0;
+    NAMES.iter().find(|(end, _)| hue < *end).map_or("rose", |(_, name)| name)
+}
+
+/// Hue of the n-th of `count` colors spread evenly from `offset`, as okhsl h.
+pub fn palette_hue(offset: f32, n: i32, count: i32) -> f64 {
+    let hue_degrees = (offset + (n as f32 * 360.0 / count as f32)) % 360.0;
+    f64::from(hue_degrees / 360.0)
+}
+
+/// One point of the sweep: a palette's lightness and saturation in percent and
+/// the hue its first color starts at.
+#[derive(Debug)]
+pub struct ValidCombination {
+    pub lightness: u8,  // 0-100
+    pub saturation: u8, // 0-100
+    pub offset: u16,    // 0-359
+}
+
+/// How far apart a palette's colors must stay, as [`cvd::delta_e`]: any two of
+/// them with typical vision, and any two under each of
+/// [`cvd::Deficiency::DICHROMACIES`]. `None` leaves a distance unchecked.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Distinct {
+    pub separation: Option<f32>,
+    pub cvd: Option<f32>,
+}
+
+impl Distinct {
+    /// Whether `colors` keep both distances.
+    pub fn holds(self, colors: &[[u8; 3]]) -> bool {
+        let apart = |seen: &[[u8; 3]], min: f32| {
+            seen.iter()
+                .enumerate()
+                .all(|(i, &a)| seen[i + 1..].iter().all(|&b| cvd::delta_e(a, b) >= min))
+        };
+        self.separation.is_none_or(|min| apart(colors, min))
+            && self.cvd.is_none_or(|min| {
+                cvd::Deficiency::DICHROMACIES.iter().all(|deficiency| {
+                    deficiency.closest_pair(colors).is_none_or(|(_, _, distance)| distance >= min)
+                })
+            })
+    }
+
+    const fn is_none(self) -> bool {
+        self.separation.is_none() && self.cvd.is_none()
+    }
+}
+
+/// Where a sweep runs. The shader's float math can land a boundary point on the
+/// other side of a threshold, so the cache keeps the two apart.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Cpu,
+    Gpu,
+}
+
+impl Backend {
+    /// The GPU when built with the `gpu` feature and an adapter is found.
+    pub fn available() -> Self {
+        #[cfg(feature = "gpu")]
+        if gpu::available() {
+            return Self::Gpu;
+        }
+        Self::Cpu
+    }
+
+    /// `cpu` or `gpu`, as `cache list` shows it.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Cpu => "cpu",
+            Self::Gpu => "gpu",
+        }
+    }
+}
+
+/// Every (lightness, saturation, offset) whose `count` hues, spread evenly from
+/// the offset as the palette spreads them, all clear the minimum on `bg` and
+/// stay as far apart as `distinct` asks.
+///
+/// This sweeps the whole space and takes seconds; [`cache::load_or_gen_combs`]
+/// keeps the result on disk.
+pub fn gen_valid_combs(
+    bg: [u8; 3],
+    minimum: Thresholds,
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> ValidSet {
+    sweep_on(bg, minimum, count, distinct).0
+}
+
+// The sweep and the backend it ran on.
+pub(crate) fn sweep_on(
+    bg: [u8; 3],
+    minimum: Thresholds,
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> (ValidSet, Backend) {
+    #[cfg(feature = "gpu")]
+    if let Some(valid) = gpu::gen_valid_combs(bg, minimum, count, distinct) {
+        return (valid, Backend::Gpu);
+    }
+
+    let ctx = Context::new(bg);
+    let [r, g, b] = bg;
+    log::info(format!(
+        "Computing valid combinations for #{r:02X}{g:02X}{b:02X}... this takes a few seconds on the first run"
+    ));
+    let valid = sweep(count, distinct, |fg| minimum.passes(ctx.wcag(fg), ctx.apca(fg)));
+    (valid, Backend::Cpu)
+}
+
+/// Like [`gen_valid_combs`], but a color passes when it reaches the threshold
+/// of every metric given with it, e.g. `&[(&Weber, 0.8), (&Apca, 45.0)]`.
+///
+/// Runs on the CPU, and [`cache`] does not keep the result.
+pub fn gen_valid_combs_by(
+    bg: [u8; 3],
+    metrics: &[(&dyn ContrastMetric, f64)],
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> ValidSet {
+    sweep(count, distinct, |fg| {
+        metrics.iter().all(|(metric, threshold)| metric.passes(fg, bg, *threshold))
+    })
+}
+
+fn sweep(count: NonZeroU16, distinct: Distinct, passes: impl Fn([u8; 3]) -> bool) -> ValidSet {
+    let count = i32::from(count.get());
+    let mut valid = ValidSet::new();
+
+    for l in 0..=100 {
+        for s in 0..=100 {
+            let lightness = f32::from(l) / 100.0;
+            let saturation = f32::from(s) / 100.0;
+
+            let passes_at = |h: f64| {
+                let rgb = Okhsl { h, s: saturation, l: lightness }.to_srgb();
+                passes([rgb.r, rgb.g, rgb.b])
+            };
+            let offset_colors = |o: u16| -> Vec<[u8; 3]> {
+                (0..count)
+                    .map(|n| {
+                        let h = palette_hue(f32::from(o), n, count);
+                        let rgb = Okhsl { h, s: saturation, l: lightness }.to_srgb();
+                        [rgb.r, rgb.g, rgb.b]
+                    })
+                    .collect()
+            };
+
+            // When the hues land on whole degrees, as for any count dividing
+            // 360, each hue of the row is evaluated once and looked up for all
+            // offsets that land on it. Other counts check their hues per offset.
+            let hue_passes: Option<Vec<bool>> = (360 % count == 0)
+                .then(|| (0..360u16).map(|hue| passes_at(f64::from(hue) / 360.0)).collect());
+            let step = 360 / count as usize;
+
+            for o in 0..360 {
+                let valid_offset = hue_passes.as_ref().map_or_else(
+                    || (0..count).all(|n| passes_at(palette_hue(f32::from(o), n, count))),
+                    |hue_passes| {
+                        (0..count as usize).all(|n| hue_passes[(usize::from(o) + n * step) % 360])
+                    },
+                );
+                // Distances take every pair of colors, so they are checked only
+                // for palettes that already pass.
+                let valid_offset =
+                    valid_offset && (distinct.is_none() || distinct.holds(&offset_colors(o)));
+                if valid_offset {
+                    valid.insert(&ValidCombination { lightness: l, saturation: s, offset: o });
+                }
+            }
+        }
+        if l % 10 == 0 {
+            log::info(format!("Progress: {l}%"));
+        }
+    }
+
+    valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_normalizes() {
+        assert_eq!(parse_hex("#1e1e2e"), Ok(String::from("1E1E2E")));
+        assert_eq!(parse_hex(" 1E1E2E "), Ok(String::from("1E1E2E")));
+        assert_eq!(parse_hex("#fff"), Ok(String::from("FFFFFF")));
+        assert_eq!(parse_hex("a1c"), Ok(String::from("AA11CC")));
+    }
+
+    #[test]
+    fn parse_hex_names_the_problem() {
+        assert_eq!(
+            parse_hex("#12345g"),
+            Err(ColorizeError::HexDigit { input: String::from("#12345g"), digit: 'g' })
+        );
+        assert_eq!(
+            parse_hex("#1234"),
+            Err(ColorizeError::HexLength { input: String::from("#1234"), len: 4 })
+        );
+        assert_eq!(parse_hex(""), Err(ColorizeError::HexLength { input: String::new(), len: 0 }));
+        // Multi-byte characters are not digits rather than miscounted.
+        assert!(matches!(parse_hex("#ééé"), Err(ColorizeError::HexDigit { digit: 'é', .. })));
+    }
+
+    #[test]
+    fn hex_to_rgb_u8_reads_channels() {
+        assert_eq!(hex_to_rgb_u8("#1e1e2e"), Ok([0x1e, 0x1e, 0x2e]));
+        assert_eq!(hex_to_rgb_u8("f0a"), Ok([0xff, 0x00, 0xaa]));
+        assert!(hex_to_rgb_u8("zzz").is_err());
+        assert_eq!(hex_to_rgb("ffffff"), Ok((1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn gen_valid_combs_by_sweeps_with_any_metric() {
+        let bg = [0x1e, 0x1e, 0x2e];
+        let count = NonZeroU16::new(4).unwrap();
+        let valid = gen_valid_combs_by(bg, &[(&metric::Weber, 2.5)], count, Distinct::default());
+        assert!(!valid.is_empty());
+
+        for combo in valid.combinations().iter().step_by(97) {
+            let (s, l) = (f32::from(combo.saturation) / 100.0, f32::from(combo.lightness) / 100.0);
+            for n in 0..4 {
+                let h = palette_hue(f32::from(combo.offset), n, 4);
+                let rgb = Okhsl { h, s, l }.to_srgb();
+                assert!(metric::Weber.score([rgb.r, rgb.g, rgb.b], bg) >= 2.5);
+            }
+        }
+
+        // Thresholds of several metrics all have to hold.
+        let both = [(&metric::Weber as &dyn ContrastMetric, 2.5), (&metric::Apca, 90.0)];
+        assert!(gen_valid_combs_by(bg, &both, count, Distinct::default()).len() < valid.len());
+        let none = gen_valid_combs_by(bg, &[(&metric::Weber, 1000.0)], count, Distinct::default());
+        assert!(none.is_empty());
+    }
+}
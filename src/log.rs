@@ -0,0 +1,92 @@
+use std::fmt::{Display, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::ColorizeError;
+
+// Diagnostics, kept apart from the palette: they all go to stderr so that
+// stdout only carries what scripts consume. Warnings and progress are shown by
+// default, `-v` adds cache decisions and every written path, `-vv` every
+// threshold evaluation. `--quiet` silences all but warnings on stderr, and
+// `--log-file` appends whatever the verbosity lets through to a file either way.
+const WARN: u8 = 0;
+const INFO: u8 = 1;
+const DEBUG: u8 = 2;
+const TRACE: u8 = 3;
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(INFO);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Takes the logging flags from the command line: `-v`, `-vv`, `--quiet` and
+/// `--log-file`. They are left in place for the argument loop, which skips
+/// them. A `--log-file` that cannot be opened is an error.
+pub fn init(args: &[String]) -> Result<(), ColorizeError> {
+    for (i, arg) in args.iter().enumerate() {
+        match arg.as_str() {
+            "-v" | "--verbose" => {
+                VERBOSITY.fetch_max(DEBUG, Ordering::Relaxed);
+            }
+            "-vv" => VERBOSITY.store(TRACE, Ordering::Relaxed),
+            "-q" | "--quiet" => QUIET.store(true, Ordering::Relaxed),
+            "--log-file" => {
+                let Some(path) = args.get(i + 1) else { continue };
+                let file =
+                    OpenOptions::new().create(true).append(true).open(path).map_err(|err| {
+                        ColorizeError::InvalidValue {
+                            flag: String::from("--log-file"),
+                            value: path.clone(),
+                            expected: format!("could not open it: {err}"),
+                        }
+                    })?;
+                let _ = FILE.set(Mutex::new(file));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Shown even with `--quiet`.
+pub fn warn(message: impl Display) {
+    log(WARN, "warn", message);
+}
+
+/// Progress, shown unless `--quiet`.
+pub fn info(message: impl Display) {
+    log(INFO, "info", message);
+}
+
+/// Cache decisions and written paths, shown with `-v`.
+pub fn debug(message: impl Display) {
+    log(DEBUG, "debug", message);
+}
+
+/// Every threshold evaluation, shown with `-vv`.
+pub fn trace(message: impl Display) {
+    log(TRACE, "trace", message);
+}
+
+/// Whether `-vv` asked for trace messages, for callers that would otherwise
+/// compute them for nothing.
+pub fn tracing() -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= TRACE
+}
+
+fn log(level: u8, name: &str, message: impl Display) {
+    if level > VERBOSITY.load(Ordering::Relaxed) {
+        return;
+    }
+    if level == WARN || !QUIET.load(Ordering::Relaxed) {
+        eprintln!("{message}");
+    }
+    if let Some(file) = FILE.get() {
+        let mut lines = String::new();
+        for line in message.to_string().lines() {
+            let _ = writeln!(lines, "[{name}] {line}");
+        }
+        let _ = file.lock().unwrap().write_all(lines.as_bytes());
+    }
+}
@@ -1,151 +1,384 @@
+mod animate;
+mod ansi;
+mod apply;
+mod bench;
+mod check;
+mod cli;
+mod compare;
+mod config;
+#[cfg(windows)]
+mod console;
+mod contrast;
+mod dbus;
+mod export;
+mod fonts;
+mod format;
+mod gallery;
+mod image;
+mod json;
+mod map;
+mod material;
+mod metrics;
+mod nvim;
+mod preview;
+mod report;
+mod robustness;
+mod roles;
+mod rpc;
+mod serve;
+mod sort;
+mod state;
+mod templates;
+mod term;
+mod theme;
+mod wal;
+mod wallpaper;
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::num::NonZeroU16;
 use std::path::Path;
-use std::{env, fs};
+use std::process::{Command, Stdio};
+
+use colorize::context::{Context, Thresholds, clipped, nearest_passing, polarity};
+use colorize::{ColorizeError, Distinct, Palette};
+use colorize::{
+    apca_contrast, cache, color_name, context, cvd, gen_valid_combs, grade, hct, hex_to_rgb_u8,
+    linearize, log, metric, palette, palette_hue, parse_hex, relative_luminance, rgb_to_hex,
+    wcag_contrast, wcag_levels,
+};
+use config::Config;
+use okhsl::{Okhsl, Oklab, Rgb};
 
-use okhsl::{Okhsl, Rgb};
-
-const CACHE_FILE: &str = "valid_combs.bin";
-
-#[derive(Debug)]
-struct ValidCombination {
-    lightness: u8,  // 0-100
-    saturation: u8, // 0-100
-    offset: u16,    // 0-359
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
 }
 
-fn gen_valid_combs(bg: &str) -> Vec<ValidCombination> {
-    let bg_u8 = hex_to_rgb_u8(bg);
-    let bg_rgb = hex_to_rgb(bg);
-    let bg_lum = relative_luminance(bg_rgb);
-    let mut valid = Vec::new();
-
-    println!("Computing valid combinations... this takes a few seconds on the first run");
-
-    for l in 0..=100 {
-        for s in 0..=100 {
-            for o in 0..360 {
-                let lightness = f32::from(l) / 100.0;
-                let saturation = f32::from(s) / 100.0;
-
-                let mut all_pass = true;
-                for n in 0..6 {
-                    let hue_degrees = ((n as f32).mul_add(60.0, f32::from(o))) % 360.0;
-                    let h = f64::from(hue_degrees / 360.0);
-
-                    let okhsl = Okhsl { h, s: saturation, l: lightness };
-                    let rgb = okhsl.to_srgb();
-
-                    let fg_lum = relative_luminance((
-                        f32::from(rgb.r) / 255.0,
-                        f32::from(rgb.g) / 255.0,
-                        f32::from(rgb.b) / 255.0,
-                    ));
-                    let wcag = wcag_contrast(bg_lum, fg_lum);
-                    let apca = apca_contrast([rgb.r, rgb.g, rgb.b], bg_u8);
-
-                    if wcag < 4.5 || apca.abs() < 32.0 {
-                        all_pass = false;
-                        break;
-                    }
-                }
+fn run() -> Result<(), ColorizeError> {
+    let mut args: Vec<String> = env::args().collect();
 
-                if all_pass {
-                    valid.push(ValidCombination { lightness: l, saturation: s, offset: o });
-                }
-            }
-        }
-        if l % 10 == 0 {
-            println!("Progress: {l}%");
+    if args.get(1).is_some_and(|cmd| cmd == "history") {
+        if args.get(2).is_none_or(|cmd| cmd != "rerun") {
+            state::history(&args[2..]);
+            return Ok(());
         }
+        let rerun = state::rerun(&args[3..]);
+        args.splice(1.., rerun);
     }
 
-    valid
-}
-
-fn load_or_gen_combs(bg: &str) -> Vec<ValidCombination> {
-    let cache_path = format!("{CACHE_FILE}.{bg}");
-
-    if Path::new(&cache_path).exists() {
-        println!("Loading cached combinations...");
-        if let Ok(data) = fs::read(&cache_path) {
-            let count = data.len() / 4;
-            let mut combinations = Vec::with_capacity(count);
-            for chunk in data.chunks_exact(4) {
-                combinations.push(ValidCombination {
-                    lightness: chunk[0],
-                    saturation: chunk[1],
-                    offset: u16::from_le_bytes([chunk[2], chunk[3]]),
-                });
-            }
-            println!("Loaded {} valid combinations", combinations.len());
-            return combinations;
-        }
+    // `build [dir]` generates from the theme's palette.toml. Its flags go
+    // first so that the ones on the command line still win.
+    if args.get(1).is_some_and(|cmd| cmd == "build") {
+        let dir = match args.get(2) {
+            Some(dir) if !dir.starts_with('-') => args.remove(2),
+            _ => String::from("."),
+        };
+        let theme = theme::args(Path::new(&dir));
+        args.splice(2..2, std::iter::once(dir).chain(theme));
     }
-
-    let combinations = gen_valid_combs(bg);
-
-    let mut data = Vec::with_capacity(combinations.len() * 4);
-    for combo in &combinations {
-        data.push(combo.lightness);
-        data.push(combo.saturation);
-        data.extend_from_slice(&combo.offset.to_le_bytes());
+    // A preset's flags take its place, so flags after it still override it.
+    if let Some(i) = args.iter().position(|arg| arg == "--preset") {
+        let Some(name) = args.get(i + 1).cloned() else {
+            return Err(ColorizeError::MissingValue { flag: String::from("--preset") });
+        };
+        args.splice(i..i + 2, theme::preset(&name));
     }
-
-    if fs::write(&cache_path, data).is_ok() {
-        println!("Cached {} combinations to {}", combinations.len(), cache_path);
+    if args.get(1).is_some_and(|cmd| cmd == "init")
+        && args.get(2).is_none_or(|name| name.starts_with('-'))
+    {
+        return Err(usage("colorize init <name> [options]"));
     }
 
-    combinations
-}
+    log::init(&args)?;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    let mut bg = String::from("000000");
+    let mut backgrounds: Vec<String> = Vec::new();
     let mut saturation = 100.0;
     let mut lightness = 60.0;
     let mut offset = 0.0;
     let mut count = 6;
 
+    let mut previews = vec!["text"];
+    let mut simulate = None;
+    let mut profile: Option<cvd::Deficiency> = None;
+    let mut cvd_distinct: Option<f32> = None;
+    let mut adjacent: Option<f32> = None;
+    let mut min_separation: Option<f32> = None;
+    let mut sort: Option<sort::Order> = None;
+    let mut roles = Vec::new();
+    let mut sample = None;
+
+    let config = Config::load();
+    let mut min_wcag = config.number("min_wcag", 1.0..=21.0)?;
+    let mut min_apca = config.number("min_apca", 0.0..=108.0)?;
+    let mut min_apca_reverse = config.number("min_apca_reverse", 0.0..=108.0)?;
+
     let mut random_mode = false;
+    let mut analyze = false;
+    let mut try_it = false;
+    let mut all_ttys = false;
+    let mut apply_now = false;
+    let mut wal = false;
+    let mut dbus = false;
+    let mut color_scheme = false;
+    let mut animate = false;
+    let mut matrix = false;
+    let mut auto_fix = false;
+    let mut ui = false;
+    let mut badges = false;
+    let mut robustness = false;
+    let mut target_lc: Option<f64> = None;
+    let mut metrics: Option<metric::Metrics> = None;
+    let mut report = None;
+    let mut format: Option<String> = None;
+    let mut bundle: Vec<&str> = Vec::new();
+    let mut out = String::from("colorize-bundle");
+    let mut export_options = export::Options::default();
+    let subcommand = args.get(1).map(String::as_str).filter(|cmd| {
+        [
+            "analyze",
+            "apply",
+            "bench",
+            "build",
+            "export",
+            "from-image",
+            "generate",
+            "init",
+            "map",
+            "material",
+            "random",
+        ]
+        .contains(cmd)
+    });
+
+    if args.get(1).is_some_and(|cmd| cmd == "help") {
+        cli::help();
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "cache") {
+        return cache_command(&args[2..]);
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "term") {
+        term::print_report();
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "serve") {
+        return serve::run(&args[2..]);
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "nvim-server") {
+        nvim::run();
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|arg| arg == "--rpc") {
+        rpc::run();
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "gallery") {
+        return gallery::run(&args[2..]);
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "check") {
+        return check::run(&args[2..]);
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "compare") {
+        compare::run(&args[2..]);
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "last") {
+        return state::last(&args[2..]);
+    }
+    if args.get(1).is_some_and(|cmd| cmd == "contrast") {
+        return contrast::run(&args[2..]);
+    }
 
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "-b" | "--background" => {
-                bg.clone_from(&args[i + 1]);
-                i += 2;
+    let start = match subcommand {
+        Some("build" | "init") => 3,
+        Some("export") if args.get(2).is_some_and(|arg| !arg.starts_with('-')) => 3,
+        Some("material" | "from-image") if args.get(2).is_some_and(|arg| !arg.starts_with('-')) => {
+            3
+        }
+        Some(_) => 2,
+        None => 1,
+    };
+    let options = &args[start.min(args.len())..];
+    if options.iter().any(|arg| arg == "-h" || arg == "--help") {
+        cli::help();
+        return Ok(());
+    }
+    let mut parsed = cli::parse(options, subcommand)?;
+    // With -a the run is `analyze`, which takes fewer options.
+    if subcommand != Some("analyze") && parsed.iter().any(|(flag, _)| *flag == "--analyze") {
+        parsed = cli::parse(options, Some("analyze"))?;
+    }
+    if parsed.iter().any(|(flag, _)| *flag == "--pager") && io::stdout().is_terminal() {
+        run_in_pager(&args, &parsed);
+        return Ok(());
+    }
+    for (flag, value) in parsed {
+        match flag {
+            "--background" => {
+                for bg in value.split(',') {
+                    if bg == "auto" {
+                        backgrounds.push(String::from(bg));
+                        continue;
+                    }
+                    backgrounds.push(parse_hex(bg)?);
+                }
             }
-            "-s" | "--saturation" => {
-                saturation = args[i + 1].parse().unwrap();
-                i += 2;
+            "--saturation" => saturation = cli::number(flag, value, 0.0..=100.0)?,
+            "--lightness" => lightness = cli::number(flag, value, 0.0..=100.0)?,
+            "--offset" => offset = cli::number(flag, value, 0.0..=360.0)?,
+            "--count" => count = cli::number(flag, value, 1..=360)?,
+            "--preview" => {
+                previews = value.split(',').collect();
+                if let Some(kind) = previews.iter().find(|kind| !preview::KINDS.contains(kind)) {
+                    let expected = format!("expected one of {}", preview::KINDS.join(", "));
+                    return Err(invalid(flag, kind, &expected));
+                }
             }
-            "-l" | "--lightness" => {
-                lightness = args[i + 1].parse().unwrap();
-                i += 2;
+            "--backend-compat" => {
+                if value != "wal" {
+                    return Err(invalid(flag, value, "the only backend is wal"));
+                }
+                wal = true;
             }
-            "-o" | "--offset" => {
-                offset = args[i + 1].parse().unwrap();
-                i += 2;
+            "--bundle" => bundle = export::groups(value)?,
+            "--out" => out = value.to_string(),
+            "--xterm-256" => export_options.xterm256 = true,
+            "--dbus" => dbus = true,
+            "--set-color-scheme" => color_scheme = true,
+            "--apply" => apply_now = true,
+            "--all-ttys" => all_ttys = true,
+            "--roles" => roles = roles::parse(value, &config)?,
+            "--min-separation" => min_separation = Some(cli::number(flag, value, 0.0..=100.0)?),
+            "--adjacent" => adjacent = Some(cli::number(flag, value, 1.0..=21.0)?),
+            "--require-cvd-distinct" => {
+                cvd_distinct = Some(cli::number(flag, value, 0.0..=100.0)?);
             }
-            "-c" | "--count" => {
-                count = args[i + 1].parse().unwrap();
-                i += 2;
+            "--profile" => profile = Some(cli::parsed(flag, value)?),
+            "--simulate" => simulate = Some(cli::parsed(flag, value)?),
+            "--sort" => sort = Some(cli::parsed(flag, value)?),
+            "--sample-file" => {
+                let text = fs::read_to_string(value);
+                sample = Some(text.map_err(|err| invalid(flag, value, &err.to_string()))?);
             }
-            "-r" | "--random" => {
-                random_mode = true;
-                i += 1;
+            "--sample-text" => sample = Some(value.to_string()),
+            "--format" => {
+                if !format::FORMATS.contains(&value) {
+                    let expected = format!("expected one of {}", format::FORMATS.join(", "));
+                    return Err(invalid(flag, value, &expected));
+                }
+                format = Some(value.to_string());
+            }
+            "--report" => {
+                if !report::FORMATS.contains(&value) {
+                    let expected = format!("expected one of {}", report::FORMATS.join(", "));
+                    return Err(invalid(flag, value, &expected));
+                }
+                report = Some(value.to_string());
             }
-            "-a" | "--analyze" => {
-                analyze_colorschemes();
-                return;
+            "--lc" => target_lc = Some(cli::number(flag, value, 0.0..=108.0)?),
+            "--metrics" => metrics = Some(cli::parsed(flag, value)?),
+            "--robustness" => robustness = true,
+            "--badges" => badges = true,
+            "--ui" => ui = true,
+            "--auto-fix" => auto_fix = true,
+            "--matrix" => matrix = true,
+            "--animate" => animate = true,
+            "--try-it" => try_it = true,
+            "--random" => random_mode = true,
+            "--min-wcag" => min_wcag = Some(cli::number(flag, value, 1.0..=21.0)?),
+            "--min-apca" => min_apca = Some(cli::number(flag, value, 0.0..=108.0)?),
+            "--min-apca-reverse" => {
+                min_apca_reverse = Some(cli::number(flag, value, 0.0..=108.0)?);
             }
-            _ => i += 1,
+            "--analyze" => analyze = true,
+            // Taken by log::init, or before the options are read.
+            _ => {}
+        }
+    }
+    random_mode |= subcommand == Some("random");
+    analyze |= subcommand == Some("analyze");
+    // `export <target>` prints one fragment, `export --bundle` writes many.
+    let export_target = (subcommand == Some("export") && start == 3)
+        .then(|| export::target(&args[2]))
+        .transpose()?;
+    if subcommand == Some("export") && export_target.is_none() && bundle.is_empty() {
+        return Err(usage(
+            "colorize export <target> | --bundle terminal,editor,wm,bar [--out dir] [options]",
+        ));
+    }
+    // Structured output leaves no room for anything else on stdout.
+    let plain = format.is_none() && export_target.is_none();
+
+    // Without explicit thresholds generation keeps to the minimum and the badges
+    // ask for more; a given threshold applies to both.
+    let mut minimum = Thresholds {
+        wcag: min_wcag.unwrap_or(Thresholds::MINIMUM.wcag),
+        apca: min_apca.unwrap_or(Thresholds::MINIMUM.apca),
+        apca_reverse: min_apca_reverse.or(min_apca).unwrap_or(Thresholds::MINIMUM.apca_reverse),
+    };
+    let mut target = Thresholds {
+        wcag: min_wcag.unwrap_or(Thresholds::TARGET.wcag),
+        apca: min_apca.unwrap_or(Thresholds::TARGET.apca),
+        apca_reverse: min_apca_reverse.or(min_apca).unwrap_or(Thresholds::TARGET.apca_reverse),
+    };
+    // A low-vision profile raises both thresholds and previews through its eyes.
+    if let Some(profile) = profile {
+        minimum = profile.tighten(minimum);
+        target = profile.tighten(target);
+        simulate = simulate.or(Some(profile));
+        if plain {
+            println!(
+                "Profile {}: target WCAG {:.1} and APCA Lc {:.0}\n",
+                profile.name(),
+                target.wcag,
+                target.apca
+            );
         }
     }
 
-    let bg_rgb = hex_to_rgb(&bg);
-    let bg_lum = relative_luminance(bg_rgb);
-    let bg_u8 = hex_to_rgb_u8(&bg);
+    export_options.minimum = minimum;
+    log::debug(format!(
+        "Thresholds: minimum WCAG {} and Lc {}/{}, target WCAG {} and Lc {}/{}",
+        minimum.wcag,
+        minimum.apca,
+        minimum.apca_reverse,
+        target.wcag,
+        target.apca,
+        target.apca_reverse
+    ));
+
+    if analyze {
+        return analyze_colorschemes(target, metrics.as_ref());
+    }
+
+    if backgrounds.is_empty() {
+        backgrounds.push(String::from("000000"));
+    }
+    for bg in &mut backgrounds {
+        if bg == "auto" {
+            let rgb = term::caps().background.unwrap_or([0, 0, 0]);
+            *bg = format!("{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2]);
+        }
+    }
+    let surfaces = backgrounds.iter().map(|bg| hex_to_rgb_u8(bg)).collect::<Result<Vec<_>, _>>()?;
+
+    match subcommand {
+        Some("bench") => {
+            bench::run(surfaces[0]);
+            return Ok(());
+        }
+        Some("map") => {
+            map::run(surfaces[0], minimum, count);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let mut ctx = Context::new(surfaces[0]).with_thresholds(minimum, target);
 
     let mut has_contrast_issue = false;
 
@@ -153,11 +386,22 @@ fn main() {
         use std::collections::hash_map::RandomState;
         use std::hash::{BuildHasher, Hash, Hasher};
 
-        let valid_combos = load_or_gen_combs(&bg);
+        // Random mode only picks palettes that keep the asked distances, so
+        // that none of their colors are dropped or rejected below.
+        let distinct = Distinct { separation: min_separation, cvd: cvd_distinct };
+        let sweep_count = u16::try_from(count).ok().and_then(NonZeroU16::new).ok_or_else(|| {
+            invalid("--count", &count.to_string(), "expected a number from 1 to 360")
+        })?;
+        let required = metrics.as_ref().map(metric::Metrics::required).unwrap_or_default();
+        let valid_combos = if required.is_empty() {
+            cache::load_or_gen_combs(&surfaces, minimum, sweep_count, distinct)
+        } else {
+            sweep_by(&surfaces, minimum, &required, sweep_count, distinct)
+        };
 
         if valid_combos.is_empty() {
-            eprintln!("No valid combinations found for this background!");
-            return;
+            eprintln!("No valid combinations found for these backgrounds!");
+            return Ok(());
         }
 
         let random_state = RandomState::new();
@@ -171,235 +415,667 @@ fn main() {
         saturation = f32::from(combo.saturation);
         offset = f32::from(combo.offset);
 
-        println!("Random mode: l={lightness} s={saturation} o={offset}\n");
+        if plain {
+            println!("Random mode: l={lightness} s={saturation} o={offset}\n");
+        }
     }
 
     let s = saturation / 100.0;
     let l = lightness / 100.0;
 
-    let mut all_colors = Vec::new();
-    for n in 0..count {
-        let hue_degrees = (offset + (n as f32 * 360.0 / count as f32)) % 360.0;
-        let h = f64::from(hue_degrees / 360.0);
+    if animate {
+        offset = animate::run(&mut ctx, l, s, offset, count);
+    }
 
-        let okhsl = Okhsl { h, s, l };
-        let rgb = okhsl.to_srgb();
-        let hex = rgb_to_hex(rgb);
+    // Further backgrounds are secondary surfaces such as selections and popups;
+    // every color has to reach the target contrast on those too.
+    let secondary: Vec<Context> = surfaces[1..]
+        .iter()
+        .map(|&surface| Context::new(surface).with_thresholds(minimum, target))
+        .collect();
+
+    // `material <image>` takes its colors from the image's tonal palettes
+    // instead of spreading them around the hue wheel.
+    let image = matches!(subcommand, Some("material" | "from-image")).then(|| image_path(&args));
+    let request = Request {
+        lightness,
+        l,
+        s,
+        offset,
+        count,
+        roles: &roles,
+        target_lc,
+        ui,
+        auto_fix,
+        plain,
+        min_separation,
+    };
+    // Everything is generated before anything is reported, so that --sort
+    // orders the report as well as the exports.
+    let (mut palette, mut generated, count) = if let Some(image) = &image {
+        // The accents go through the same checks as generated colors, on the
+        // secondary surfaces too, and keep the image's surfaces.
+        let material = material::run(image, &ctx);
+        let wanted = material.rgbs().into_iter().map(|[r, g, b]| {
+            let okhsl = Okhsl::from(Oklab::from(Rgb { r, g, b }));
+            (okhsl, None)
+        });
+        let (palette, generated) = settle_colors(&request, &mut ctx, &secondary, wanted);
+        (palette.with_surfaces(material.surfaces), generated, 0)
+    } else {
+        let (palette, generated) = generate_colors(&request, &mut ctx, &secondary);
+        (palette, generated, count)
+    };
 
-        all_colors.push(hex.clone());
+    if let Some(order) = sort {
+        let indices = order.indices(&palette, &ctx);
+        palette.colors = sort::apply(palette.colors, &indices);
+        if !generated.is_empty() {
+            generated = sort::apply(generated, &indices);
+        }
+    }
 
-        let fg_lum = relative_luminance((
-            f32::from(rgb.r) / 255.0,
-            f32::from(rgb.g) / 255.0,
-            f32::from(rgb.b) / 255.0,
-        ));
-        let wcag = wcag_contrast(bg_lum, fg_lum);
-        let apca = apca_contrast([rgb.r, rgb.g, rgb.b], bg_u8);
+    let run = state::Run {
+        random: random_mode,
+        image: image.as_deref(),
+        backgrounds: &backgrounds,
+        lightness,
+        saturation,
+        offset,
+        count,
+    };
+    state::save(&run, &palette);
 
-        let wcag_pass = if wcag >= 7.0 {
-            "✅"
-        } else {
-            has_contrast_issue = true;
-            "❌"
-        };
-        let apca_pass = if apca.abs() >= 50.0 {
-            "✅"
-        } else {
+    let contexts: Vec<&Context> = std::iter::once(&ctx).chain(&secondary).collect();
+    let grade = grade::compute(&palette, &ctx, &contexts[1..]);
+
+    // Structured output has no room for the contrast report.
+    if let Some(format) = &format {
+        format::print(format, &palette, ctx.target, &grade);
+        return Ok(());
+    }
+    if let Some(target) = export_target {
+        export::print(target, &palette, export_options, &grade);
+        return Ok(());
+    }
+
+    for (i, (color, generated)) in palette.colors.iter().zip(generated).enumerate() {
+        let Generated { rgb, requested, fix, role } = generated;
+        let hex = color.hex();
+        let width = if secondary.is_empty() { 18 } else { 23 };
+        let label = format!("{:>2} #{hex} {}", i + 1, color_name(color.rgb));
+        // A role's band replaces the target for the main background.
+        let passes = print_contrast(&ctx, color.rgb, &label, width, ui);
+        if !role.map_or(passes, |(_, band)| band.contains(ctx.apca(color.rgb))) {
             has_contrast_issue = true;
-            "❌"
-        };
+        }
+        for surface in &secondary {
+            let label = format!(
+                "  on #{}",
+                rgb_to_hex(Rgb { r: surface.bg[0], g: surface.bg[1], b: surface.bg[2] })
+            );
+            if !print_contrast(surface, color.rgb, &label, width, ui) {
+                has_contrast_issue = true;
+            }
+        }
+        if let Some((name, band)) = role {
+            let apca = ctx.apca(color.rgb);
+            let mark = if band.contains(apca) { "✅" } else { "❌" };
+            println!("  {name}: Lc {:.0} for {band} {mark}", apca.abs());
+        }
+        if *hex == rgb_to_hex(rgb)
+            && let Some(shown) = clipped(requested, rgb)
+        {
+            println!("  sRGB cannot show this exactly: {} off", adjustment(requested, shown));
+        }
+        if !auto_fix && let Some(fixed) = fix {
+            let fixed_rgb = fixed.to_srgb();
+            let fixed_hex = rgb_to_hex(fixed_rgb);
+            let fg = [fixed_rgb.r, fixed_rgb.g, fixed_rgb.b];
+            let label = colorize_output(fg, &format!("#{fixed_hex}"), ctx.bg);
+            println!("  nearest passing: {label} ({})", adjustment(requested, fixed));
+        }
+    }
+
+    if let Some(min) = adjacent {
+        print_adjacent(&palette, min, &ctx);
+    }
+    if let Some(min) = cvd_distinct {
+        require_cvd_distinct(&palette, min);
+    }
 
-        let colored_hex = colorize_output(&hex, &format!("#{hex}"));
-        println!("{colored_hex} | WCAG: {wcag:.2} {wcag_pass} | APCA: {apca:.0} {apca_pass}");
+    if let Some(metrics) = &metrics {
+        metrics::print_table(metrics, &palette, &surfaces);
+    }
+    preview::render(&previews, &palette, &ctx, &surfaces, simulate, sample.as_deref());
+    if matrix {
+        preview::print_matrix(&palette);
+    }
+    if badges {
+        preview::print_badges(&palette, &ctx);
+    }
+    if robustness {
+        robustness::print(&palette, &ctx);
     }
 
-    print_sample_text(&all_colors);
+    grade.print();
+    if let Some(format) = &report {
+        report::write(
+            Path::new("colorize-report"),
+            format,
+            &palette,
+            &contexts,
+            &grade,
+            metrics.as_ref(),
+        );
+    }
 
     if has_contrast_issue {
-        println!("\nChange lightness and/or saturation for better contrast.");
+        println!("\nChange lightness and/or saturation for better contrast, or use --auto-fix.");
     }
-}
 
-fn colorize_output(hex: &str, text: &str) -> String {
-    format!(
-        "\x1b[1m\x1b[48;2;0;0;0m\x1b[38;2;{};{};{}m{}\x1b[0m",
-        u8::from_str_radix(&hex[0..2], 16).unwrap(),
-        u8::from_str_radix(&hex[2..4], 16).unwrap(),
-        u8::from_str_radix(&hex[4..6], 16).unwrap(),
-        text
-    )
-}
+    if wal {
+        wal::write(&palette);
+    }
+    templates::render(&apply::Scheme::new(&palette));
+    if subcommand == Some("export") {
+        export::bundle(&bundle, Path::new(&out), &palette, export_options);
+    }
+    if let Some(cmd @ ("build" | "init")) = subcommand {
+        let dir = Path::new(&args[2]);
+        if cmd == "init" {
+            theme::init(dir, &backgrounds, lightness, saturation, offset, count);
+        }
+        let groups = if bundle.is_empty() { export::groups("all")? } else { bundle };
+        theme::build(dir, &groups, &palette, export_options, &contexts, &grade);
+    }
+    if subcommand == Some("apply") || apply_now {
+        apply::apply(&palette, all_ttys);
+    }
+    if dbus || color_scheme {
+        dbus::announce(&palette, color_scheme);
+    }
+    if try_it {
+        apply::try_it(&palette);
+    }
 
-fn hex_to_rgb(hex: &str) -> (f32, f32, f32) {
-    let hex = hex.trim_start_matches('#');
-    let r = f32::from(u8::from_str_radix(&hex[0..2], 16).unwrap()) / 255.0;
-    let g = f32::from(u8::from_str_radix(&hex[2..4], 16).unwrap()) / 255.0;
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap();
-    (r, g, f32::from(b) / 255.0)
+    run_hooks(&config.get_list("hooks"), &palette);
+    Ok(())
 }
 
-fn hex_to_rgb_u8(hex: &str) -> [u8; 3] {
-    let hex = hex.trim_start_matches('#');
-    [
-        u8::from_str_radix(&hex[0..2], 16).unwrap(),
-        u8::from_str_radix(&hex[2..4], 16).unwrap(),
-        u8::from_str_radix(&hex[4..6], 16).unwrap(),
-    ]
+// What the hue loop generates from, besides the contexts.
+struct Request<'a> {
+    // The okhsl lightness as given, in percent, and l and s from 0 to 1.
+    lightness: f32,
+    l: f32,
+    s: f32,
+    offset: f32,
+    count: i32,
+    roles: &'a [(String, roles::Band)],
+    target_lc: Option<f64>,
+    ui: bool,
+    auto_fix: bool,
+    plain: bool,
+    min_separation: Option<f32>,
 }
 
-fn rgb_to_hex(rgb: Rgb<u8>) -> String {
-    format!("{:02X}{:02X}{:02X}", rgb.r, rgb.g, rgb.b)
+// How a generated color came about, for the report under the palette.
+struct Generated<'a> {
+    // The color as requested, before any auto-fix.
+    rgb: Rgb<u8>,
+    requested: Okhsl,
+    fix: Option<Okhsl>,
+    role: Option<&'a (String, roles::Band)>,
 }
 
-fn relative_luminance(rgb: (f32, f32, f32)) -> f32 {
-    let r = linearize(rgb.0);
-    let g = linearize(rgb.1);
-    let b = linearize(rgb.2);
-    0.072_2_f32.mul_add(b, 0.212_6_f32.mul_add(r, 0.715_2 * g))
+// The palette of `request` on the context's background, each color held to
+// the target there and on the secondary surfaces, with how each came about.
+fn generate_colors<'a>(
+    request: &Request<'a>,
+    ctx: &mut Context,
+    secondary: &[Context],
+) -> (Palette, Vec<Generated<'a>>) {
+    let Request { lightness, l, s, offset, count, .. } = *request;
+    let mut wanted = Vec::new();
+    for n in 0..count {
+        let h = palette_hue(offset, n, count);
+        let role = request.roles.get(n as usize);
+        // A color with a role aims for the middle of its band, and otherwise
+        // with --lc the lightness is solved per hue instead of fixed.
+        let l = role.map(|(_, band)| band.aim()).or(request.target_lc).map_or(l, |lc| {
+            ctx.lightness_for_lc(h, s, lc).unwrap_or_else(|| {
+                eprintln!(
+                    "Lc {lc} is out of reach at hue {:.0}°, keeping l={lightness}",
+                    h * 360.0
+                );
+                l
+            })
+        });
+        wanted.push((Okhsl { h, s, l }, role));
+    }
+    settle_colors(request, ctx, secondary, wanted)
 }
 
-fn linearize(v: f32) -> f32 {
-    if v <= 0.040_45 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
-}
+// Each wanted color checked on every surface, fixed when --auto-fix asks for
+// it and dropped when too close to an earlier one, with how each came about.
+fn settle_colors<'a>(
+    request: &Request<'a>,
+    ctx: &mut Context,
+    secondary: &[Context],
+    wanted: impl IntoIterator<Item = (Okhsl, Option<&'a (String, roles::Band)>)>,
+) -> (Palette, Vec<Generated<'a>>) {
+    let Request { ui, auto_fix, plain, .. } = *request;
+    let mut kept: Vec<(String, [u8; 3])> = Vec::new();
+    let mut generated = Vec::new();
+    for (requested, role) in wanted {
+        // Roles are held to their band and UI accents to the non-text
+        // thresholds instead of the text target.
+        let goal = |c: &Context| match role {
+            Some((_, band)) => band.thresholds(),
+            None if ui => Thresholds::NON_TEXT,
+            None => c.target,
+        };
+        let rgb = ctx.srgb(requested);
+        let hex = rgb_to_hex(rgb);
 
-fn wcag_contrast(l1: f32, l2: f32) -> f32 {
-    let (lighter, darker) = if l1 > l2 { (l1, l2) } else { (l2, l1) };
-    (lighter + 0.05) / (darker + 0.05)
-}
+        let fg = [rgb.r, rgb.g, rgb.b];
+        let contexts: Vec<&Context> = std::iter::once(&*ctx).chain(secondary).collect();
+        if log::tracing() {
+            for c in &contexts {
+                let (wcag, apca) = (c.wcag(fg), c.apca(fg));
+                log::trace(format!(
+                    "#{hex} on #{}: WCAG {wcag:.2} of {}, Lc {apca:.1} of {}: {}",
+                    rgb_to_hex(Rgb { r: c.bg[0], g: c.bg[1], b: c.bg[2] }),
+                    goal(c).wcag,
+                    goal(c).required(apca),
+                    if goal(c).passes(wcag, apca) { "pass" } else { "fail" }
+                ));
+            }
+        }
+        let fix = if contexts.iter().all(|c| goal(c).passes(c.wcag(fg), c.apca(fg))) {
+            None
+        } else {
+            nearest_passing(requested, &contexts, goal)
+        };
+        let (hex, shown) = if auto_fix && let Some(fixed) = fix {
+            let fixed_rgb = fixed.to_srgb();
+            let fixed_hex = rgb_to_hex(fixed_rgb);
+            if plain {
+                println!("#{hex} → #{fixed_hex} (auto-fix: {})", adjustment(requested, fixed));
+            }
+            (fixed_hex, [fixed_rgb.r, fixed_rgb.g, fixed_rgb.b])
+        } else {
+            (hex, fg)
+        };
 
-fn apca_contrast(fg: [u8; 3], bg: [u8; 3]) -> f64 {
-    const B_EXP: f64 = 1.414;
-    const R_SCALE: f64 = 1.14;
-    const B_THRESH: f64 = 0.022;
-    const W_OFFSET: f64 = 0.027;
-    const P_IN: f64 = 0.0005;
-    const P_OUT: f64 = 0.1;
+        // Many evenly spread hues bunch up where okhsl hues change slowly, so
+        // near-duplicates of an earlier color can be dropped.
+        if let Some(min) = request.min_separation
+            && let Some(((near, _), distance)) = kept
+                .iter()
+                .map(|kept| (kept, cvd::delta_e(kept.1, shown)))
+                .find(|(_, distance)| *distance < min)
+        {
+            if plain {
+                println!("#{hex} dropped: ΔE {distance:.1} from #{near}");
+            }
+            continue;
+        }
 
-    fn f_clamp(y: f64) -> f64 {
-        if y >= B_THRESH { y } else { y + (B_THRESH - y).powf(B_EXP) }
+        kept.push((hex, shown));
+        generated.push(Generated { rgb, requested, fix, role });
     }
+    (Palette::new(ctx, kept.into_iter().map(|(_, rgb)| rgb)), generated)
+}
 
-    fn screen_luminance(r: f64, g: f64, b: f64) -> f64 {
-        b.powf(2.4)
-            .mul_add(0.072_175_0, r.powf(2.4).mul_add(0.212_672_9, g.powf(2.4) * 0.715_152_2))
+fn invalid(flag: &str, value: &str, expected: &str) -> ColorizeError {
+    ColorizeError::InvalidValue {
+        flag: flag.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
     }
+}
 
-    let fg_f = [f64::from(fg[0]) / 255.0, f64::from(fg[1]) / 255.0, f64::from(fg[2]) / 255.0];
-    let bg_f = [f64::from(bg[0]) / 255.0, f64::from(bg[1]) / 255.0, f64::from(bg[2]) / 255.0];
+fn usage(usage: &str) -> ColorizeError {
+    ColorizeError::Usage { usage: usage.to_string() }
+}
 
-    let fg_luma = f_clamp(screen_luminance(fg_f[0], fg_f[1], fg_f[2]));
-    let bg_luma = f_clamp(screen_luminance(bg_f[0], bg_f[1], bg_f[2]));
+// Writes a file of the exports, creating its directory, or says why it could
+// not and exits.
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) {
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+    let written =
+        parent.map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(path, contents));
+    if let Err(err) = written {
+        eprintln!("Could not write {}: {err}", path.display());
+        std::process::exit(1);
+    }
+}
 
-    let s_norm = bg_luma.powf(0.56) - fg_luma.powf(0.57);
-    let s_rev = bg_luma.powf(0.65) - fg_luma.powf(0.62);
+// `cache [list|clear]`: the sweeps random mode has cached in the working
+// directory, or deletes them.
+fn cache_command(args: &[String]) -> Result<(), ColorizeError> {
+    cli::reject(args.get(1..).unwrap_or_default())?;
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            let entries = cache::entries();
+            if entries.is_empty() {
+                println!("No cached sweeps");
+            }
+            for entry in entries {
+                let [r, g, b] = entry.background;
+                let minimum = entry.minimum;
+                let distances: String =
+                    [("ΔE", entry.distinct.separation), ("CVD ΔE", entry.distinct.cvd)]
+                        .into_iter()
+                        .filter_map(|(name, min)| min.map(|min| format!("  {name} {min}")))
+                        .collect();
+                println!(
+                    "#{r:02X}{g:02X}{b:02X}  {} colors  WCAG {}  Lc {}/{}{distances}  {}  {} valid",
+                    entry.count,
+                    minimum.wcag,
+                    minimum.apca,
+                    minimum.apca_reverse,
+                    entry.backend.name(),
+                    entry.valid
+                );
+            }
+        }
+        Some("clear") => match cache::clear() {
+            Ok(true) => println!("Cleared the cached sweeps"),
+            Ok(false) => println!("No cached sweeps"),
+            Err(err) => {
+                return Err(ColorizeError::Io {
+                    action: String::from("clear the cache"),
+                    reason: err.to_string(),
+                });
+            }
+        },
+        Some(other) => {
+            return Err(ColorizeError::UnknownArgument {
+                arg: other.to_string(),
+                suggestion: None,
+            });
+        }
+    }
+    Ok(())
+}
 
-    let c = if (bg_luma - fg_luma).abs() < P_IN {
-        0.0
-    } else if fg_luma < bg_luma {
-        s_norm * R_SCALE
-    } else {
-        s_rev * R_SCALE
+// The image `material` (or `from-image`) was given, or the current wallpaper
+// when the path is left out.
+fn image_path(args: &[String]) -> String {
+    if let Some(path) = args.get(2).filter(|arg| !arg.starts_with('-')) {
+        return path.clone();
+    }
+    let Some(path) = wallpaper::detect() else {
+        eprintln!("No image given and no wallpaper found through swww, hyprpaper, feh or GNOME");
+        std::process::exit(1);
     };
+    log::info(format!("Using the current wallpaper {}", path.display()));
+    path.display().to_string()
+}
 
-    let s_apc = if c.abs() < P_OUT {
-        0.0
-    } else if c > 0.0 {
-        c - W_OFFSET
-    } else {
-        c + W_OFFSET
-    };
+// Re-runs colorize with its output piped into $PAGER. The child has no terminal
+// on stdout and does not probe it, so `auto` backgrounds are resolved up front.
+fn run_in_pager(args: &[String], parsed: &cli::Options) {
+    let pager = env::var("PAGER").unwrap_or_else(|_| String::from("less"));
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let mut pager = Command::new(program);
+    pager.args(words).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let mut pager = pager.spawn().unwrap_or_else(|err| {
+        eprintln!("Could not start the pager {program}: {err}");
+        std::process::exit(1);
+    });
+
+    // The child keeps `--pager` and ignores it, its stdout being the pager. The
+    // backgrounds are the values `parsed` took for `--background`, so a word
+    // such as `--sample-text -b` is passed on as it is.
+    let backgrounds: Vec<&str> = parsed
+        .iter()
+        .filter(|(flag, _)| *flag == "--background")
+        .map(|(_, value)| *value)
+        .collect();
+    let mut child_args = Vec::new();
+    for arg in &args[1..] {
+        if backgrounds.iter().any(|value| std::ptr::eq(*value, arg.as_str())) {
+            let resolved: Vec<String> = arg
+                .split(',')
+                .map(|bg| {
+                    let [r, g, b] = term::caps().background.unwrap_or([0, 0, 0]);
+                    if bg == "auto" { format!("{r:02X}{g:02X}{b:02X}") } else { bg.to_string() }
+                })
+                .collect();
+            child_args.push(resolved.join(","));
+        } else {
+            child_args.push(arg.clone());
+        }
+    }
 
-    s_apc * 100.0
+    // The pager sees the end of its input once the child exits or fails to
+    // start, and is waited for either way.
+    let stdout = pager.stdin.take().map_or_else(Stdio::inherit, Stdio::from);
+    let status = env::current_exe()
+        .and_then(|exe| Command::new(exe).args(child_args).stdout(stdout).spawn())
+        .and_then(|mut child| child.wait());
+    let _ = pager.wait();
+    match status {
+        Ok(status) if !status.success() => std::process::exit(status.code().unwrap_or(1)),
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("Could not run colorize under the pager: {err}");
+            std::process::exit(1);
+        }
+    }
 }
 
-pub fn print_sample_text(colors: &[String]) {
-    let text = "Lorem ipsum dolor sit amet consectetur adipiscing elit. Quisque faucibus ex \
-                sapien vitae pellentesque sem placerat. In id cursus mi pretium tellus duis \
-                convallis. Tempus leo eu aenean sed diam urna tempor. Pulvinar vivamus fringilla \
-                lacus nec metus bibendum egestas. Iaculis massa nisl malesuada lacinia integer \
-                nunc posuere. Ut hendrerit semper vel class aptent taciti sociosqu. Ad litora \
-                torquent per conubia nostra inceptos himenaeos.";
+fn colorize_output(fg: [u8; 3], text: &str, bg: [u8; 3]) -> String {
+    format!("\x1b[1m{}{}{text}\x1b[0m", ansi::bg(bg), ansi::fg(fg))
+}
 
-    let words: Vec<&str> = text.split_whitespace().collect();
+// Neighbouring hues end up next to each other in charts and diffs. Lists the
+// neighbours, the last color wrapping to the first, whose luminance ratio is
+// below `min` and so only tell apart by hue, which grayscale print loses.
+fn print_adjacent(palette: &Palette, min: f32, ctx: &Context) {
+    let colors = &palette.colors;
+    // Two colors are one pair, not a pair and its wrap-around.
+    let pairs = if colors.len() > 2 { colors.len() } else { colors.len().saturating_sub(1) };
+
+    println!("\nAdjacent luminance (at least {min}:1):");
+    let mut violations = 0;
+    for i in 0..pairs {
+        let (a, b) = (&colors[i], &colors[(i + 1) % colors.len()]);
+        let ratio = wcag_contrast(ctx.luminance(a.rgb), ctx.luminance(b.rgb));
+        if ratio < min {
+            violations += 1;
+            println!(
+                "  {} next to {}: {ratio:.2}:1",
+                colorize_output(a.rgb, &format!("#{}", a.hex()), ctx.bg),
+                colorize_output(b.rgb, &format!("#{}", b.hex()), ctx.bg)
+            );
+        }
+    }
+    if violations == 0 {
+        println!("  every neighbouring pair differs in luminance");
+    }
+}
 
-    println!("\nBold:");
-    for (i, word) in words.iter().enumerate() {
-        let color = &colors[i % colors.len()];
-        let (r, g, b) = parse_hex(color);
-        print!("\x1b[1m\x1b[38;2;{r};{g};{b}m{word}\x1b[0m ");
+// Runs the `hooks` commands from the config through `sh` once the palette is
+// out, with the colors in `COLORIZE_COLORS`, so themed programs can reload.
+fn run_hooks(hooks: &[String], palette: &Palette) {
+    let colors: Vec<String> =
+        palette.colors.iter().map(|color| format!("#{}", color.hex())).collect();
+    for hook in hooks {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("COLORIZE_COLORS", colors.join(" "))
+            .status();
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => log::warn(format!("Hook `{hook}` failed: {status}")),
+            Err(err) => log::warn(format!("Hook `{hook}` could not run: {err}")),
+        }
     }
+}
 
-    println!("\n\nNormal:");
-    for (i, word) in words.iter().enumerate() {
-        let color = &colors[i % colors.len()];
-        let (r, g, b) = parse_hex(color);
-        print!("\x1b[1m\x1b[38;2;{r};{g};{b}m{word}\x1b[0m ");
+// Exits with an error naming every deficiency under which two colors of the
+// palette come closer than `min`.
+fn require_cvd_distinct(palette: &Palette, min: f32) {
+    let rgbs = palette.rgbs();
+    let mut collided = false;
+    for deficiency in cvd::Deficiency::DICHROMACIES {
+        if let Some((i, j, distance)) = deficiency.closest_pair(&rgbs)
+            && distance < min
+        {
+            eprintln!(
+                "#{} and #{} are only ΔE {distance:.1} apart with {} (at least {min} required)",
+                palette.colors[i].hex(),
+                palette.colors[j].hex(),
+                deficiency.name()
+            );
+            collided = true;
+        }
     }
-    println!();
+    if collided {
+        eprintln!("Spread the hues, vary the lightness or use fewer colors.");
+        std::process::exit(1);
+    }
+}
+
+fn adjustment(from: Okhsl, to: Okhsl) -> String {
+    let dl = ((to.l - from.l) * 100.0).round();
+    let ds = ((to.s - from.s) * 100.0).round();
+    format!("l {dl:+}, s {ds:+}")
 }
 
-fn parse_hex(hex: &str) -> (u8, u8, u8) {
-    (
-        u8::from_str_radix(&hex[0..2], 16).unwrap(),
-        u8::from_str_radix(&hex[2..4], 16).unwrap(),
-        u8::from_str_radix(&hex[4..6], 16).unwrap(),
-    )
+// Prints the contrast of the color on the context's background, the label drawn
+// in the color on that background. Returns whether it meets the target.
+fn print_contrast(ctx: &Context, fg: [u8; 3], label: &str, width: usize, ui: bool) -> bool {
+    let wcag = ctx.wcag(fg);
+    let apca = ctx.apca(fg);
+    let apca_pass = if ctx.target.apca_passes(apca) { "✅" } else { "❌" };
+
+    let colored = colorize_output(fg, label, ctx.bg);
+    let levels = wcag_levels(wcag);
+    let pad = " ".repeat(width.saturating_sub(label.chars().count()));
+    let line = format!(
+        "{colored}{pad} | WCAG: {wcag:5.2} {levels:<14} | APCA: {apca:3.0} {apca_pass} {}",
+        polarity(apca)
+    );
+    if !ui {
+        println!("{line}");
+        return ctx.target.passes(wcag, apca);
+    }
+
+    let passes = Thresholds::NON_TEXT.passes(wcag, apca);
+    println!("{line} | UI: {}", if passes { "✅" } else { "❌" });
+    passes
 }
 
-fn analyze_colorschemes() {
+// Popular colorschemes' backgrounds and accents, measured like a generated
+// palette.
+fn popular_schemes() -> Result<Vec<(&'static str, Palette)>, ColorizeError> {
     let schemes = [
-        ("Nord", "2E3440", vec!["bf616a", "a3be8c", "ebcb8b", "81a1c1", "b48ead", "8fbcbb"]),
-        ("Dracula", "282a36", vec!["ff5555", "50fa7b", "f1fa8c", "bd93f9", "ff79c6", "8be9fd"]),
-        ("Catppuccin", "1e1e2e", vec!["f38ba8", "a6e3a1", "f9e2af", "89b4fa", "cba6f7", "94e2d5"]),
-        ("Gruvbox", "1d2021", vec!["fb4934", "b8bb26", "fabd2f", "83a598", "d3869b", "8ec07c"]),
-        ("Rosepine", "191724", vec!["eb6f92", "31748f", "f6c177", "c4a7e7", "ebbcba", "9ccfd8"]),
+        ("Nord", "2E3440", ["bf616a", "a3be8c", "ebcb8b", "81a1c1", "b48ead", "8fbcbb"]),
+        ("Dracula", "282a36", ["ff5555", "50fa7b", "f1fa8c", "bd93f9", "ff79c6", "8be9fd"]),
+        ("Catppuccin", "1e1e2e", ["f38ba8", "a6e3a1", "f9e2af", "89b4fa", "cba6f7", "94e2d5"]),
+        ("Gruvbox", "1d2021", ["fb4934", "b8bb26", "fabd2f", "83a598", "d3869b", "8ec07c"]),
+        ("Rosepine", "191724", ["eb6f92", "31748f", "f6c177", "c4a7e7", "ebbcba", "9ccfd8"]),
     ];
+    schemes
+        .into_iter()
+        .map(|(name, bg, colors)| {
+            let ctx = Context::new(hex_to_rgb_u8(bg)?);
+            let colors = colors.into_iter().map(hex_to_rgb_u8).collect::<Result<Vec<_>, _>>()?;
+            Ok((name, Palette::new(&ctx, colors)))
+        })
+        .collect()
+}
+
+// The minimum on one surface as a metric, so that sweeps with `--metrics`
+// thresholds still keep every color readable.
+struct Minimum(Context);
+
+impl metric::ContrastMetric for Minimum {
+    fn name(&self) -> &'static str {
+        "minimum"
+    }
+
+    fn score(&self, fg: [u8; 3], _bg: [u8; 3]) -> f64 {
+        self.0.apca(fg)
+    }
 
-    for (name, bg_hex, colors) in schemes {
+    fn passes(&self, fg: [u8; 3], _bg: [u8; 3], _threshold: f64) -> bool {
+        self.0.minimum.passes(self.0.wcag(fg), self.0.apca(fg))
+    }
+}
+
+// Random mode's sweep when `--metrics` sets thresholds: the combinations that
+// reach them and the minimum on every surface. These are not cached.
+fn sweep_by(
+    surfaces: &[[u8; 3]],
+    minimum: Thresholds,
+    required: &[(&dyn metric::ContrastMetric, f64)],
+    count: NonZeroU16,
+    distinct: Distinct,
+) -> Vec<colorize::ValidCombination> {
+    let mut result: Option<cache::ValidSet> = None;
+    for &bg in surfaces {
+        let floor = Minimum(Context::new(bg).with_thresholds(minimum, minimum));
+        let mut metrics = vec![(&floor as &dyn metric::ContrastMetric, 0.0)];
+        metrics.extend_from_slice(required);
+        let set = colorize::gen_valid_combs_by(bg, &metrics, count, distinct);
+        match &mut result {
+            Some(acc) => acc.intersect(&set),
+            None => result = Some(set),
+        }
+    }
+    result.map(|set| set.combinations()).unwrap_or_default()
+}
+
+fn analyze_colorschemes(
+    target: Thresholds,
+    metrics: Option<&metric::Metrics>,
+) -> Result<(), ColorizeError> {
+    for (name, palette) in popular_schemes()? {
         println!("\n{name} Analysis:");
-        println!("Background: #{bg_hex}");
-        println!("─────────────────────────────────────────────────────────────────");
-
-        let bg_rgb = hex_to_rgb(bg_hex);
-        let bg_lum = relative_luminance(bg_rgb);
-        let bg_u8 = hex_to_rgb_u8(bg_hex);
-
-        for color_hex in colors {
-            let fg_rgb = hex_to_rgb(color_hex);
-            let fg_u8 = hex_to_rgb_u8(color_hex);
-            let fg_lum = relative_luminance(fg_rgb);
-
-            let wcag = wcag_contrast(bg_lum, fg_lum);
-            let apca = apca_contrast(fg_u8, bg_u8);
-
-            let rgb = Rgb { r: fg_u8[0], g: fg_u8[1], b: fg_u8[2] };
-            let oklab = okhsl::Oklab::from(rgb);
-            let okhsl = okhsl::Okhsl::from(oklab);
-
-            let wcag_status = if wcag >= 7.0 { "✅" } else { "❌" };
-            let apca_status = if apca.abs() >= 50.0 { "✅" } else { "❌" };
-
-            let colored_hex = format!(
-                "\x1b[1m\x1b[48;2;{};{};{}m\x1b[38;2;{};{};{}m#{}\x1b[0m",
-                bg_u8[0],
-                bg_u8[1],
-                bg_u8[2], // background color
-                fg_u8[0],
-                fg_u8[1],
-                fg_u8[2], // foreground color
-                color_hex.to_uppercase()
-            );
-            println!(
-                "{} | WCAG: {:5.2} {} | APCA: {:4.0} {} | H:{:6.1}° S:{:4.1}% L:{:4.1}%",
-                colored_hex,
-                wcag,
-                wcag_status,
-                apca,
-                apca_status,
-                okhsl.h * 360.0,
-                okhsl.s * 100.0,
-                okhsl.l * 100.0
+        println!("Background: #{}", palette.background());
+        println!("{}", "─".repeat(term::width().min(65)));
+
+        let bg_u8 = palette.bg;
+        for color in &palette.colors {
+            let (wcag, apca, okhsl) = (color.wcag, color.apca, color.okhsl);
+            let levels = wcag_levels(wcag);
+            let apca_status = if target.apca_passes(apca) { "✅" } else { "❌" };
+
+            let colored_hex = colorize_output(color.rgb, &format!("#{}", color.hex()), bg_u8);
+            let contrast = format!(
+                "{colored_hex} | WCAG: {wcag:5.2} {levels:<14} | APCA: {apca:4.0} {apca_status}"
             );
+            let coherence =
+                format!("H:{:6.1}° S:{:4.1}% L:{:4.1}%", okhsl.h, okhsl.s * 100.0, okhsl.l * 100.0);
+            // The full row is 80 cells wide; narrower terminals get it on two lines.
+            if term::width() >= 80 {
+                println!("{contrast} | {coherence}");
+            } else {
+                println!("{contrast}\n        {coherence}");
+            }
+            // `--metrics` adds the palette's scores under the other measures.
+            if let Some(metrics) = metrics {
+                let scores: Vec<String> = metrics
+                    .0
+                    .iter()
+                    .map(|(metric, _)| {
+                        format!("{}: {:.2}", metric.name(), metric.score(color.rgb, bg_u8))
+                    })
+                    .collect();
+                println!("        {}", scores.join(" | "));
+            }
         }
     }
+    Ok(())
 }
@@ -0,0 +1,86 @@
+use std::fmt::Write;
+
+use okhsl::Okhsl;
+
+use crate::ansi;
+use crate::context::{Context, Thresholds};
+use crate::preview::foreground_for;
+
+const STEP: usize = 2;
+
+// Draws lightness (rows) against saturation (columns) with half blocks, two
+// rows per line. Green cells have at least one valid offset and get brighter
+// with more APCA headroom on their best offset; red cells fail everywhere and
+// darken the further they are from passing.
+pub fn run(bg: [u8; 3], minimum: Thresholds, count: i32) {
+    let ctx = Context::new(bg).with_thresholds(minimum, minimum);
+    let levels: Vec<u8> = (0..=100).rev().step_by(STEP).collect();
+    let columns: Vec<u8> = (0..=100).step_by(STEP).collect();
+
+    let grid: Vec<Vec<[u8; 3]>> = levels
+        .iter()
+        .map(|&l| columns.iter().map(|&s| cell_color(&ctx, l, s, count)).collect())
+        .collect();
+
+    let fg = ansi::fg(foreground_for(ctx.bg));
+    let bg_seq = ansi::bg(ctx.bg);
+
+    let [r, g, b] = bg;
+    println!("Valid (l, s) region on #{r:02X}{g:02X}{b:02X}, colored by worst-case APCA margin\n");
+    for (i, pair) in grid.chunks(2).enumerate() {
+        let l = levels[i * 2];
+        let label =
+            if l.is_multiple_of(20) { format!("l {l:>3} ") } else { String::from("      ") };
+        let mut line = format!("{bg_seq}{fg}{label}");
+        for (s, top) in pair[0].iter().enumerate() {
+            let bottom = pair.get(1).map_or(ctx.bg, |row| row[s]);
+            write!(line, "{}{}▀", ansi::fg(*top), ansi::bg(bottom)).unwrap();
+        }
+        println!("{line}\x1b[0m");
+    }
+
+    let width = columns.len();
+    println!("{bg_seq}{fg}      s 0{:^w$}100\x1b[0m", 50, w = width - 4);
+}
+
+fn cell_color(ctx: &Context, l: u8, s: u8, count: i32) -> [u8; 3] {
+    let lightness = f32::from(l) / 100.0;
+    let saturation = f32::from(s) / 100.0;
+
+    let mut margins = [0.0; 360];
+    let mut passes = [false; 360];
+    for hue in 0..360 {
+        let h = f64::from(hue as u16) / 360.0;
+        let rgb = Okhsl { h, s: saturation, l: lightness }.to_srgb();
+        let fg = [rgb.r, rgb.g, rgb.b];
+        let apca = ctx.apca(fg);
+        margins[hue] = apca.abs() - ctx.minimum.required(apca);
+        passes[hue] = ctx.minimum.passes(ctx.wcag(fg), ctx.apca(fg));
+    }
+
+    let mut best_valid = None::<f64>;
+    let mut best = f64::MIN;
+    for o in 0..360 {
+        // The palette's hues, to the nearest degree.
+        let count = count as usize;
+        let hues = (0..count).map(|n| (o + (n * 360 + count / 2) / count) % 360);
+        let margin = hues.clone().map(|h| margins[h]).fold(f64::MAX, f64::min);
+        best = best.max(margin);
+        if hues.into_iter().all(|h| passes[h]) {
+            best_valid = Some(best_valid.map_or(margin, |b| b.max(margin)));
+        }
+    }
+
+    let color = best_valid.map_or_else(
+        || {
+            let t = (1.0 + best / ctx.minimum.apca).clamp(0.0, 1.0) as f32;
+            Okhsl { h: 25.0 / 360.0, s: 0.7, l: 0.3f32.mul_add(t, 0.15) }
+        },
+        |margin| {
+            let t = (margin / 30.0).clamp(0.0, 1.0) as f32;
+            Okhsl { h: 145.0 / 360.0, s: 0.8, l: 0.4f32.mul_add(t, 0.4) }
+        },
+    );
+    let color = color.to_srgb();
+    [color.r, color.g, color.b]
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::process::Command;
+
+use crate::ansi;
+use crate::context::Context;
+use crate::hct::Hct;
+use crate::palette::Palette;
+use crate::preview::foreground_for;
+use crate::{colorize_output, print_contrast};
+
+const TONES: [u8; 13] = [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 95, 99, 100];
+
+// Google blue, what Material falls back to for an image without color.
+const FALLBACK_SEED: [u8; 3] = [0x42, 0x85, 0xF4];
+
+// `colorize material <image>`: the Material You tonal palettes around the
+// image's seed color. Prints every palette at the usual tones and returns the
+// accents, each at the tone nearest Material's own (80 on dark, 40 on light)
+// that reaches the target contrast, for the previews and exports to use. The
+// neutral and neutral variant palettes give its surfaces at the tones Material
+// uses for surface containers and surface variants.
+pub fn run(path: &str, ctx: &Context) -> Palette {
+    let seed = seed(&load(path));
+    let seed_hct = Hct::from_rgb(seed);
+    let hue = seed_hct.hue;
+    let palettes = [
+        ("primary", hue, seed_hct.chroma.max(48.0)),
+        ("secondary", hue, 16.0),
+        ("tertiary", (hue + 60.0) % 360.0, 24.0),
+        ("neutral", hue, 4.0),
+        ("variant", hue, 8.0),
+        ("error", 25.0, 84.0),
+    ];
+
+    let [r, g, b] = seed;
+    let seed_hex = format!("{r:02X}{g:02X}{b:02X}");
+    let label = colorize_output(seed, &format!("#{seed_hex}"), ctx.bg);
+    println!("Seed {label} (hue {hue:.0}, chroma {:.0})\n", seed_hct.chroma);
+    for (name, hue, chroma) in palettes {
+        let mut row = format!("{name:<10}");
+        for tone in TONES {
+            let rgb = Hct { hue, chroma, tone: f64::from(tone) }.to_rgb();
+            let (swatch, text) = (ansi::bg(rgb), ansi::fg(foreground_for(rgb)));
+            write!(row, "{swatch}{text}{tone:^5}").unwrap();
+        }
+        println!("{row}\x1b[0m");
+    }
+    println!();
+
+    let dark = Hct::from_rgb(ctx.bg).tone < 50.0;
+    let mut accents = Vec::new();
+    let accents_of = |(name, ..): &(&str, f64, f64)| !matches!(*name, "neutral" | "variant");
+    for (name, hue, chroma) in palettes.into_iter().filter(accents_of) {
+        // Step away from the background until the target is met; 0 and 100
+        // are black and white, as far as any palette goes.
+        let mut tone = if dark { 80 } else { 40 };
+        let mut rgb = Hct { hue, chroma, tone: f64::from(tone) }.to_rgb();
+        while !ctx.target.passes(ctx.wcag(rgb), ctx.apca(rgb)) && (1..100).contains(&tone) {
+            tone = if dark { tone + 1 } else { tone - 1 };
+            rgb = Hct { hue, chroma, tone: f64::from(tone) }.to_rgb();
+        }
+        print_contrast(ctx, rgb, &format!("{name} {tone}"), 13, false);
+        accents.push(rgb);
+    }
+    let surfaces =
+        [("neutral", if dark { 12 } else { 94 }), ("variant", if dark { 30 } else { 90 })].map(
+            |(name, tone)| {
+                let (_, hue, chroma) = palettes.iter().find(|(n, ..)| *n == name).unwrap();
+                Hct { hue: *hue, chroma: *chroma, tone: f64::from(tone) }.to_rgb()
+            },
+        );
+    Palette::new(ctx, accents).with_surfaces(surfaces.to_vec())
+}
+
+// Pixels of a binary PPM as is, anything else through ImageMagick, shrunk
+// first since the seed does not need every pixel.
+fn load(path: &str) -> Vec<[u8; 3]> {
+    let bytes = fs::read(path).unwrap_or_else(|err| {
+        eprintln!("Cannot read {path}: {err}");
+        std::process::exit(1);
+    });
+    if bytes.starts_with(b"P6") {
+        return ppm(&bytes).unwrap_or_else(|| bad_image(path));
+    }
+    let converted = ["magick", "convert"].iter().find_map(|tool| {
+        let output =
+            Command::new(tool).args([path, "-resize", "256x256>", "ppm:-"]).output().ok()?;
+        output.status.success().then_some(output.stdout)
+    });
+    let Some(converted) = converted else {
+        eprintln!("Only binary PPM images are read directly; others need ImageMagick installed");
+        std::process::exit(1);
+    };
+    ppm(&converted).unwrap_or_else(|| bad_image(path))
+}
+
+fn bad_image(path: &str) -> ! {
+    eprintln!("{path} is not an image colorize can read");
+    std::process::exit(1);
+}
+
+// P6 with 8-bit samples: four whitespace separated header fields, `#`
+// comments allowed between them, then one whitespace byte and the pixels.
+fn ppm(bytes: &[u8]) -> Option<Vec<[u8; 3]>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while fields.len() < 4 {
+        match bytes.get(pos)? {
+            b'#' => pos += bytes[pos..].iter().position(|&b| b == b'\n')?,
+            b if b.is_ascii_whitespace() => pos += 1,
+            _ => {
+                let len = bytes[pos..].iter().position(u8::is_ascii_whitespace)?;
+                fields.push(std::str::from_utf8(&bytes[pos..pos + len]).ok()?);
+                pos += len;
+            }
+        }
+    }
+    let [width, height, max] = [1, 2, 3].map(|i| fields[i].parse::<usize>().ok());
+    if fields[0] != "P6" || max? > 255 {
+        return None;
+    }
+    let pixels = bytes.get(pos + 1..pos + 1 + width? * height? * 3)?;
+    Some(pixels.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect())
+}
+
+// The most colorful hue in the image: pixels are bucketed by color, buckets
+// with a visible chroma vote for their hue weighted by population and chroma,
+// and the seed is the most populous bucket of the winning 10° slice.
+fn seed(pixels: &[[u8; 3]]) -> [u8; 3] {
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+    for pixel in pixels {
+        let (count, sum) = buckets.entry(pixel.map(|c| c >> 3)).or_default();
+        *count += 1;
+        for (total, c) in sum.iter_mut().zip(pixel) {
+            *total += u32::from(*c);
+        }
+    }
+
+    let colors: Vec<(u32, [u8; 3], Hct)> = buckets
+        .into_values()
+        .map(|(count, sum)| {
+            let rgb = sum.map(|total| (total / count) as u8);
+            (count, rgb, Hct::from_rgb(rgb))
+        })
+        .filter(|(_, _, hct)| hct.chroma >= 15.0)
+        .collect();
+
+    let slice = |hct: &Hct| (hct.hue / 10.0) as usize % 36;
+    let mut votes = [0.0; 36];
+    for (count, _, hct) in &colors {
+        votes[slice(hct)] += f64::from(*count) * hct.chroma;
+    }
+    let best = (0..36).max_by(|&a, &b| votes[a].total_cmp(&votes[b])).unwrap();
+    colors
+        .iter()
+        .filter(|(_, _, hct)| slice(hct) == best)
+        .max_by_key(|(count, ..)| *count)
+        .map_or(FALLBACK_SEED, |(_, rgb, _)| *rgb)
+}
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use crate::{apca_contrast, relative_luminance, wcag_contrast};
+
+/// A contrast measure between a text and a background color.
+///
+/// WCAG and APCA drive generation; the rest are there to compare against in
+/// `--metrics` columns, and [`gen_valid_combs_by`](crate::gen_valid_combs_by)
+/// sweeps with any of them, including one of your own.
+pub trait ContrastMetric {
+    fn name(&self) -> &'static str;
+
+    /// The contrast of `fg` text on `bg`. Signed metrics are negative for
+    /// text lighter than its background.
+    fn score(&self, fg: [u8; 3], bg: [u8; 3]) -> f64;
+
+    /// Whether `fg` on `bg` reaches `threshold`. By default the magnitude of
+    /// the score has to, whatever its sign.
+    fn passes(&self, fg: [u8; 3], bg: [u8; 3], threshold: f64) -> bool {
+        self.score(fg, bg).abs() >= threshold
+    }
+}
+
+/// WCAG 2 contrast ratio, from 1 to 21.
+pub struct Wcag;
+/// APCA Lc, from about -108 to 106.
+pub struct Apca;
+/// Luminance difference relative to the background.
+pub struct Weber;
+/// Luminance difference relative to the sum, from 0 to 1.
+pub struct Michelson;
+
+impl ContrastMetric for Wcag {
+    fn name(&self) -> &'static str {
+        "WCAG"
+    }
+
+    fn score(&self, fg: [u8; 3], bg: [u8; 3]) -> f64 {
+        f64::from(wcag_contrast(luminance(fg), luminance(bg)))
+    }
+}
+
+impl ContrastMetric for Apca {
+    fn name(&self) -> &'static str {
+        "APCA"
+    }
+
+    fn score(&self, fg: [u8; 3], bg: [u8; 3]) -> f64 {
+        apca_contrast(fg, bg)
+    }
+}
+
+// Luminance difference relative to the background, with the same 0.05 flare
+// term as WCAG so pure black stays finite.
+impl ContrastMetric for Weber {
+    fn name(&self) -> &'static str {
+        "Weber"
+    }
+
+    fn score(&self, fg: [u8; 3], bg: [u8; 3]) -> f64 {
+        let (fg, bg) = (f64::from(luminance(fg)) + 0.05, f64::from(luminance(bg)) + 0.05);
+        (fg - bg) / bg
+    }
+}
+
+impl ContrastMetric for Michelson {
+    fn name(&self) -> &'static str {
+        "Michelson"
+    }
+
+    fn score(&self, fg: [u8; 3], bg: [u8; 3]) -> f64 {
+        let (fg, bg) = (f64::from(luminance(fg)), f64::from(luminance(bg)));
+        if fg + bg == 0.0 { 0.0 } else { (fg - bg).abs() / (fg + bg) }
+    }
+}
+
+fn luminance(rgb: [u8; 3]) -> f32 {
+    relative_luminance(rgb.map(|c| f32::from(c) / 255.0).into())
+}
+
+/// A list of metrics, parsed from names such as `wcag,apca,weber,michelson`.
+/// A name can carry a threshold to sweep with, as in `weber:2.5`.
+pub struct Metrics(pub Vec<(Box<dyn ContrastMetric>, Option<f64>)>);
+
+impl Metrics {
+    /// The metrics given a threshold, as [`gen_valid_combs_by`](crate::gen_valid_combs_by)
+    /// takes them.
+    pub fn required(&self) -> Vec<(&dyn ContrastMetric, f64)> {
+        self.0
+            .iter()
+            .filter_map(|(metric, threshold)| threshold.map(|t| (metric.as_ref(), t)))
+            .collect()
+    }
+}
+
+impl FromStr for Metrics {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|entry| -> Result<(Box<dyn ContrastMetric>, Option<f64>), String> {
+                let (name, threshold) = match entry.split_once(':') {
+                    Some((name, threshold)) => {
+                        let threshold = threshold
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|t| t.is_finite())
+                            .ok_or_else(|| format!("invalid threshold '{threshold}' for {name}"))?;
+                        (name, Some(threshold))
+                    }
+                    None => (entry, None),
+                };
+                let metric: Box<dyn ContrastMetric> = match name.trim().to_lowercase().as_str() {
+                    "wcag" => Box::new(Wcag),
+                    "apca" => Box::new(Apca),
+                    "weber" => Box::new(Weber),
+                    "michelson" => Box::new(Michelson),
+                    other => {
+                        return Err(format!(
+                            "unknown metric '{other}' (wcag, apca, weber, michelson)"
+                        ));
+                    }
+                };
+                Ok((metric, threshold))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt::Write;
+
+use crate::colorize_output;
+use crate::metric::Metrics;
+use crate::palette::Palette;
+
+// One table per surface with a column for every selected metric.
+pub fn print_table(metrics: &Metrics, palette: &Palette, surfaces: &[[u8; 3]]) {
+    for bg in surfaces {
+        println!("\nMetrics on #{:02X}{:02X}{:02X}:", bg[0], bg[1], bg[2]);
+        let mut header = format!("{:8}", "");
+        for (metric, _) in &metrics.0 {
+            write!(header, "{:>11}", metric.name()).unwrap();
+        }
+        println!("{header}");
+
+        for color in &palette.colors {
+            let fg = color.rgb;
+            let mut row = colorize_output(fg, &format!("#{}", color.hex()), *bg);
+            row.push(' ');
+            for (metric, _) in &metrics.0 {
+                write!(row, "{:>11.2}", metric.score(fg, *bg)).unwrap();
+            }
+            println!("{row}");
+        }
+    }
+}
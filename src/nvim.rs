@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::io::{self, BufReader, Read, Write};
+
+use crate::apply::Scheme;
+use crate::export::highlights;
+use crate::palette::Color;
+use crate::serve;
+use crate::wcag_levels;
+
+// `colorize nvim-server`: msgpack-RPC on stdin and stdout for a Neovim plugin
+// that starts it with `jobstart({'colorize', 'nvim-server'}, {rpc = true})`.
+// `rpcrequest(chan, 'palette', {bg = '1e1e2e', l = 65, s = 80, o = 0, c = 6})`
+// pushes the highlight groups and terminal colors of that palette into the
+// editor, as notifications ahead of the response so they are in place when
+// the call returns, and answers with the colors and their contrast.
+// `rpcnotify` does the same without the answer. It ends when the editor
+// closes the channel.
+pub fn run() {
+    let mut input = BufReader::new(io::stdin().lock());
+    let mut output = io::stdout().lock();
+    while let Some(message) = Value::read(&mut input) {
+        let Value::Array(parts) = message else { continue };
+        let sent = match parts.as_slice() {
+            [Value::Int(0), Value::Int(id), Value::Str(method), Value::Array(params)] => {
+                let (notifications, error, result) = match handle(method, params) {
+                    Ok((notifications, result)) => (notifications, Value::Nil, result),
+                    Err(message) => (Vec::new(), Value::Str(message), Value::Nil),
+                };
+                let response = Value::Array(vec![Value::Int(1), Value::Int(*id), error, result]);
+                notifications.iter().chain([&response]).try_for_each(|v| v.send(&mut output))
+            }
+            [Value::Int(2), Value::Str(method), Value::Array(params)] => handle(method, params)
+                .map_or(Ok(()), |(notifications, _)| {
+                    notifications.iter().try_for_each(|v| v.send(&mut output))
+                }),
+            // Responses; nothing here sends requests that would need them.
+            _ => Ok(()),
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+// The notifications that set the palette in the editor, and the answer.
+fn handle(method: &str, params: &[Value]) -> Result<(Vec<Value>, Value), String> {
+    if method != "palette" {
+        return Err(format!("unknown method {method}"));
+    }
+    let mut values = Vec::new();
+    if let Some(Value::Map(options)) = params.first() {
+        for (key, value) in options {
+            let Value::Str(key) = key else { continue };
+            let value = match value {
+                Value::Str(s) => s.trim_start_matches('#').to_string(),
+                Value::Int(n) => n.to_string(),
+                Value::Float(n) => n.to_string(),
+                _ => continue,
+            };
+            values.push((key.as_str(), value));
+        }
+    }
+    let query: HashMap<&str, &str> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let palette = serve::from_query(&query).map_err(|err| err.to_string())?;
+    let scheme = Scheme::new(&palette);
+
+    let hex = |[r, g, b]: [u8; 3]| Value::Str(format!("#{r:02x}{g:02x}{b:02x}"));
+    let mut notifications = Vec::new();
+    let mut notify = |method: &str, args: Vec<Value>| {
+        notifications.push(Value::Array(vec![
+            Value::Int(2),
+            Value::Str(method.to_string()),
+            Value::Array(args),
+        ]));
+    };
+    for (i, color) in scheme.ansi.iter().enumerate() {
+        let name = Value::Str(format!("terminal_color_{i}"));
+        notify("nvim_set_var", vec![name, hex(*color)]);
+    }
+    for (group, fg, bg) in highlights(&scheme) {
+        let colors = [("fg", fg), ("bg", bg)]
+            .into_iter()
+            .filter_map(|(key, color)| Some((Value::Str(key.to_string()), hex(color?))))
+            .collect();
+        notify(
+            "nvim_set_hl",
+            vec![Value::Int(0), Value::Str(group.to_string()), Value::Map(colors)],
+        );
+    }
+
+    let colors = palette
+        .colors
+        .iter()
+        .map(|Color { rgb, wcag, apca, pass, .. }| {
+            Value::Map(vec![
+                (Value::Str(String::from("hex")), hex(*rgb)),
+                (Value::Str(String::from("wcag")), Value::Float(f64::from(*wcag))),
+                (Value::Str(String::from("level")), Value::Str(wcag_levels(*wcag).to_string())),
+                (Value::Str(String::from("apca")), Value::Float(*apca)),
+                (Value::Str(String::from("pass")), Value::Bool(*pass)),
+            ])
+        })
+        .collect();
+    Ok((notifications, Value::Array(colors)))
+}
+
+// The msgpack values the RPC carries. Extension types, which Neovim uses for
+// buffer, window and tabpage handles, are read and dropped as Nil.
+#[derive(Debug, PartialEq)]
+enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bin,
+    Array(Vec<Self>),
+    Map(Vec<(Self, Self)>),
+}
+
+impl Value {
+    fn read(input: &mut impl Read) -> Option<Self> {
+        let be = |buf: &[u8]| buf.iter().fold(0u64, |n, b| n << 8 | u64::from(*b));
+        let mut bytes = |n: usize| take(input, n);
+
+        let marker = bytes(1)?[0];
+        let (kind, len) = match marker {
+            0x00..=0x7f => return Some(Self::Int(i64::from(marker))),
+            0xe0..=0xff => return Some(Self::Int(i64::from(marker as i8))),
+            0x80..=0x8f => ("map", u64::from(marker & 0x0f)),
+            0x90..=0x9f => ("array", u64::from(marker & 0x0f)),
+            0xa0..=0xbf => ("str", u64::from(marker & 0x1f)),
+            0xc0 => return Some(Self::Nil),
+            0xc2 | 0xc3 => return Some(Self::Bool(marker == 0xc3)),
+            0xc4..=0xc6 => ("bin", be(&bytes(1 << (marker - 0xc4))?)),
+            0xc7..=0xc9 => ("ext", be(&bytes(1 << (marker - 0xc7))?)),
+            0xca => return Some(Self::Float(f64::from(f32::from_bits(be(&bytes(4)?) as u32)))),
+            0xcb => return Some(Self::Float(f64::from_bits(be(&bytes(8)?)))),
+            0xcc..=0xcf => return Some(Self::Int(be(&bytes(1 << (marker - 0xcc))?) as i64)),
+            0xd0..=0xd3 => {
+                let size = 1 << (marker - 0xd0);
+                let shift = 64 - 8 * size;
+                return Some(Self::Int(((be(&bytes(size)?) << shift) as i64) >> shift));
+            }
+            0xd4..=0xd8 => {
+                bytes(1 + (1 << (marker - 0xd4)))?;
+                return Some(Self::Nil);
+            }
+            0xd9..=0xdb => ("str", be(&bytes(1 << (marker - 0xd9))?)),
+            0xdc | 0xdd => ("array", be(&bytes(2 << (marker - 0xdc))?)),
+            0xde | 0xdf => ("map", be(&bytes(2 << (marker - 0xde))?)),
+            0xc1 => return None,
+        };
+        let len = usize::try_from(len).ok()?;
+        Some(match kind {
+            "str" => Self::Str(String::from_utf8_lossy(&take(input, len)?).into_owned()),
+            "bin" => {
+                skip(input, len)?;
+                Self::Bin
+            }
+            "ext" => {
+                skip(input, len + 1)?;
+                Self::Nil
+            }
+            "array" => Self::Array((0..len).map(|_| Self::read(input)).collect::<Option<_>>()?),
+            _ => Self::Map(
+                (0..len)
+                    .map(|_| Some((Self::read(input)?, Self::read(input)?)))
+                    .collect::<Option<_>>()?,
+            ),
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        let header = |out: &mut Vec<u8>, fix: u8, markers: [u8; 2], len: usize| {
+            if len < 16 {
+                out.push(fix | len as u8);
+            } else if len <= 0xffff {
+                out.push(markers[0]);
+                out.extend((len as u16).to_be_bytes());
+            } else {
+                out.push(markers[1]);
+                out.extend((len as u32).to_be_bytes());
+            }
+        };
+        match self {
+            Self::Nil | Self::Bin => out.push(0xc0),
+            Self::Bool(b) => out.push(0xc2 | u8::from(*b)),
+            Self::Int(n) if (-32..128).contains(n) => out.push(*n as u8),
+            Self::Int(n) => {
+                out.push(0xd3);
+                out.extend(n.to_be_bytes());
+            }
+            Self::Float(n) => {
+                out.push(0xcb);
+                out.extend(n.to_bits().to_be_bytes());
+            }
+            Self::Str(s) => {
+                if s.len() < 32 {
+                    out.push(0xa0 | s.len() as u8);
+                } else {
+                    out.push(0xdb);
+                    out.extend((s.len() as u32).to_be_bytes());
+                }
+                out.extend(s.as_bytes());
+            }
+            Self::Array(items) => {
+                header(out, 0x90, [0xdc, 0xdd], items.len());
+                for item in items {
+                    item.write(out);
+                }
+            }
+            Self::Map(pairs) => {
+                header(out, 0x80, [0xde, 0xdf], pairs.len());
+                for (key, value) in pairs {
+                    key.write(out);
+                    value.write(out);
+                }
+            }
+        }
+    }
+
+    fn send(&self, output: &mut impl Write) -> io::Result<()> {
+        let mut out = Vec::new();
+        self.write(&mut out);
+        output.write_all(&out)?;
+        output.flush()
+    }
+}
+
+// The next `n` bytes. The buffer grows as they arrive rather than being sized
+// from a length off the wire, which could claim gigabytes that never come.
+fn take(input: &mut impl Read, n: usize) -> Option<Vec<u8>> {
+    let mut buf = Vec::new();
+    input.take(n as u64).read_to_end(&mut buf).ok()?;
+    (buf.len() == n).then_some(buf)
+}
+
+fn skip(input: &mut impl Read, n: usize) -> Option<()> {
+    let skipped = io::copy(&mut input.take(n as u64), &mut io::sink()).ok()?;
+    (skipped == n as u64).then_some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &Value) -> Option<Value> {
+        let mut out = Vec::new();
+        value.write(&mut out);
+        Value::read(&mut out.as_slice())
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let long = "x".repeat(40);
+        let values = [
+            Value::Nil,
+            Value::Bool(true),
+            Value::Int(-32),
+            Value::Int(127),
+            Value::Int(-33),
+            Value::Int(1 << 40),
+            Value::Float(0.25),
+            Value::Str(String::from("palette")),
+            Value::Str(long),
+            Value::Array((0..20).map(Value::Int).collect()),
+            Value::Map(vec![(Value::Str(String::from("bg")), Value::Str(String::from("1e1e2e")))]),
+        ];
+        for value in values {
+            assert_eq!(round_trip(&value).as_ref(), Some(&value));
+        }
+    }
+
+    #[test]
+    fn read_decodes_other_encoders() {
+        let read = |bytes: &[u8]| Value::read(&mut &bytes[..]);
+        assert_eq!(read(&[0xcc, 0xff]), Some(Value::Int(255)));
+        assert_eq!(read(&[0xd0, 0x80]), Some(Value::Int(-128)));
+        assert_eq!(read(&[0xd1, 0xff, 0x00]), Some(Value::Int(-256)));
+        assert_eq!(read(&[0xca, 0x3f, 0x80, 0, 0]), Some(Value::Float(1.0)));
+        assert_eq!(read(&[0xd9, 2, b'h', b'i']), Some(Value::Str(String::from("hi"))));
+        assert_eq!(read(&[0xc4, 2, 1, 2]), Some(Value::Bin));
+        // A buffer handle is an ext type, which is dropped.
+        assert_eq!(read(&[0xd4, 0, 1]), Some(Value::Nil));
+        assert_eq!(read(&[0xc7, 1, 0, 1]), Some(Value::Nil));
+    }
+
+    #[test]
+    fn read_refuses_truncated_input() {
+        let read = |bytes: &[u8]| Value::read(&mut &bytes[..]);
+        assert_eq!(read(&[]), None);
+        assert_eq!(read(&[0xc1]), None);
+        assert_eq!(read(&[0xa5, b'a']), None);
+        assert_eq!(read(&[0x92, 0x01]), None);
+        // A length off the wire far beyond what arrives.
+        assert_eq!(read(&[0xdb, 0xff, 0xff, 0xff, 0xff]), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::num::NonZeroU16;
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::context::Context;
+use crate::palette_hue;
+
+/// A palette as generation leaves it: the background and the colors in order,
+/// each measured against that background.
+///
+/// Colors are kept as channels; [`Palette::background`] and [`Color::hex`]
+/// spell them out. With `serde`, channels are written as hex digits, under
+/// `background` and `hex`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    /// The background's channels.
+    #[cfg_attr(feature = "serde", serde(rename = "background", with = "hex"))]
+    pub bg: [u8; 3],
+    /// The colors in the order they were generated or given, each measured
+    /// against `bg`.
+    pub colors: Vec<Color>,
+    /// Surfaces that came with the colors, a raised one and a variant of it,
+    /// such as Material's neutral tones. Empty when exports mix their own from
+    /// the background and foreground.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub surfaces: Vec<[u8; 3]>,
+}
+
+/// One color of a palette with its contrast on the palette's background.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    /// The color's channels.
+    #[cfg_attr(feature = "serde", serde(rename = "hex", with = "hex"))]
+    pub rgb: [u8; 3],
+    /// WCAG 2 contrast ratio, from 1 to 21.
+    pub wcag: f32,
+    /// APCA Lc, negative for light text on a dark background.
+    pub apca: f64,
+    /// Whether both clear the context's target thresholds.
+    pub pass: bool,
+    pub okhsl: Coordinates,
+}
+
+/// Okhsl coordinates: hue in degrees, saturation and lightness from 0 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coordinates {
+    pub h: f64,
+    pub s: f32,
+    pub l: f32,
+}
+
+impl Palette {
+    /// Measures `colors` against the background and target of `ctx`.
+    pub fn new(ctx: &Context, colors: impl IntoIterator<Item = [u8; 3]>) -> Self {
+        let colors = colors
+            .into_iter()
+            .map(|fg| {
+                let (wcag, apca) = (ctx.wcag(fg), ctx.apca(fg));
+                let okhsl = Okhsl::from(Oklab::from(Rgb { r: fg[0], g: fg[1], b: fg[2] }));
+                Color {
+                    rgb: fg,
+                    wcag,
+                    apca,
+                    pass: ctx.target.passes(wcag, apca),
+                    okhsl: Coordinates { h: okhsl.h * 360.0, s: okhsl.s, l: okhsl.l },
+                }
+            })
+            .collect();
+        Self { bg: ctx.bg, colors, surfaces: Vec::new() }
+    }
+
+    /// The same palette with surfaces of its own.
+    #[must_use]
+    pub fn with_surfaces(mut self, surfaces: Vec<[u8; 3]>) -> Self {
+        self.surfaces = surfaces;
+        self
+    }
+
+    /// `count` colors of one okhsl lightness and saturation, in percent, with
+    /// hues spread evenly from `offset` degrees. Colors outside the sRGB gamut
+    /// are clipped by okhsl.
+    pub fn generate(
+        ctx: &Context,
+        lightness: f32,
+        saturation: f32,
+        offset: f32,
+        count: NonZeroU16,
+    ) -> Self {
+        let count = i32::from(count.get());
+        let colors = (0..count).map(|n| {
+            let h = palette_hue(offset, n, count);
+            let rgb = Okhsl { h, s: saturation / 100.0, l: lightness / 100.0 }.to_srgb();
+            [rgb.r, rgb.g, rgb.b]
+        });
+        Self::new(ctx, colors)
+    }
+
+    /// The background as six uppercase hex digits without `#`.
+    pub fn background(&self) -> String {
+        hex(self.bg)
+    }
+
+    /// The colors' hex digits in order.
+    pub fn hexes(&self) -> Vec<String> {
+        self.colors.iter().map(Color::hex).collect()
+    }
+
+    /// The colors' channels in order.
+    pub fn rgbs(&self) -> Vec<[u8; 3]> {
+        self.colors.iter().map(|color| color.rgb).collect()
+    }
+
+    /// A context on the background with the default thresholds.
+    pub fn context(&self) -> Context {
+        Context::new(self.bg)
+    }
+}
+
+impl Color {
+    /// Six uppercase hex digits without `#`, like [`Palette::background`].
+    pub fn hex(&self) -> String {
+        hex(self.rgb)
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("{r:02X}{g:02X}{b:02X}")
+}
+
+// Channels as the hex digits `Palette::background` and `Color::hex` give, read
+// back with or without `#`.
+#[cfg(feature = "serde")]
+mod hex {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(rgb: &[u8; 3], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::hex(*rgb))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 3], D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        crate::hex_to_rgb_u8(&hex).map_err(de::Error::custom)
+    }
+}
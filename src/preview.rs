@@ -0,0 +1,800 @@
+use std::fmt::Write;
+
+use okhsl::Rgb;
+
+use crate::apply::Scheme;
+use crate::context::Context;
+use crate::cvd::Deficiency;
+use crate::palette::{Color, Palette};
+use crate::{ansi, apca_contrast, fonts, image, term, wcag_levels};
+
+// The previews `--preview` takes.
+pub const KINDS: [&str; 12] = [
+    "swatch", "text", "code", "terminal", "ui", "wheel", "grid", "gradient", "fonts", "image",
+    "kitty", "sixel",
+];
+
+// With `simulate` set, the text and swatch previews pair every surface and
+// color with its color vision deficiency simulation. `kinds` are of `KINDS`.
+pub fn render(
+    kinds: &[&str],
+    palette: &Palette,
+    ctx: &Context,
+    surfaces: &[[u8; 3]],
+    simulate: Option<Deficiency>,
+    sample: Option<&str>,
+) {
+    for &kind in kinds {
+        match kind {
+            "swatch" => print_swatches(palette, ctx, simulate),
+            "text" => print_sample_text(palette, surfaces, simulate, sample),
+            "code" => print_code(palette),
+            "terminal" => print_terminal(palette),
+            "ui" => print_ui(palette),
+            "wheel" => print_wheel(palette, ctx),
+            "grid" => print_grid(palette),
+            "gradient" => print_gradients(palette),
+            "fonts" => fonts::print_guidance(palette, ctx),
+            "image" | "kitty" | "sixel" => print_image(kind, palette),
+            other => unreachable!("unknown preview {other}"),
+        }
+    }
+}
+
+// One column of the text sample: a surface and the palette as drawn on it.
+struct Column {
+    label: String,
+    bg: [u8; 3],
+    colors: Vec<[u8; 3]>,
+}
+
+// `sample` replaces the lorem ipsum, e.g. with text in the user's own language.
+pub fn print_sample_text(
+    palette: &Palette,
+    surfaces: &[[u8; 3]],
+    simulate: Option<Deficiency>,
+    sample: Option<&str>,
+) {
+    const LOREM: &str = "Lorem ipsum dolor sit amet consectetur adipiscing elit. Quisque faucibus ex \
+                sapien vitae pellentesque sem placerat. In id cursus mi pretium tellus duis \
+                convallis. Tempus leo eu aenean sed diam urna tempor. Pulvinar vivamus fringilla \
+                lacus nec metus bibendum egestas. Iaculis massa nisl malesuada lacinia integer \
+                nunc posuere. Ut hendrerit semper vel class aptent taciti sociosqu. Ad litora \
+                torquent per conubia nostra inceptos himenaeos.";
+
+    let words: Vec<&str> = sample.unwrap_or(LOREM).split_whitespace().collect();
+    let rgbs = palette.rgbs();
+
+    let mut columns = Vec::new();
+    for bg in surfaces {
+        let label = format!("#{:02X}{:02X}{:02X}", bg[0], bg[1], bg[2]);
+        columns.push(Column { label: label.clone(), bg: *bg, colors: rgbs.clone() });
+        if let Some(cvd) = simulate {
+            columns.push(Column {
+                label: format!("{label} {}", cvd.name()),
+                bg: cvd.simulate(*bg),
+                colors: rgbs.iter().map(|rgb| cvd.simulate(*rgb)).collect(),
+            });
+        }
+    }
+
+    // Weight and decoration change perceived contrast as much as the color does.
+    for (name, style) in [
+        ("Bold", "\x1b[1m"),
+        ("Normal", ""),
+        ("Dim", "\x1b[2m"),
+        ("Italic", "\x1b[3m"),
+        ("Underline", "\x1b[4m"),
+    ] {
+        println!("\n{name}:");
+        print_text_block(&words, &columns, style);
+    }
+}
+
+// Wraps the words into fixed-width blocks painted with the background colors,
+// so the sample reads the same as it would inside a themed terminal or editor.
+// Several columns are laid out side by side with the same line breaks, as many
+// per row as the terminal width allows.
+fn print_text_block(words: &[&str], columns: &[Column], style: &str) {
+    const MIN_WIDTH: usize = 24;
+
+    // Every column adds a cell of padding on both sides and a two-cell gap.
+    let available = term::width() + 2;
+    let per_row = (available / (MIN_WIDTH + 4)).clamp(1, columns.len());
+    let labelled = columns.len() > 1;
+
+    for (i, group) in columns.chunks(per_row).enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let width = (available / group.len()).saturating_sub(4).clamp(MIN_WIDTH, 72);
+        print_columns(words, group, style, width, labelled);
+    }
+}
+
+fn print_columns(words: &[&str], columns: &[Column], style: &str, width: usize, labelled: bool) {
+    let blank = " ".repeat(width + 2);
+    let blocks: Vec<Vec<(String, usize)>> =
+        columns.iter().map(|column| wrap_words(words, &column.colors, style, width)).collect();
+
+    if labelled {
+        let labels: Vec<String> =
+            columns.iter().map(|column| format!("{:<w$}", column.label, w = width + 2)).collect();
+        println!("{}", labels.join("  "));
+    }
+
+    let bg_seqs: Vec<String> = columns.iter().map(|column| ansi::bg(column.bg)).collect();
+    let blank_row: Vec<String> = bg_seqs.iter().map(|seq| format!("{seq}{blank}\x1b[0m")).collect();
+
+    println!("{}", blank_row.join("  "));
+    for n in 0..blocks[0].len() {
+        let cells: Vec<String> = blocks
+            .iter()
+            .zip(&bg_seqs)
+            .map(|(lines, seq)| {
+                let (line, len) = &lines[n];
+                format!("{seq} {line}{} \x1b[0m", " ".repeat(width.saturating_sub(*len)))
+            })
+            .collect();
+        println!("{}", cells.join("  "));
+    }
+    println!("{}", blank_row.join("  "));
+}
+
+fn wrap_words(
+    words: &[&str],
+    colors: &[[u8; 3]],
+    style: &str,
+    width: usize,
+) -> Vec<(String, usize)> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_len = 0;
+
+    for (i, word) in words.iter().enumerate() {
+        let color = colors[i % colors.len()];
+        for piece in split_to_width(word, width) {
+            let piece_len = display_width(piece);
+            if line_len > 0 && line_len + 1 + piece_len > width {
+                lines.push((std::mem::take(&mut line), line_len));
+                line_len = 0;
+            }
+            if line_len > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+
+            write!(line, "{style}{}{piece}\x1b[22;23;24m", ansi::fg(color)).unwrap();
+            line_len += piece_len;
+        }
+    }
+
+    if line_len > 0 {
+        lines.push((line, line_len));
+    }
+    lines
+}
+
+// Breaks a word wider than a line, such as unspaced CJK text, into line-sized pieces.
+fn split_to_width(word: &str, width: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut piece_len = 0;
+    for (i, ch) in word.char_indices() {
+        let ch_len = char_width(ch);
+        if piece_len + ch_len > width && piece_len > 0 {
+            pieces.push(&word[start..i]);
+            start = i;
+            piece_len = 0;
+        }
+        piece_len += ch_len;
+    }
+    pieces.push(&word[start..]);
+    pieces
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// Terminal cells taken by a character: two for East Asian wide and fullwidth
+// forms and emoji, none for combining marks.
+const fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+// Snippets are marked up as «role:text» so the braces of the code itself need no
+// escaping. Roles map onto palette slots in generation order, which lines up with
+// the usual red/orange/green/cyan/blue/magenta layout at offset 0.
+const RUST_SNIPPET: &str = "\
+«c:// Parse a hex color into channels»
+«k:fn» «f:parse_hex»(hex: &«t:str») -> «t:Option»<[«t:u8»; «n:3»]> {
+    «k:let» hex = hex.«f:trim_start_matches»(«s:'#'»);
+    «k:if» hex.«f:len»() != «n:6» { «k:return» «t:None»; }
+    «m:println!»(«s:\"parsing {hex}\"»);
+    «t:Some»([«n:0», «n:2», «n:4»].«f:map»(|i| «t:u8»::«f:from_str_radix»(&hex[i..i + «n:2»], «n:16»).«f:ok»()?))
+}";
+
+const PYTHON_SNIPPET: &str = "\
+«m:@dataclass»
+«k:class» «t:Swatch»:
+    name: «t:str» = «s:\"accent\"»
+    lightness: «t:float» = «n:0.65»  «c:# okhsl L»
+
+    «k:def» «f:hex»(self) -> «t:str»:
+        «k:return» «s:f\"#{self.name}\"» «k:if» self.lightness > «n:0» «k:else» «t:None»";
+
+const JSON_SNIPPET: &str = "\
+{
+    «f:\"name\"»: «s:\"colorize\"»,
+    «f:\"contrast\"»: { «f:\"wcag\"»: «n:7.12», «f:\"apca\"»: «n:-68» },
+    «f:\"random\"»: «k:true»,
+    «f:\"parent\"»: «k:null»
+}";
+
+pub fn print_code(palette: &Palette) {
+    let bg = palette.bg;
+    let colors = palette.rgbs();
+    let fg = foreground_for(bg);
+    let comment = mix(fg, bg, 0.45);
+
+    let role_style = |role: char| {
+        let slot = match role {
+            'k' => 0,
+            'n' => 1,
+            's' => 2,
+            't' => 3,
+            'f' => 4,
+            'm' => 5,
+            'c' => return (comment, "\x1b[3m"),
+            _ => return (fg, ""),
+        };
+        (colors[slot % colors.len()], "")
+    };
+
+    for (name, snippet) in
+        [("Rust", RUST_SNIPPET), ("Python", PYTHON_SNIPPET), ("JSON", JSON_SNIPPET)]
+    {
+        println!("\n{name}:");
+        print_marked(snippet, bg, role_style);
+    }
+}
+
+// Renders «role:text» markup as a block on the background. `role_style` maps a
+// role letter to its color and any SGR attributes; unmarked text uses role 'p'.
+fn print_marked(snippet: &str, bg: [u8; 3], role_style: impl Fn(char) -> ([u8; 3], &'static str)) {
+    let mut lines = Vec::new();
+    for source_line in snippet.lines() {
+        let mut line = String::new();
+        let mut line_len = 0;
+        let mut rest = source_line;
+
+        while !rest.is_empty() {
+            let (role, text, tail) = match rest.find('«') {
+                Some(0) => {
+                    let end = rest.find('»').unwrap();
+                    let inner = &rest['«'.len_utf8()..end];
+                    let role = inner.chars().next().unwrap();
+                    (role, &inner[2..], &rest[end + '»'.len_utf8()..])
+                }
+                Some(start) => ('p', &rest[..start], &rest[start..]),
+                None => ('p', rest, ""),
+            };
+
+            let (rgb, style) = role_style(role);
+            write!(line, "{style}{}{text}\x1b[22;23;24m", ansi::fg(rgb)).unwrap();
+            line_len += text.chars().count();
+            rest = tail;
+        }
+
+        lines.push((line, line_len));
+    }
+
+    let width = lines.iter().map(|(_, len)| *len).max().unwrap_or(0) + 2;
+    let bg_seq = ansi::bg(bg);
+    let blank = " ".repeat(width + 2);
+    println!("{bg_seq}{blank}\x1b[0m");
+    for (line, len) in lines {
+        println!("{bg_seq} {line}{} \x1b[0m", " ".repeat(width - len));
+    }
+    println!("{bg_seq}{blank}\x1b[0m");
+}
+
+const SHELL_SESSION: &str = "\
+«g:user»@«g:host» «b:~/colorize» «m:(main)» «y:$» ls --color
+«r:archive.tar.gz»  «B:assets»  Cargo.toml  «x:install.sh»  «c:latest -> assets/v2»  «m:logo.png»  README.md  «B:src»
+«g:user»@«g:host» «b:~/colorize» «m:(main)» «y:$» git diff
+«W:diff --git a/src/main.rs b/src/main.rs»
+«W:--- a/src/main.rs»
+«W:+++ b/src/main.rs»
+«c:@@ -101,7 +101,7 @@» fn main() {
+     let mut saturation = 100.0;
+«r:-    let mut lightness = 60.0;»
+«g:+    let mut lightness = 65.0;»
+     let mut offset = 0.0;
+«g:user»@«g:host» «b:~/colorize» «m:(main)» «y:$» cargo build
+   «d:Compiling colorize v0.1.0 (/home/user/colorize)»
+«R:error[E0308]»«W:: mismatched types»
+  «b:-->» src/main.rs:14:22
+   «b:|»
+«b:14 |»     let count: u8 = \"six\";
+   «b:|»                --    «R:^^^^^» «R:expected `u8`, found `&str`»
+«Y:warning»«W:: unused variable: `offset`»
+«g:user»@«g:host» «b:~/colorize» «m:(main)» «r:[1]» «y:$» ";
+
+// Maps the palette onto the six ANSI hues in slot order (red, green, yellow,
+// blue, magenta, cyan). Pairs are matched greedily by okhsl hue distance so no
+// color fills two slots while unused ones remain; small palettes reuse colors.
+pub fn ansi_hues(rgbs: &[[u8; 3]], fallback: [u8; 3]) -> [[u8; 3]; 6] {
+    ansi_slots(rgbs).map(|slot| slot.map_or(fallback, |color| rgbs[color]))
+}
+
+// The index of the color in each ANSI slot, None when there are no colors.
+pub fn ansi_slots(rgbs: &[[u8; 3]]) -> [Option<usize>; 6] {
+    const TARGETS: [f64; 6] = [25.0, 145.0, 100.0, 260.0, 330.0, 195.0];
+
+    let hues: Vec<f64> = rgbs.iter().map(|rgb| hue_of(*rgb)).collect();
+
+    let mut pairs: Vec<(usize, usize)> =
+        (0..6).flat_map(|slot| (0..rgbs.len()).map(move |color| (slot, color))).collect();
+    pairs.sort_by(|a, b| {
+        hue_distance(hues[a.1], TARGETS[a.0]).total_cmp(&hue_distance(hues[b.1], TARGETS[b.0]))
+    });
+
+    let mut slots = [None; 6];
+    let mut used = vec![false; rgbs.len()];
+    for (slot, color) in pairs {
+        if slots[slot].is_none() && (!used[color] || used.iter().all(|u| *u)) {
+            slots[slot] = Some(color);
+            used[color] = true;
+        }
+    }
+    slots
+}
+
+pub fn print_terminal(palette: &Palette) {
+    let bg = palette.bg;
+    let fg = foreground_for(bg);
+    let dim = mix(fg, bg, 0.45);
+    let [red, green, yellow, blue, magenta, cyan] = ansi_hues(&palette.rgbs(), fg);
+
+    let role_style = |role: char| match role {
+        'r' => (red, ""),
+        'R' => (red, "\x1b[1m"),
+        'y' => (yellow, ""),
+        'Y' => (yellow, "\x1b[1m"),
+        'g' => (green, ""),
+        'c' => (cyan, ""),
+        'b' => (blue, ""),
+        'B' => (blue, "\x1b[1m"),
+        'm' => (magenta, ""),
+        'x' => (green, "\x1b[1m"),
+        'd' => (dim, ""),
+        'W' => (fg, "\x1b[1m"),
+        _ => (fg, ""),
+    };
+
+    println!("\nTerminal:");
+    print_marked(SHELL_SESSION, bg, role_style);
+}
+
+// A run of styled cells whose printed width is tracked alongside the escapes.
+struct Cells {
+    text: String,
+    len: usize,
+}
+
+impl Cells {
+    const fn new() -> Self {
+        Self { text: String::new(), len: 0 }
+    }
+
+    fn push(&mut self, text: &str, fg: [u8; 3], bg: [u8; 3], style: &str) {
+        write!(self.text, "{}{}{style}{text}\x1b[22m", ansi::bg(bg), ansi::fg(fg)).unwrap();
+        self.len += text.chars().count();
+    }
+
+    fn pad(&mut self, width: usize, bg: [u8; 3]) {
+        let fill = " ".repeat(width.saturating_sub(self.len));
+        self.push(&fill, bg, bg, "");
+    }
+}
+
+// Mock window chrome for judging the non-text roles: borders, tabs, a selected
+// list item, buttons and a statusline. Surfaces are tints of the background and
+// the first palette color is the accent.
+pub fn print_ui(palette: &Palette) {
+    const WIDTH: usize = 58;
+
+    let bg = palette.bg;
+    let colors = palette.rgbs();
+    let fg = foreground_for(bg);
+    let surface = mix(bg, fg, 0.06);
+    let overlay = mix(bg, fg, 0.14);
+    let border = mix(bg, fg, 0.35);
+    let muted = mix(fg, bg, 0.45);
+    let Some(&accent) = colors.first() else { return };
+    let on_accent = foreground_for(accent);
+    let [red, ..] = ansi_hues(&colors, fg);
+
+    let framed = |cells: Cells| {
+        let mut line = Cells::new();
+        line.push("│", border, bg, "");
+        line.text.push_str(&cells.text);
+        line.len += cells.len;
+        line.pad(WIDTH + 1, bg);
+        line.push("│", border, bg, "");
+        println!("{}\x1b[0m", line.text);
+    };
+
+    println!("\nUI:");
+    let mut top = Cells::new();
+    top.push("╭─ ", border, bg, "");
+    top.push("colorize", accent, bg, "\x1b[1m");
+    top.push(&format!(" {}╮", "─".repeat(WIDTH - 11)), border, bg, "");
+    println!("{}\x1b[0m", top.text);
+
+    let mut tabs = Cells::new();
+    for (i, name) in ["palette", "preview", "export"].iter().enumerate() {
+        if i == 0 {
+            tabs.push(&format!(" {name} "), accent, overlay, "\x1b[1m");
+        } else {
+            tabs.push(&format!(" {name} "), muted, surface, "");
+        }
+    }
+    tabs.pad(WIDTH, surface);
+    framed(tabs);
+    println!("{}├{}┤\x1b[0m", ansi::bg(bg) + &ansi::fg(border), "─".repeat(WIDTH));
+
+    for (i, item) in ["Backgrounds", "Swatches", "Contrast", "Export"].iter().enumerate() {
+        let dot = colors[i % colors.len()];
+        let mut row = Cells::new();
+        if i == 1 {
+            row.push(&format!(" ▶ ● {item}"), on_accent, accent, "\x1b[1m");
+            row.pad(WIDTH, accent);
+        } else {
+            row.push("   ", fg, bg, "");
+            row.push("●", dot, bg, "");
+            row.push(&format!(" {item}"), fg, bg, "");
+        }
+        framed(row);
+    }
+    framed(Cells::new());
+
+    let mut buttons = Cells::new();
+    buttons.pad(WIDTH - 33, bg);
+    buttons.push("  Apply  ", on_accent, accent, "\x1b[1m");
+    buttons.push("  ", fg, bg, "");
+    buttons.push("  Cancel  ", fg, overlay, "");
+    buttons.push("  ", fg, bg, "");
+    buttons.push("  Delete  ", foreground_for(red), red, "");
+    framed(buttons);
+    println!("{}╰{}╯\x1b[0m", ansi::bg(bg) + &ansi::fg(border), "─".repeat(WIDTH));
+
+    let clock = colors[colors.len() / 2];
+    let mut status = Cells::new();
+    status.push(" NORMAL ", on_accent, accent, "\x1b[1m");
+    status.push("  main ", fg, overlay, "");
+    status.push(" src/preview.rs ", muted, surface, "");
+    status.pad(WIDTH - 12, surface);
+    status.push(" utf-8 ", muted, surface, "");
+    status.push(" 12:34 ", foreground_for(clock), clock, "\x1b[1m");
+    println!("{}\x1b[0m", status.text);
+}
+
+// Two samples per cell: the left half block takes one as its foreground and
+// the cell background the next, so ramps show banding at twice the resolution.
+pub fn print_gradients(palette: &Palette) {
+    let bg = palette.bg;
+    let cells = term::width().saturating_sub(12).clamp(16, 64);
+    let samples = cells * 2;
+    let label = ansi::fg(foreground_for(bg)) + &ansi::bg(bg);
+
+    let strip = |color_at: &dyn Fn(f32) -> [u8; 3]| {
+        let mut out = String::new();
+        for cell in 0..cells {
+            let left = color_at((cell * 2) as f32 / (samples - 1) as f32);
+            let right = color_at((cell * 2 + 1) as f32 / (samples - 1) as f32);
+            write!(out, "{}{}▌", ansi::fg(left), ansi::bg(right)).unwrap();
+        }
+        out
+    };
+
+    println!("\nGradients:");
+    for color in &palette.colors {
+        let (hex, rgb) = (color.hex(), color.rgb);
+        let okhsl = okhsl::Okhsl::from(okhsl::Oklab::from(Rgb { r: rgb[0], g: rgb[1], b: rgb[2] }));
+        let blend = strip(&|t| mix(bg, rgb, t));
+        let ramp = strip(&|l| {
+            let step = okhsl::Okhsl { l, ..okhsl }.to_srgb();
+            [step.r, step.g, step.b]
+        });
+        println!("{label}#{hex} bg→ {blend}\x1b[0m");
+        println!("{label}{:<12}{ramp}\x1b[0m", "   ramp");
+    }
+}
+
+// Contrast of every palette color, the background and its foreground as text
+// (rows) on each other as surfaces (columns). Each cell is drawn as that pair,
+// APCA Lc on top and the WCAG ratio below.
+pub fn print_matrix(palette: &Palette) {
+    const CELL: usize = 8;
+
+    let bg = palette.bg;
+    let fg = foreground_for(bg);
+    let mut entries = vec![(String::from("bg"), bg), (String::from("fg"), fg)];
+    entries.extend(palette.colors.iter().map(|color| (color.hex(), color.rgb)));
+
+    let surfaces: Vec<Context> = entries.iter().map(|&(_, rgb)| Context::new(rgb)).collect();
+    let label = ansi::fg(fg) + &ansi::bg(bg);
+
+    println!("\nContrast matrix (text ↓ on surface →):");
+    let mut header = format!("{label}{:CELL$}", "");
+    for (name, _) in &entries {
+        write!(header, "{name:^CELL$}").unwrap();
+    }
+    println!("{header}\x1b[0m");
+
+    for (i, (name, text)) in entries.iter().enumerate() {
+        let mut apca_line = format!("{label}{name:<CELL$}");
+        let mut wcag_line = format!("{label}{:CELL$}", "");
+        for (j, surface) in surfaces.iter().enumerate() {
+            let cell = ansi::bg(surface.bg) + &ansi::fg(*text);
+            if i == j {
+                write!(apca_line, "{cell}{:CELL$}", "").unwrap();
+                write!(wcag_line, "{cell}{:CELL$}", "").unwrap();
+            } else {
+                let apca = format!("Lc {:.0}", surface.apca(*text));
+                let wcag = format!("{:.2}", surface.wcag(*text));
+                write!(apca_line, "{cell}{apca:^CELL$}").unwrap();
+                write!(wcag_line, "{cell}{wcag:^CELL$}").unwrap();
+            }
+        }
+        println!("{apca_line}\x1b[0m");
+        println!("{wcag_line}\x1b[0m");
+    }
+}
+
+// Every accent as a fill (buttons, badges, tags) with the theme background and
+// foreground as text on it, and which of the two reads better.
+pub fn print_badges(palette: &Palette, ctx: &Context) {
+    let bg = ctx.bg;
+    let fg = foreground_for(bg);
+    let (dark, light) = if ctx.luminance(bg) < ctx.luminance(fg) { (bg, fg) } else { (fg, bg) };
+    let badge = |text: [u8; 3], fill: [u8; 3], label: &str| {
+        format!("{}{} {label} \x1b[0m", ansi::bg(fill), ansi::fg(text))
+    };
+    let mark = |apca: f64| if ctx.target.apca_passes(apca) { "✅" } else { "❌" };
+
+    println!("\nBadges (theme text on accent fills):");
+    for color in &palette.colors {
+        let (hex, fill) = (color.hex(), Context::new(color.rgb));
+        let (on_dark, on_light) = (fill.apca(dark), fill.apca(light));
+        let verdict = match on_dark.abs() - on_light.abs() {
+            margin if margin >= 0.0 => format!("dark text wins by Lc {margin:.0}"),
+            margin => format!("light text wins by Lc {:.0}", -margin),
+        };
+        println!(
+            "{} {} Lc {on_dark:3.0} {}  {} Lc {on_light:4.0} {}  {verdict}",
+            badge(dark, fill.bg, &format!("#{hex}")),
+            badge(dark, fill.bg, "dark"),
+            mark(on_dark),
+            badge(light, fill.bg, "light"),
+            mark(on_light),
+        );
+    }
+}
+
+// The classic colortest: every foreground of the derived 16-color scheme on
+// every background, the defaults first.
+pub fn print_grid(palette: &Palette) {
+    let scheme = Scheme::new(palette);
+    let slots: Vec<(String, [u8; 3], [u8; 3])> =
+        std::iter::once((String::from("def"), scheme.foreground, scheme.background))
+            .chain(scheme.ansi.iter().enumerate().map(|(i, rgb)| (format!("{i:>3}"), *rgb, *rgb)))
+            .collect();
+
+    let label = ansi::fg(scheme.foreground) + &ansi::bg(scheme.background);
+    println!("\nColor grid:");
+    let mut header = format!("{label}    ");
+    for (name, _, _) in &slots {
+        write!(header, "{name} ").unwrap();
+    }
+    println!("{header}\x1b[0m");
+    for (name, fg, _) in &slots {
+        let mut line = format!("{label}{name} ");
+        for (_, _, cell_bg) in &slots {
+            write!(line, "{}{}gYw ", ansi::bg(*cell_bg), ansi::fg(*fg)).unwrap();
+        }
+        println!("{line}\x1b[0m");
+    }
+}
+
+// Unrolls the hue circle at the palette's lightness and saturation into a
+// ribbon, 5° per cell. Markers above show where the colors sit; hatched cells
+// are hues that miss the minimum contrast on the background.
+pub fn print_wheel(palette: &Palette, ctx: &Context) {
+    const CELLS: usize = 72;
+
+    let bg = ctx.bg;
+    let fg = foreground_for(bg);
+    let okhsls: Vec<okhsl::Okhsl> = palette
+        .colors
+        .iter()
+        .map(|Color { rgb: [r, g, b], .. }| {
+            okhsl::Okhsl::from(okhsl::Oklab::from(Rgb { r: *r, g: *g, b: *b }))
+        })
+        .collect();
+    let l = okhsls.iter().map(|c| c.l).sum::<f32>() / okhsls.len() as f32;
+    let s = okhsls.iter().map(|c| c.s).sum::<f32>() / okhsls.len() as f32;
+
+    let mut markers = vec![None; CELLS];
+    for (okhsl, color) in okhsls.iter().zip(&palette.colors) {
+        markers[(okhsl.h * CELLS as f64).round() as usize % CELLS] = Some(color.rgb);
+    }
+
+    let bg_seq = ansi::bg(bg);
+    let mut marker_line = bg_seq.clone();
+    let mut ribbon = bg_seq.clone();
+    for (cell, marker) in markers.iter().enumerate() {
+        match marker {
+            Some(rgb) => write!(marker_line, "{}▼", ansi::fg(*rgb)).unwrap(),
+            None => marker_line.push(' '),
+        }
+
+        let h = cell as f64 / CELLS as f64;
+        let rgb = okhsl::Okhsl { h, s, l }.to_srgb();
+        let rgb = [rgb.r, rgb.g, rgb.b];
+        if ctx.minimum.passes(ctx.wcag(rgb), ctx.apca(rgb)) {
+            write!(ribbon, "{}█", ansi::fg(rgb)).unwrap();
+        } else {
+            write!(ribbon, "{}▒", ansi::fg(rgb)).unwrap();
+        }
+    }
+
+    println!("\nHue wheel (l={:.0} s={:.0}):", l * 100.0, s * 100.0);
+    println!("{marker_line}\x1b[0m");
+    println!("{ribbon}\x1b[0m");
+    println!("{ribbon}\x1b[0m");
+    println!(
+        "{bg_seq}{}0°{:>17}{:>18}{:>18}{:>17}\x1b[0m",
+        ansi::fg(fg),
+        "90°",
+        "180°",
+        "270°",
+        "360°"
+    );
+}
+
+fn hue_of(rgb: [u8; 3]) -> f64 {
+    let okhsl = okhsl::Okhsl::from(okhsl::Oklab::from(Rgb { r: rgb[0], g: rgb[1], b: rgb[2] }));
+    okhsl.h * 360.0
+}
+
+fn hue_distance(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+// Large filled blocks make hue and chroma far easier to judge than colored words.
+// Each swatch carries its hex label and, below it on the background, the WCAG
+// level it reaches and the APCA badge.
+// A simulated row, if any, sits directly under the originals on the simulated
+// background.
+pub fn print_swatches(palette: &Palette, ctx: &Context, simulate: Option<Deficiency>) {
+    let per_row = (term::width().saturating_sub(1) / (SWATCH_WIDTH + 1)).clamp(1, 6);
+
+    let bg = ctx.bg;
+    let bg_seq = ansi::bg(bg);
+
+    println!("\nSwatches:");
+    for row in palette.colors.chunks(per_row) {
+        let rgbs: Vec<[u8; 3]> = row.iter().map(|color| color.rgb).collect();
+        let labels: Vec<String> = row.iter().map(|color| format!("#{}", color.hex())).collect();
+        print_blocks(&rgbs, &labels, bg);
+
+        let fg = foreground_for(bg);
+        let mut wcag_line = bg_seq.clone();
+        let mut apca_line = bg_seq.clone();
+        for rgb in &rgbs {
+            let wcag = ctx.wcag(*rgb);
+            let apca = ctx.apca(*rgb);
+            let apca_badge = if ctx.target.apca_passes(apca) { "✅" } else { "❌" };
+            let level = wcag_levels(wcag).split(',').next().unwrap_or_default();
+            write!(wcag_line, " {:<SWATCH_WIDTH$}", format!("{wcag:.2} {level}")).unwrap();
+            write!(
+                apca_line,
+                " {:<w$}{apca_badge}",
+                format!("APCA {apca:.0}"),
+                w = SWATCH_WIDTH - 2
+            )
+            .unwrap();
+        }
+        let fg_seq = ansi::fg(fg);
+        println!("{fg_seq}{wcag_line} \x1b[0m");
+        println!("{fg_seq}{apca_line} \x1b[0m");
+
+        if let Some(cvd) = simulate {
+            let simulated: Vec<[u8; 3]> = rgbs.iter().map(|rgb| cvd.simulate(*rgb)).collect();
+            let labels = vec![String::from(cvd.name()); simulated.len()];
+            print_blocks(&simulated, &labels, cvd.simulate(bg));
+        }
+    }
+}
+
+const SWATCH_WIDTH: usize = 13;
+
+pub fn print_blocks(rgbs: &[[u8; 3]], labels: &[String], bg: [u8; 3]) {
+    print!("{}", blocks(rgbs, labels, bg));
+}
+
+// The lines `print_blocks` prints, each ending in a newline.
+pub fn blocks(rgbs: &[[u8; 3]], labels: &[String], bg: [u8; 3]) -> String {
+    const HEIGHT: usize = 3;
+
+    let bg_seq = ansi::bg(bg);
+    let mut out = String::new();
+    for line in 0..HEIGHT {
+        out.push_str(&bg_seq);
+        for (label, rgb) in labels.iter().zip(rgbs) {
+            let label = if line == HEIGHT / 2 { label.as_str() } else { "" };
+            let swatch = ansi::bg(*rgb);
+            let text = ansi::fg(foreground_for(*rgb));
+            write!(out, " {swatch}{text}{label:^SWATCH_WIDTH$}{bg_seq}").unwrap();
+        }
+        out.push_str(" \x1b[0m\n");
+    }
+    out
+}
+
+// `image` picks the protocol from the environment; `kitty` and `sixel` force one.
+pub fn print_image(kind: &str, palette: &Palette) {
+    let protocol = match kind {
+        "image" if term::caps().kitty_graphics => "kitty",
+        "image" if term::caps().sixel => "sixel",
+        "image" => {
+            eprintln!("\nThe image preview needs a terminal with kitty graphics or sixel support.");
+            return;
+        }
+        forced => forced,
+    };
+
+    let image = image::palette_image(palette);
+    if protocol == "kitty" {
+        println!("\n{}", image::kitty(&image));
+    } else {
+        println!("\n{}", image::sixel(&image));
+    }
+}
+
+// A neutral text color for the background: near-white on dark, near-black on light.
+pub fn foreground_for(bg: [u8; 3]) -> [u8; 3] {
+    if is_dark(bg) { [0xD8, 0xD8, 0xD8] } else { [0x20, 0x20, 0x20] }
+}
+
+// Whether a background takes light text: white on it has more APCA contrast
+// than black. Schemes and desktops call it dark.
+pub fn is_dark(bg: [u8; 3]) -> bool {
+    apca_contrast([0xFF; 3], bg).abs() > apca_contrast([0x00; 3], bg).abs()
+}
+
+pub fn mix(a: [u8; 3], b: [u8; 3], t: f32) -> [u8; 3] {
+    [0, 1, 2].map(|i| (f32::from(b[i]) - f32::from(a[i])).mul_add(t, f32::from(a[i])).round() as u8)
+}
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::context::{Context, Thresholds, nearest_passing, polarity};
+use crate::grade::Grade;
+use crate::log;
+use crate::metric::Metrics;
+use crate::palette::Palette;
+use crate::{wcag_levels, write_file};
+
+pub const FORMATS: [&str; 3] = ["html", "json", "sarif"];
+
+// One color, by its number in the palette, measured on one surface.
+struct Finding {
+    number: usize,
+    color: [u8; 3],
+    surface: [u8; 3],
+    wcag: f32,
+    apca: f64,
+    passes: bool,
+    suggestion: Option<[u8; 3]>,
+    extra: Vec<(&'static str, f64)>,
+}
+
+// Writes the audit of every color on every surface to `<stem>.<ext>`, which
+// is `colorize-report.<ext>` from the command line. Metrics selected with
+// `--metrics` are added as extra columns. `format` is one of `FORMATS`.
+pub fn write(
+    stem: &Path,
+    format: &str,
+    palette: &Palette,
+    surfaces: &[&Context],
+    grade: &Grade,
+    metrics: Option<&Metrics>,
+) {
+    let findings: Vec<Finding> = surfaces
+        .iter()
+        .flat_map(|ctx| {
+            palette
+                .colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| audit(ctx, i + 1, color.rgb, metrics))
+        })
+        .collect();
+    let target = surfaces[0].target;
+
+    let (document, ext) = match format {
+        "html" => (html(&findings, target, grade), "html"),
+        "json" => (json(&findings, target, grade), "json"),
+        _ => (sarif(&findings, target, grade), "sarif"),
+    };
+
+    let path = stem.with_extension(ext);
+    write_file(&path, document);
+    let failures = findings.iter().filter(|f| !f.passes).count();
+    log::info(format!(
+        "Wrote {}: {} checks, {failures} below target",
+        path.display(),
+        findings.len()
+    ));
+}
+
+fn audit(ctx: &Context, number: usize, color: [u8; 3], metrics: Option<&Metrics>) -> Finding {
+    let wcag = ctx.wcag(color);
+    let apca = ctx.apca(color);
+    let passes = ctx.target.passes(wcag, apca);
+    let suggestion = if passes {
+        None
+    } else {
+        let start = Okhsl::from(Oklab::from(Rgb { r: color[0], g: color[1], b: color[2] }));
+        nearest_passing(start, &[ctx], |ctx| ctx.target).map(|fix| {
+            let rgb = fix.to_srgb();
+            [rgb.r, rgb.g, rgb.b]
+        })
+    };
+    let extra = metrics.map_or_else(Vec::new, |metrics| {
+        metrics.0.iter().map(|(metric, _)| (metric.name(), metric.score(color, ctx.bg))).collect()
+    });
+    Finding { number, color, surface: ctx.bg, wcag, apca, passes, suggestion, extra }
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", rgb[0], rgb[1], rgb[2])
+}
+
+fn summary(f: &Finding, target: Thresholds) -> String {
+    let mut misses = Vec::new();
+    if f.wcag < target.wcag {
+        misses.push(format!("WCAG {:.2} < {}", f.wcag, target.wcag));
+    }
+    if !target.apca_passes(f.apca) {
+        misses.push(format!(
+            "APCA Lc {:.0} < {} ({})",
+            f.apca.abs(),
+            target.required(f.apca),
+            polarity(f.apca)
+        ));
+    }
+    let mut text = format!("{} on {}: {}", hex(f.color), hex(f.surface), misses.join(", "));
+    if let Some(fix) = f.suggestion {
+        write!(text, ". Suggested: {}", hex(fix)).unwrap();
+    }
+    text
+}
+
+fn json(findings: &[Finding], target: Thresholds, grade: &Grade) -> String {
+    let mut out = format!(
+        "{{\n  \"thresholds\": {{ \"wcag\": {}, \"apca\": {}, \"apca_reverse\": {} }},\n",
+        target.wcag, target.apca, target.apca_reverse
+    );
+    let cvd = grade.cvd.map_or_else(
+        || String::from("null"),
+        |(deficiency, distance)| {
+            format!("{{ \"deficiency\": \"{}\", \"delta_e\": {distance:.1} }}", deficiency.name())
+        },
+    );
+    writeln!(
+        out,
+        "  \"grade\": {{ \"letter\": \"{}\", \"score\": {}, \"worst_apca\": {:.1}, \"mean_margin\": {:.1}, \
+         \"passing\": {}, \"checks\": {}, \"closest_cvd\": {cvd} }},",
+        grade.letter(),
+        grade.score,
+        grade.worst_lc,
+        grade.mean_margin,
+        grade.passing,
+        grade.checks
+    )
+    .unwrap();
+    out.push_str("  \"results\": [\n");
+    for (i, f) in findings.iter().enumerate() {
+        let suggestion =
+            f.suggestion.map_or_else(|| String::from("null"), |s| format!("\"{}\"", hex(s)));
+        write!(
+            out,
+            "    {{ \"color\": \"{}\", \"surface\": \"{}\", \"wcag\": {:.2}, \"wcag_level\": \"{}\", \
+             \"apca\": {:.1}, \"polarity\": \"{}\", \"pass\": {}, \"suggestion\": {suggestion}, \"metrics\": {{ {} }} }}",
+            hex(f.color),
+            hex(f.surface),
+            f.wcag,
+            wcag_levels(f.wcag),
+            f.apca,
+            polarity(f.apca),
+            f.passes,
+            f.extra
+                .iter()
+                .map(|(name, value)| format!("\"{name}\": {value:.3}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .unwrap();
+        out.push_str(if i + 1 < findings.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
+
+// SARIF 2.1.0 with one result per failing check, for CI annotation tooling.
+// The palette has no source file, so each result is located at its color as
+// `color-<n>` on the surface, numbered as the report prints them.
+fn sarif(findings: &[Finding], target: Thresholds, grade: &Grade) -> String {
+    let results: Vec<String> = findings
+        .iter()
+        .filter(|f| !f.passes)
+        .map(|f| {
+            format!(
+                "        {{ \"ruleId\": \"contrast\", \"level\": \"error\", \"message\": {{ \"text\": \"{}\" }}, \
+                 \"locations\": [ {{ \"logicalLocations\": [ {{ \"name\": \"color-{1}\", \
+                 \"fullyQualifiedName\": \"{2}/color-{1}\", \"kind\": \"variable\" }} ] }} ] }}",
+                summary(f, target),
+                f.number,
+                hex(f.surface)
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"version\": \"2.1.0\",\n  \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\",\n  \
+         \"runs\": [\n    {{\n      \"tool\": {{ \"driver\": {{ \"name\": \"colorize\", \"rules\": [\n        \
+         {{ \"id\": \"contrast\", \"shortDescription\": {{ \"text\": \"Color contrast below WCAG {} / APCA Lc {}\" }} }}\n      \
+         ] }} }},\n      \"properties\": {{ \"grade\": \"{}\", \"score\": {} }},\n      \
+         \"results\": [\n{}\n      ]\n    }}\n  ]\n}}\n",
+        target.wcag,
+        target.apca,
+        grade.letter(),
+        grade.score,
+        results.join(",\n")
+    )
+}
+
+fn html(findings: &[Finding], target: Thresholds, grade: &Grade) -> String {
+    let mut rows = String::new();
+    for f in findings {
+        let mut extra = String::new();
+        for (_, value) in &f.extra {
+            write!(extra, "<td>{value:.2}</td>").unwrap();
+        }
+        let status = if f.passes { "pass" } else { "fail" };
+        let suggestion = f.suggestion.map_or_else(String::new, |s| {
+            format!(
+                "<span class=\"sample\" style=\"color:{0};background:{1}\">{0}</span>",
+                hex(s),
+                hex(f.surface)
+            )
+        });
+        writeln!(
+            rows,
+            "<tr class=\"{status}\"><td><span class=\"sample\" style=\"color:{0};background:{1}\">{0}</span></td>\
+             <td>{1}</td><td>{2:.2}</td><td>{3}</td><td>{4:.0} {6}</td><td>{status}</td><td>{suggestion}</td>{5}</tr>",
+            hex(f.color),
+            hex(f.surface),
+            f.wcag,
+            wcag_levels(f.wcag),
+            f.apca,
+            extra,
+            polarity(f.apca)
+        )
+        .unwrap();
+    }
+    let (wcag, apca, reverse) = (target.wcag, target.apca, target.apca_reverse);
+    let (letter, score, worst) = (grade.letter(), grade.score, grade.worst_lc);
+    let (passing, checks) = (grade.passing, grade.checks);
+    let mut extra_headers = String::new();
+    for (name, _) in findings.first().map_or(&[][..], |f| &f.extra[..]) {
+        write!(extra_headers, "<th>{name}</th>").unwrap();
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>colorize accessibility report</title>\n\
+         <style>\nbody {{ font-family: sans-serif; margin: 2em; }}\ntable {{ border-collapse: collapse; }}\n\
+         td, th {{ padding: .4em .8em; border-bottom: 1px solid #ccc; text-align: left; }}\n\
+         .sample {{ font-family: monospace; font-weight: bold; padding: .2em .5em; }}\n\
+         .fail td:nth-child(6) {{ color: #b00020; font-weight: bold; }}\n</style>\n</head>\n<body>\n\
+         <h1>Accessibility report</h1>\n<p>Target: WCAG {wcag}:1 and APCA Lc {apca} for dark on light, \
+         Lc {reverse} for light on dark. Suggestions are the \
+         closest okhsl colors of the same hue that pass.</p>\n\
+         <p>Grade: <strong>{letter}</strong> ({score}/100), worst APCA Lc {worst:.0}, {passing} of {checks} \
+         checks passing.</p>\n\
+         <table>\n<tr><th>Color</th><th>Surface</th><th>WCAG</th><th>Level</th><th>APCA Lc</th><th>Status</th><th>Suggested</th>{extra_headers}</tr>\n\
+         {rows}</table>\n</body>\n</html>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Json;
+
+    const BG: [u8; 3] = [0x1e, 0x1e, 0x2e];
+
+    fn grade() -> Grade {
+        Grade { worst_lc: 10.0, mean_margin: -20.0, passing: 1, checks: 2, cvd: None, score: 40 }
+    }
+
+    fn array<'a>(value: &'a Json, key: &str) -> &'a [Json] {
+        match value.get(key) {
+            Some(Json::Array(items)) => items,
+            other => panic!("{key} is {other:?}"),
+        }
+    }
+
+    fn text<'a>(value: &'a Json, key: &str) -> &'a str {
+        match value.get(key) {
+            Some(Json::String(text)) => text,
+            other => panic!("{key} is {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sarif_locates_each_failing_color() {
+        let ctx = Context::new(BG);
+        let findings = [audit(&ctx, 1, [0xff; 3], None), audit(&ctx, 2, [0x30, 0x30, 0x40], None)];
+        assert!(findings[0].passes && !findings[1].passes);
+
+        let log = Json::parse(&sarif(&findings, ctx.target, &grade())).unwrap();
+        assert_eq!(text(&log, "version"), "2.1.0");
+        let run = &array(&log, "runs")[0];
+        let driver = run.get("tool").and_then(|tool| tool.get("driver")).unwrap();
+        let rules = array(driver, "rules");
+        let results = array(run, "results");
+        assert_eq!(results.len(), 1);
+        assert!(rules.iter().any(|rule| text(rule, "id") == text(&results[0], "ruleId")));
+        let location = &array(&array(&results[0], "locations")[0], "logicalLocations")[0];
+        assert_eq!(text(location, "name"), "color-2");
+        assert_eq!(text(location, "fullyQualifiedName"), "#1E1E2E/color-2");
+
+        let passing = Json::parse(&sarif(&findings[..1], ctx.target, &grade())).unwrap();
+        assert!(array(&array(&passing, "runs")[0], "results").is_empty());
+    }
+
+    #[test]
+    fn json_and_html_list_every_check() {
+        let ctx = Context::new(BG);
+        let findings = [audit(&ctx, 1, [0xff; 3], None), audit(&ctx, 2, [0x30, 0x30, 0x40], None)];
+
+        let report = Json::parse(&json(&findings, ctx.target, &grade())).unwrap();
+        let results = array(&report, "results");
+        let passes: Vec<_> = results.iter().map(|result| result.get("pass")).collect();
+        assert_eq!(passes, [Some(&Json::Bool(true)), Some(&Json::Bool(false))]);
+        assert_eq!(results[0].get("suggestion"), Some(&Json::Null));
+        assert!(matches!(results[1].get("suggestion"), Some(Json::String(_))));
+
+        let page = html(&findings, ctx.target, &grade());
+        assert_eq!(page.matches("<tr class=\"pass\">").count(), 1);
+        assert_eq!(page.matches("<tr class=\"fail\">").count(), 1);
+        assert!(page.ends_with("</table>\n</body>\n</html>\n"));
+    }
+}
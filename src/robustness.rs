@@ -0,0 +1,85 @@
+use std::fmt::Write;
+
+use crate::context::Context;
+use crate::cvd::delinearize;
+use crate::palette::Palette;
+use crate::{colorize_output, linearize};
+
+// Ways a real display drifts from sRGB. Both text and background go through
+// the same change, since the whole screen does.
+const VARIATIONS: [(&str, Variation); 4] = [
+    ("γ 1.8", Variation::Gamma(1.8)),
+    ("γ 2.6", Variation::Gamma(2.6)),
+    ("dim 70%", Variation::Brightness(0.7)),
+    ("night", Variation::Night),
+];
+
+#[derive(Clone, Copy)]
+enum Variation {
+    // A panel decoding with this exponent instead of sRGB's ~2.2.
+    Gamma(f32),
+    // Backlight scaled down, in linear light.
+    Brightness(f32),
+    // Night-light filters cut blue and some green, roughly 3400 K.
+    Night,
+}
+
+impl Variation {
+    fn apply(self, rgb: [u8; 3]) -> [u8; 3] {
+        let linear = rgb.map(|c| linearize(f32::from(c) / 255.0));
+        let shown = match self {
+            Self::Gamma(gamma) => rgb.map(|c| (f32::from(c) / 255.0).powf(gamma)),
+            Self::Brightness(scale) => linear.map(|c| c * scale),
+            Self::Night => [linear[0], linear[1] * 0.82, linear[2] * 0.55],
+        };
+        shown.map(|c| (delinearize(c.clamp(0.0, 1.0)) * 255.0).round() as u8)
+    }
+}
+
+// Re-checks every color under each variation and flags the ones that pass on
+// a calibrated display but fail on some of these.
+pub fn print(palette: &Palette, ctx: &Context) {
+    let surfaces: Vec<Context> = VARIATIONS
+        .iter()
+        .map(|(_, variation)| {
+            Context::new(variation.apply(ctx.bg)).with_thresholds(ctx.minimum, ctx.target)
+        })
+        .collect();
+
+    println!("\nRobustness (APCA Lc under display variations):");
+    let mut header = format!("{:8}{:>8}", "", "sRGB");
+    for (name, _) in &VARIATIONS {
+        write!(header, "{name:>10}").unwrap();
+    }
+    println!("{header}");
+
+    let mut fragile = 0;
+    for color in &palette.colors {
+        let (hex, fg) = (color.hex(), color.rgb);
+        let nominal = ctx.target.passes(ctx.wcag(fg), ctx.apca(fg));
+        let mark = |passes: bool| if passes { "✅" } else { "❌" };
+
+        let mut row = format!(
+            "{} {:>5.0} {}",
+            colorize_output(fg, &format!("#{hex}"), ctx.bg),
+            ctx.apca(fg),
+            mark(nominal)
+        );
+        let mut holds = true;
+        for (surface, (_, variation)) in surfaces.iter().zip(&VARIATIONS) {
+            let shown = variation.apply(fg);
+            let passes = surface.target.passes(surface.wcag(shown), surface.apca(shown));
+            holds &= passes;
+            write!(row, " {:>6.0} {}", surface.apca(shown), mark(passes)).unwrap();
+        }
+        if nominal && !holds {
+            fragile += 1;
+            row.push_str("  fragile");
+        }
+        println!("{row}");
+    }
+
+    if fragile > 0 {
+        println!("{fragile} of {} colors pass only on a calibrated display.", palette.colors.len());
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ColorizeError;
+use crate::config::Config;
+use crate::context::Thresholds;
+
+// APCA bands for common roles, after the APCA readability guidance: comments
+// and other de-emphasized text sit low on purpose, body text needs Lc 75 and
+// errors should stand out above everything else.
+const DEFAULTS: [(&str, Band); 3] = [
+    ("comment", Band { min: 35.0, max: Some(50.0) }),
+    ("text", Band { min: 75.0, max: None }),
+    ("error", Band { min: 90.0, max: None }),
+];
+
+// A range of APCA Lc magnitudes, `35-50` or an open-ended `75`.
+#[derive(Clone, Copy)]
+pub struct Band {
+    pub min: f64,
+    pub max: Option<f64>,
+}
+
+impl Band {
+    pub fn contains(self, apca: f64) -> bool {
+        apca.abs() >= self.min && self.max.is_none_or(|max| apca.abs() <= max)
+    }
+
+    // The Lc generation solves for: the middle of a closed band, just above
+    // the floor of an open one.
+    pub fn aim(self) -> f64 {
+        self.max.map_or(self.min + 1.0, |max| f64::midpoint(self.min, max))
+    }
+
+    // Bands are APCA only, so fixes for a role ignore the WCAG ratio.
+    pub const fn thresholds(self) -> Thresholds {
+        Thresholds { wcag: 1.0, apca: self.min, apca_reverse: self.min }
+    }
+}
+
+impl FromStr for Band {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |v: &str| v.trim().parse().map_err(|_| format!("invalid Lc band '{s}'"));
+        match s.split_once('-') {
+            Some((min, max)) => Ok(Self { min: parse(min)?, max: Some(parse(max)?) }),
+            None => Ok(Self { min: parse(s)?, max: None }),
+        }
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "Lc {}–{max}", self.min),
+            None => write!(f, "Lc {}+", self.min),
+        }
+    }
+}
+
+// Roles for the palette colors in order, from a comma separated list. Bands
+// come from the `[roles]` table of the config, falling back to the defaults.
+pub fn parse(list: &str, config: &Config) -> Result<Vec<(String, Band)>, ColorizeError> {
+    list.split(',')
+        .map(|name| {
+            let band = config
+                .parsed(&format!("roles.{name}"))?
+                .or_else(|| DEFAULTS.iter().find(|(role, _)| *role == name).map(|(_, band)| *band));
+            let band = band.ok_or_else(|| ColorizeError::InvalidValue {
+                flag: String::from("--roles"),
+                value: name.to_string(),
+                expected: format!(
+                    "expected one of {}, or a band for it under [roles] in the config",
+                    DEFAULTS.map(|(role, _)| role).join(", ")
+                ),
+            })?;
+            Ok((name.to_string(), band))
+        })
+        .collect()
+}
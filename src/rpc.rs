@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::context::{Context, Thresholds, polarity};
+use crate::cvd::delta_e;
+use crate::hct::Hct;
+use crate::image;
+use crate::json::Json;
+use crate::palette::Palette;
+use crate::serve;
+
+// `colorize --rpc`: newline-delimited JSON-RPC 2.0 on stdin and stdout, so an
+// editor plugin or GUI keeps one process around instead of spawning the CLI
+// per query. One request per line, one response line per request with an
+// id; requests without an id are notifications and get none. It ends when
+// stdin closes or the client stops reading. Methods:
+//
+//   generate       {bg, l, s, o, c}          the palette as `/palette` returns it
+//   check          {fg, bg}                  contrast of one pair
+//   convert        {color}                   hex, rgb, okhsl, oklab and hct
+//   preview-image  {bg, l, s, o, c, format}  ppm (base64), kitty or sixel
+pub fn run() {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = respond(&line)
+            && writeln!(stdout, "{response}").and_then(|()| stdout.flush()).is_err()
+        {
+            break;
+        }
+    }
+}
+
+fn respond(line: &str) -> Option<String> {
+    let Some(request) = Json::parse(line) else {
+        return Some(error("null", -32700, "parse error"));
+    };
+    let id = request.get("id").map(Json::to_string);
+    let Some(Json::String(method)) = request.get("method") else {
+        return Some(error(id.as_deref().unwrap_or("null"), -32600, "invalid request"));
+    };
+    let params = request.get("params").cloned().unwrap_or(Json::Object(Vec::new()));
+
+    let result = match method.as_str() {
+        "generate" => generate(&params),
+        "check" => check(&params),
+        "convert" => convert(&params),
+        "preview-image" => preview_image(&params),
+        _ => return Some(error(&id?, -32601, &format!("unknown method {method}"))),
+    };
+    let id = id?;
+    Some(match result {
+        Ok(result) => format!("{{\"jsonrpc\":\"2.0\",\"id\":{id},\"result\":{result}}}"),
+        Err(message) => error(&id, -32602, &message),
+    })
+}
+
+fn error(id: &str, code: i32, message: &str) -> String {
+    let message = Json::String(message.to_string());
+    format!(
+        "{{\"jsonrpc\":\"2.0\",\"id\":{id},\"error\":{{\"code\":{code},\"message\":{message}}}}}"
+    )
+}
+
+// The `generate` parameters as the query `Palette` already understands. Values
+// out of range are an error the caller gets back as Invalid params (-32602),
+// so the process stays up for the next request.
+fn palette(params: &Json) -> Result<Palette, String> {
+    let Json::Object(fields) = params else { return Err(String::from("params must be an object")) };
+    let values: Vec<(&str, String)> = fields
+        .iter()
+        .filter_map(|(key, value)| match value {
+            Json::String(s) => Some((key.as_str(), s.trim_start_matches('#').to_string())),
+            Json::Number(n) => Some((key.as_str(), n.to_string())),
+            _ => None,
+        })
+        .collect();
+    let query: HashMap<&str, &str> = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    serve::from_query(&query).map_err(|err| err.to_string())
+}
+
+fn generate(params: &Json) -> Result<String, String> {
+    Ok(compact(&serve::json(&palette(params)?)))
+}
+
+fn check(params: &Json) -> Result<String, String> {
+    let (fg_rgb, bg) = (color(params, "fg")?, color(params, "bg")?);
+    let ctx = Context::new(bg);
+    let (wcag, apca) = (ctx.wcag(fg_rgb), ctx.apca(fg_rgb));
+    Ok(format!(
+        "{{\"wcag\":{wcag:.2},\"apca\":{apca:.1},\"polarity\":\"{}\",\"delta_e\":{:.1},\
+         \"minimum\":{},\"target\":{}}}",
+        polarity(apca),
+        delta_e(fg_rgb, ctx.bg),
+        Thresholds::MINIMUM.passes(wcag, apca),
+        Thresholds::TARGET.passes(wcag, apca)
+    ))
+}
+
+fn convert(params: &Json) -> Result<String, String> {
+    let [r, g, b] = color(params, "color")?;
+    let hex = format!("{r:02X}{g:02X}{b:02X}");
+    let oklab = Oklab::from(Rgb { r, g, b });
+    let okhsl = Okhsl::from(oklab);
+    let hct = Hct::from_rgb([r, g, b]);
+    Ok(format!(
+        "{{\"hex\":\"#{hex}\",\"rgb\":[{r},{g},{b}],\
+         \"okhsl\":{{\"h\":{:.1},\"s\":{:.3},\"l\":{:.3}}},\
+         \"oklab\":{{\"l\":{:.4},\"a\":{:.4},\"b\":{:.4}}},\
+         \"hct\":{{\"h\":{:.1},\"c\":{:.1},\"t\":{:.1}}}}}",
+        okhsl.h * 360.0,
+        okhsl.s,
+        okhsl.l,
+        oklab.l,
+        oklab.a,
+        oklab.b,
+        hct.hue,
+        hct.chroma,
+        hct.tone
+    ))
+}
+
+fn preview_image(params: &Json) -> Result<String, String> {
+    let palette = palette(params)?;
+    let image = image::palette_image(&palette);
+    let (format, data) = match params.get("format") {
+        None => ("ppm", image::base64(&image.ppm())),
+        Some(Json::String(format)) if format == "ppm" => ("ppm", image::base64(&image.ppm())),
+        Some(Json::String(format)) if format == "kitty" => ("kitty", image::kitty(&image)),
+        Some(Json::String(format)) if format == "sixel" => ("sixel", image::sixel(&image)),
+        Some(other) => return Err(format!("invalid format {other}: ppm, kitty or sixel")),
+    };
+    Ok(format!(
+        "{{\"format\":\"{format}\",\"width\":{},\"height\":{},\"data\":{}}}",
+        image.width,
+        image.height,
+        Json::String(data)
+    ))
+}
+
+// A hex color parameter as six uppercase digits without `#`.
+fn color(params: &Json, key: &str) -> Result<[u8; 3], String> {
+    let Some(Json::String(value)) = params.get(key) else {
+        return Err(format!("missing {key}"));
+    };
+    crate::hex_to_rgb_u8(value).map_err(|err| format!("invalid {key}: {err}"))
+}
+
+// Puts the pretty-printed JSON of the HTTP API on one line. Its strings hold
+// no line breaks.
+fn compact(json: &str) -> String {
+    json.lines().map(str::trim).collect()
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::num::NonZeroU16;
+use std::time::Duration;
+
+use crate::cli::{self, Flag, flag};
+use crate::context::{Context, polarity};
+use crate::palette::{Color, Palette};
+use crate::preview::foreground_for;
+use crate::{ColorizeError, hex_to_rgb_u8};
+
+const FLAGS: [Flag; 3] = [
+    flag("--port", None, Some("port"), "port to listen on (8080)"),
+    flag("--host", None, Some("address"), "address to listen on (127.0.0.1); 0.0.0.0 for the LAN"),
+    flag("--cors", None, None, "let any web page read the answers"),
+];
+
+// How long a client has to send its request before it is dropped, so one that
+// connects and stays idle does not hold up the others.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The most colors one request can ask for.
+const MAX_COUNT: NonZeroU16 = NonZeroU16::new(64).unwrap();
+
+// `colorize serve [--port N] [--host ADDR] [--cors]`: answers
+// `GET /palette?bg=1e1e2e&l=65&s=80&o=0&c=6` with JSON and `GET /preview` with
+// the same query as an HTML page. Only this machine is served unless `--host`
+// says otherwise. Requests are handled one at a time; a palette takes
+// microseconds.
+pub fn run(args: &[String]) -> Result<(), ColorizeError> {
+    let mut port = 8080;
+    let mut host = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let mut cors = false;
+    for (flag, value) in cli::parse_with(args, &FLAGS)? {
+        match flag {
+            "--port" => port = cli::number(flag, value, 1..=u16::MAX)?,
+            "--host" => {
+                host = value.parse().map_err(|_| ColorizeError::InvalidValue {
+                    flag: flag.to_string(),
+                    value: value.to_string(),
+                    expected: String::from("expected an IP address such as 127.0.0.1"),
+                })?;
+            }
+            _ => cors = true,
+        }
+    }
+    let listener = TcpListener::bind((host, port)).map_err(|err| {
+        let reason = if err.kind() == ErrorKind::AddrInUse {
+            String::from("address in use")
+        } else {
+            err.to_string()
+        };
+        ColorizeError::Io { action: format!("listen on {host} port {port}"), reason }
+    })?;
+    let address = if host.is_ipv6() { format!("[{host}]") } else { host.to_string() };
+    println!("Serving palettes on http://{address}:{port}/palette and /preview");
+
+    for stream in listener.incoming().flatten() {
+        if let Err(err) = handle(stream, cors) {
+            eprintln!("Request failed: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, cors: bool) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    let mut reader = BufReader::new(&stream);
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed; read them off so the client sees a clean close.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query: HashMap<&str, &str> =
+        query.split('&').filter_map(|pair| pair.split_once('=')).collect();
+
+    let (status, content_type, body) = match (path, from_query(&query)) {
+        ("/palette", Ok(palette)) => ("200 OK", "application/json", json(&palette)),
+        ("/preview", Ok(palette)) => ("200 OK", "text/html; charset=utf-8", html(&palette)),
+        ("/palette" | "/preview", Err(err)) => {
+            ("400 Bad Request", "text/plain", format!("{err}\n"))
+        }
+        _ => ("404 Not Found", "text/plain", String::from("Try /palette or /preview\n")),
+    };
+    let cors = if cors { "Access-Control-Allow-Origin: *\r\n" } else { "" };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         {cors}Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+// The palette of `bg`, `l`, `s`, `o` and `c`, which the RPC servers and the
+// gallery take as well. Each is checked over the range of its command line
+// option, so no request reaches okhsl with a value it cannot take.
+pub fn from_query(query: &HashMap<&str, &str>) -> Result<Palette, ColorizeError> {
+    let param =
+        |key, default, range| query.get(key).map_or(Ok(default), |v| cli::number(key, v, range));
+
+    let bg = query.get("bg").map_or("000000", |bg| bg.trim_start_matches("%23"));
+    let bg = hex_to_rgb_u8(bg)?;
+    let l = param("l", 60.0, 0.0..=100.0)?;
+    let s = param("s", 100.0, 0.0..=100.0)?;
+    let offset = param("o", 0.0, 0.0..=360.0)?;
+    let count = query.get("c").map_or(Ok(NonZeroU16::new(6).unwrap()), |v| {
+        cli::number("c", v, NonZeroU16::MIN..=MAX_COUNT)
+    })?;
+
+    Ok(Palette::generate(&Context::new(bg), l, s, offset, count))
+}
+
+pub fn json(palette: &Palette) -> String {
+    let mut out =
+        format!("{{\n  \"background\": \"#{}\",\n  \"colors\": [\n", palette.background());
+    for (i, color) in palette.colors.iter().enumerate() {
+        let Color { wcag, apca, pass, .. } = color;
+        let hex = color.hex();
+        write!(
+                out,
+                "    {{ \"hex\": \"#{hex}\", \"wcag\": {wcag:.2}, \"apca\": {apca:.1}, \"polarity\": \"{}\", \"pass\": {pass} }}",
+                polarity(*apca)
+            )
+            .unwrap();
+        out.push_str(if i + 1 < palette.colors.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("  ]\n}\n");
+    out
+}
+
+fn html(palette: &Palette) -> String {
+    let bg = format!("#{}", palette.background());
+    let [r, g, b] = foreground_for(palette.bg);
+    let fg = format!("#{r:02X}{g:02X}{b:02X}");
+    let mut items = String::new();
+    for color in &palette.colors {
+        let (hex, apca) = (color.hex(), color.apca);
+        writeln!(items, "<li style=\"color:#{hex}\"><strong>#{hex}</strong> Lc {apca:.0}</li>")
+            .unwrap();
+    }
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>colorize</title>\n\
+             <style>\nbody {{ background: {bg}; color: {fg}; font-family: monospace; margin: 2em; }}\n\
+             li {{ list-style: none; font-size: 1.4em; margin: .3em 0; }}\n</style>\n</head>\n<body>\n\
+             <p>Palette on {bg}</p>\n<ul>\n{items}</ul>\n</body>\n</html>\n"
+    )
+}
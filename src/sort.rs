@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+use okhsl::{Okhsl, Oklab, Rgb};
+
+use crate::context::Context;
+use crate::palette::Palette;
+use crate::preview::ansi_slots;
+
+// `--sort`: the order colors are reported and exported in, instead of the
+// order they were generated in.
+#[derive(Clone, Copy)]
+pub enum Order {
+    // Around the okhsl hue circle from 0°.
+    Hue,
+    // Darkest first.
+    Lightness,
+    // Most APCA contrast on the main background first.
+    Apca,
+    // The ANSI slots they fill (red, green, yellow, blue, magenta, cyan),
+    // then the colors that fill none in generation order.
+    Role,
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hue" => Ok(Self::Hue),
+            "lightness" => Ok(Self::Lightness),
+            "apca" => Ok(Self::Apca),
+            "role" => Ok(Self::Role),
+            _ => Err(format!("unknown sort '{s}' (hue, lightness, apca, role)")),
+        }
+    }
+}
+
+impl Order {
+    // Indices into the palette's colors in this order. Ties keep generation
+    // order.
+    pub fn indices(self, palette: &Palette, ctx: &Context) -> Vec<usize> {
+        let rgbs = palette.rgbs();
+        let okhsl = |[r, g, b]: [u8; 3]| Okhsl::from(Oklab::from(Rgb { r, g, b }));
+        let mut indices: Vec<usize> = (0..rgbs.len()).collect();
+        match self {
+            Self::Hue => indices.sort_by(|a, b| okhsl(rgbs[*a]).h.total_cmp(&okhsl(rgbs[*b]).h)),
+            Self::Lightness => {
+                indices.sort_by(|a, b| okhsl(rgbs[*a]).l.total_cmp(&okhsl(rgbs[*b]).l));
+            }
+            Self::Apca => indices
+                .sort_by(|a, b| ctx.apca(rgbs[*b]).abs().total_cmp(&ctx.apca(rgbs[*a]).abs())),
+            Self::Role => {
+                let mut slotted: Vec<usize> = Vec::new();
+                for color in ansi_slots(&rgbs).into_iter().flatten() {
+                    if !slotted.contains(&color) {
+                        slotted.push(color);
+                    }
+                }
+                indices.retain(|i| !slotted.contains(i));
+                slotted.extend(indices);
+                indices = slotted;
+            }
+        }
+        indices
+    }
+}
+
+// `items` rearranged so that the i-th is the one at `indices[i]`.
+pub fn apply<T>(items: Vec<T>, indices: &[usize]) -> Vec<T> {
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    indices.iter().map(|&i| items[i].take().unwrap()).collect()
+}
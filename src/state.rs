@@ -0,0 +1,301 @@
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use okhsl::{Oklab, Rgb};
+
+use crate::cli::{self, Flag, flag};
+use crate::palette::Palette;
+use crate::{ColorizeError, ansi, color_name, colorize_output, hex_to_rgb_u8, log};
+
+const SYNTAXES: [&str; 5] = ["hex", "bare", "rgb", "css", "oklch"];
+
+const LAST_FLAGS: [Flag; 2] = [
+    flag("--get", None, Some("n"), "the n-th color alone"),
+    flag("--as", None, Some("syntax"), "hex, bare, rgb, css or oklch"),
+];
+
+// What a palette was generated from, for the history to run it again.
+pub struct Run<'a> {
+    pub random: bool,
+    // The image of a `material` run.
+    pub image: Option<&'a str>,
+    pub backgrounds: &'a [String],
+    pub lightness: f32,
+    pub saturation: f32,
+    pub offset: f32,
+    pub count: i32,
+}
+
+// Remembers the palette of the latest run for `colorize last`, and adds it to
+// the history with what it was generated from.
+pub fn save(run: &Run, palette: &Palette) {
+    let Some(dir) = dir() else { return };
+    let colors = palette.hexes();
+    // The background on the first line, then one color per line in the order
+    // they were numbered.
+    let mut last = format!("{}\n", palette.background());
+    for hex in &colors {
+        writeln!(last, "{hex}").unwrap();
+    }
+
+    // One tab separated line per palette: id, Unix time, kind, backgrounds,
+    // lightness, saturation, offset, count, image and colors.
+    let history = fs::read_to_string(dir.join("history")).unwrap_or_default();
+    let id = history.lines().count() + 1;
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let kind = match (run.image, run.random) {
+        (Some(_), _) => "material",
+        (None, true) => "random",
+        (None, false) => "palette",
+    };
+    let image = run.image.map_or_else(
+        || String::from("-"),
+        |image| {
+            fs::canonicalize(image).map_or_else(|_| image.to_string(), |p| p.display().to_string())
+        },
+    );
+    let line = format!(
+        "{id}\t{time}\t{kind}\t{}\t{}\t{}\t{}\t{}\t{image}\t{}\n",
+        run.backgrounds.join(","),
+        run.lightness,
+        run.saturation,
+        run.offset,
+        run.count,
+        colors.join(",")
+    );
+
+    let saved = fs::create_dir_all(&dir).and_then(|()| {
+        fs::write(dir.join("last"), last)?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("history"))?
+            .write_all(line.as_bytes())
+    });
+    if saved.is_err() {
+        log::warn(format!("Could not remember this palette in {}", dir.display()));
+    }
+}
+
+// `colorize last`: the numbered colors of the latest run, or with
+// `--get <n> [--as <syntax>]` the n-th one alone, plain, for scripts and the
+// clipboard.
+pub fn last(args: &[String]) -> Result<(), ColorizeError> {
+    let mut get = None;
+    let mut syntax = "hex";
+    for (flag, value) in cli::parse_with(args, &LAST_FLAGS)? {
+        match flag {
+            "--get" => get = Some(value),
+            _ if SYNTAXES.contains(&value) => syntax = value,
+            _ => {
+                return Err(ColorizeError::InvalidValue {
+                    flag: flag.to_string(),
+                    value: value.to_string(),
+                    expected: format!("expected one of {}", SYNTAXES.join(", ")),
+                });
+            }
+        }
+    }
+
+    let none = || ColorizeError::Io {
+        action: String::from("show the last palette"),
+        reason: String::from("no palette generated yet"),
+    };
+    let Some((path, text)) = dir().map(|dir| dir.join("last")).and_then(|path| {
+        let text = fs::read_to_string(&path).ok()?;
+        Some((path, text))
+    }) else {
+        return Err(none());
+    };
+    let rgbs = text.lines().map(hex_to_rgb_u8).collect::<Result<Vec<_>, _>>();
+    let rgbs = rgbs.map_err(|err| ColorizeError::Io {
+        action: format!("read {}", path.display()),
+        reason: format!("it is damaged: {err}"),
+    })?;
+    let Some((&bg, colors)) = rgbs.split_first() else {
+        return Err(none());
+    };
+
+    let Some(get) = get else {
+        for (i, &rgb) in colors.iter().enumerate() {
+            let label = format!("#{} {}", hex(rgb), color_name(rgb));
+            println!("{:>2} {}", i + 1, colorize_output(rgb, &label, bg));
+        }
+        return Ok(());
+    };
+    let n = cli::number("--get", get, 1..=colors.len())?;
+    println!("{}", format(colors[n - 1], syntax));
+    Ok(())
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("{r:02X}{g:02X}{b:02X}")
+}
+
+fn format(rgb: [u8; 3], syntax: &str) -> String {
+    let [r, g, b] = rgb;
+    match syntax {
+        "bare" => hex(rgb).to_lowercase(),
+        "rgb" => format!("{r}, {g}, {b}"),
+        "css" => format!("rgb({r} {g} {b})"),
+        "oklch" => {
+            let lab = Oklab::from(Rgb { r, g, b });
+            let hue = lab.b.atan2(lab.a).to_degrees().rem_euclid(360.0);
+            format!("oklch({:.1}% {:.3} {hue:.1})", lab.l * 100.0, lab.a.hypot(lab.b))
+        }
+        _ => format!("#{}", hex(rgb).to_lowercase()),
+    }
+}
+
+// A palette of the history.
+struct Entry {
+    id: String,
+    time: u64,
+    kind: String,
+    backgrounds: String,
+    lightness: String,
+    saturation: String,
+    offset: String,
+    count: String,
+    image: String,
+    // The first background's channels and the colors', checked when read.
+    bg: [u8; 3],
+    colors: Vec<[u8; 3]>,
+}
+
+impl Entry {
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [id, time, kind, backgrounds, lightness, saturation, offset, count, image, colors] =
+            fields.as_slice()
+        else {
+            return None;
+        };
+        Some(Self {
+            id: id.to_string(),
+            time: time.parse().ok()?,
+            kind: kind.to_string(),
+            backgrounds: backgrounds.to_string(),
+            lightness: lightness.to_string(),
+            saturation: saturation.to_string(),
+            offset: offset.to_string(),
+            count: count.to_string(),
+            image: image.to_string(),
+            bg: hex_to_rgb_u8(backgrounds.split(',').next().unwrap_or_default()).ok()?,
+            colors: colors
+                .split(',')
+                .filter(|c| !c.is_empty())
+                .map(|c| hex_to_rgb_u8(c).ok())
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    // The arguments that generate this palette again.
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.kind == "material" {
+            args.extend([String::from("material"), self.image.clone()]);
+        }
+        args.extend(["-b", &self.backgrounds].map(String::from));
+        if self.kind != "material" {
+            for (flag, value) in [
+                ("-l", &self.lightness),
+                ("-s", &self.saturation),
+                ("-o", &self.offset),
+                ("-c", &self.count),
+            ] {
+                args.extend([flag.to_string(), value.clone()]);
+            }
+        }
+        args
+    }
+
+    fn swatches(&self) -> String {
+        let mut out = ansi::bg(self.bg);
+        for &rgb in &self.colors {
+            write!(out, "{}██", ansi::fg(rgb)).unwrap();
+        }
+        out + "\x1b[0m"
+    }
+}
+
+fn entries() -> Vec<Entry> {
+    let history = dir().and_then(|dir| fs::read_to_string(dir.join("history")).ok());
+    history.unwrap_or_default().lines().filter_map(Entry::parse).collect()
+}
+
+fn find(id: Option<&String>) -> Entry {
+    let entry = id.and_then(|id| entries().into_iter().find(|entry| entry.id == *id));
+    entry.unwrap_or_else(|| {
+        eprintln!("No palette {} in the history; see colorize history list", id.map_or("", |s| s));
+        std::process::exit(1);
+    })
+}
+
+// `colorize history list|show <id>`: every palette generated so far, newest
+// last, or one of them with its colors and the command that made it.
+pub fn history(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            for entry in entries() {
+                println!(
+                    "{:>4}  {}  {:<8}  {} {}",
+                    entry.id,
+                    date(entry.time),
+                    entry.kind,
+                    entry.swatches(),
+                    entry.args().join(" ")
+                );
+            }
+        }
+        Some("show") => {
+            let entry = find(args.get(1));
+            println!("{} {} {}", entry.id, date(entry.time), entry.kind);
+            println!("colorize {}\n", entry.args().join(" "));
+            for (i, &rgb) in entry.colors.iter().enumerate() {
+                let label = format!("#{} {}", hex(rgb), color_name(rgb));
+                println!("{:>2} {}", i + 1, colorize_output(rgb, &label, entry.bg));
+            }
+        }
+        Some(other) => {
+            eprintln!("Unknown history command {other}; use list, show <id> or rerun <id>");
+            std::process::exit(1);
+        }
+    }
+}
+
+// `colorize history rerun <id> [options]`: the arguments for that palette
+// again, followed by the options given now.
+pub fn rerun(args: &[String]) -> Vec<String> {
+    let mut rerun = find(args.first()).args();
+    rerun.extend(args.iter().skip(1).cloned());
+    rerun
+}
+
+// A Unix time as a UTC date and time, after Howard Hinnant's civil_from_days.
+fn date(time: u64) -> String {
+    let days = (time / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let minutes = time % 86_400 / 60;
+    format!("{year}-{month:02}-{day:02} {:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+// `$XDG_STATE_HOME/colorize`, falling back to `~/.local/state/colorize`.
+fn dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".local").join("state")))?;
+    Some(base.join("colorize"))
+}
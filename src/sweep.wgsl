@@ -0,0 +1,444 @@
+// Valid-combination sweep and auto-fix scoring.
+//
+// `sweep` runs one invocation per (lightness, saturation, offset) point; a
+// point is valid when all `count` hues spread evenly from the offset clear both
+// thresholds and keep the asked distances from each other. `score` runs one
+// invocation per candidate of `nearest_passing` and marks those that clear
+// every surface.
+// The okhsl and Oklab conversions follow Björn Ottosson's reference
+// implementation, the deficiency matrices are those of `cvd.rs`.
+
+struct Params {
+    bg_lum: f32,
+    bg_luma: f32,
+    min_wcag: f32,
+    min_apca: f32,
+    min_apca_reverse: f32,
+    count: f32,
+    // Minimum ΔE between any two colors, and under each dichromacy; 0 is off.
+    separation: f32,
+    cvd: f32,
+}
+
+struct Score {
+    h: f32,
+    s: f32,
+    surfaces: u32,
+    pad: u32,
+}
+
+struct Surface {
+    bg_lum: f32,
+    bg_luma: f32,
+    min_wcag: f32,
+    min_apca: f32,
+    min_apca_reverse: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> valid: array<atomic<u32>>;
+
+@group(0) @binding(2) var<uniform> score: Score;
+@group(0) @binding(3) var<storage, read> surfaces: array<Surface>;
+@group(0) @binding(4) var<storage, read_write> passing: array<u32>;
+
+const PI: f32 = 3.14159265358979;
+const FLT_MAX: f32 = 3.40282347e38;
+const CUBE_LEN: u32 = 3672360u;
+const MAX_COUNT: u32 = 360u;
+// 101 lightness steps times 11 saturation steps.
+const CANDIDATES: u32 = 1111u;
+
+var<private> colors: array<vec3<f32>, MAX_COUNT>;
+var<private> seen: array<vec3<f32>, MAX_COUNT>;
+
+struct Lc {
+    L: f32,
+    C: f32,
+}
+
+struct ST {
+    S: f32,
+    T: f32,
+}
+
+fn oklab_to_linear_srgb(L: f32, a: f32, b: f32) -> vec3<f32> {
+    let l_ = L + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = L - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = L - 0.0894841775 * a - 1.2914855480 * b;
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+    return vec3<f32>(
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    );
+}
+
+fn compute_max_saturation(a: f32, b: f32) -> f32 {
+    var k0: f32; var k1: f32; var k2: f32; var k3: f32; var k4: f32;
+    var wl: f32; var wm: f32; var ws: f32;
+
+    if (-1.88170328 * a - 0.80936493 * b > 1.0) {
+        k0 = 1.19086277; k1 = 1.76576728; k2 = 0.59662641; k3 = 0.75515197; k4 = 0.56771245;
+        wl = 4.0767416621; wm = -3.3077115913; ws = 0.2309699292;
+    } else if (1.81444104 * a - 1.19445276 * b > 1.0) {
+        k0 = 0.73956515; k1 = -0.45954404; k2 = 0.08285427; k3 = 0.12541070; k4 = 0.14503204;
+        wl = -1.2684380046; wm = 2.6097574011; ws = -0.3413193965;
+    } else {
+        k0 = 1.35733652; k1 = -0.00915799; k2 = -1.15130210; k3 = -0.50559606; k4 = 0.00692167;
+        wl = -0.0041960863; wm = -0.7034186147; ws = 1.7076147010;
+    }
+
+    var S = k0 + k1 * a + k2 * b + k3 * a * a + k4 * a * b;
+
+    let k_l = 0.3963377774 * a + 0.2158037573 * b;
+    let k_m = -0.1055613458 * a - 0.0638541728 * b;
+    let k_s = -0.0894841775 * a - 1.2914855480 * b;
+
+    let l_ = 1.0 + S * k_l;
+    let m_ = 1.0 + S * k_m;
+    let s_ = 1.0 + S * k_s;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    let l_dS = 3.0 * k_l * l_ * l_;
+    let m_dS = 3.0 * k_m * m_ * m_;
+    let s_dS = 3.0 * k_s * s_ * s_;
+
+    let l_dS2 = 6.0 * k_l * k_l * l_;
+    let m_dS2 = 6.0 * k_m * k_m * m_;
+    let s_dS2 = 6.0 * k_s * k_s * s_;
+
+    let f = wl * l + wm * m + ws * s;
+    let f1 = wl * l_dS + wm * m_dS + ws * s_dS;
+    let f2 = wl * l_dS2 + wm * m_dS2 + ws * s_dS2;
+
+    S = S - f * f1 / (f1 * f1 - 0.5 * f * f2);
+    return S;
+}
+
+fn find_cusp(a: f32, b: f32) -> Lc {
+    let S_cusp = compute_max_saturation(a, b);
+    let rgb = oklab_to_linear_srgb(1.0, S_cusp * a, S_cusp * b);
+    let L_cusp = pow(1.0 / max(max(rgb.r, rgb.g), rgb.b), 1.0 / 3.0);
+    return Lc(L_cusp, L_cusp * S_cusp);
+}
+
+fn halley_step(v: f32, v1: f32, v2: f32) -> f32 {
+    let u = v1 / (v1 * v1 - 0.5 * v * v2);
+    if (u >= 0.0) {
+        return -v * u;
+    }
+    return FLT_MAX;
+}
+
+fn find_gamut_intersection(a: f32, b: f32, L1: f32, C1: f32, L0: f32, cusp: Lc) -> f32 {
+    if ((L1 - L0) * cusp.C - (cusp.L - L0) * C1 <= 0.0) {
+        return cusp.C * L0 / (C1 * cusp.L + cusp.C * (L0 - L1));
+    }
+
+    var t = cusp.C * (L0 - 1.0) / (C1 * (cusp.L - 1.0) + cusp.C * (L0 - L1));
+
+    let dL = L1 - L0;
+    let dC = C1;
+
+    let k_l = 0.3963377774 * a + 0.2158037573 * b;
+    let k_m = -0.1055613458 * a - 0.0638541728 * b;
+    let k_s = -0.0894841775 * a - 1.2914855480 * b;
+
+    let l_dt = dL + dC * k_l;
+    let m_dt = dL + dC * k_m;
+    let s_dt = dL + dC * k_s;
+
+    let L = L0 * (1.0 - t) + t * L1;
+    let C = t * C1;
+
+    let l_ = L + C * k_l;
+    let m_ = L + C * k_m;
+    let s_ = L + C * k_s;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    let ldt = 3.0 * l_dt * l_ * l_;
+    let mdt = 3.0 * m_dt * m_ * m_;
+    let sdt = 3.0 * s_dt * s_ * s_;
+
+    let ldt2 = 6.0 * l_dt * l_dt * l_;
+    let mdt2 = 6.0 * m_dt * m_dt * m_;
+    let sdt2 = 6.0 * s_dt * s_dt * s_;
+
+    let r = 4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s - 1.0;
+    let r1 = 4.0767416621 * ldt - 3.3077115913 * mdt + 0.2309699292 * sdt;
+    let r2 = 4.0767416621 * ldt2 - 3.3077115913 * mdt2 + 0.2309699292 * sdt2;
+
+    let g = -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s - 1.0;
+    let g1 = -1.2684380046 * ldt + 2.6097574011 * mdt - 0.3413193965 * sdt;
+    let g2 = -1.2684380046 * ldt2 + 2.6097574011 * mdt2 - 0.3413193965 * sdt2;
+
+    let bb = -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s - 1.0;
+    let b1 = -0.0041960863 * ldt - 0.7034186147 * mdt + 1.7076147010 * sdt;
+    let b2 = -0.0041960863 * ldt2 - 0.7034186147 * mdt2 + 1.7076147010 * sdt2;
+
+    t = t + min(halley_step(r, r1, r2), min(halley_step(g, g1, g2), halley_step(bb, b1, b2)));
+    return t;
+}
+
+fn get_ST_mid(a_: f32, b_: f32) -> ST {
+    let S = 0.11516993 + 1.0 / (7.44778970 + 4.15901240 * b_
+        + a_ * (-2.19557347 + 1.75198401 * b_
+        + a_ * (-2.13704948 - 10.02301043 * b_
+        + a_ * (-4.24894561 + 5.38770819 * b_ + 4.69891013 * a_))));
+    let T = 0.11239642 + 1.0 / (1.61320320 - 0.68124379 * b_
+        + a_ * (0.40370612 + 0.90148123 * b_
+        + a_ * (-0.27087943 + 0.61223990 * b_
+        + a_ * (0.00299215 - 0.45399568 * b_ - 0.14661872 * a_))));
+    return ST(S, T);
+}
+
+fn toe_inv(x: f32) -> f32 {
+    let k_1 = 0.206;
+    let k_2 = 0.03;
+    let k_3 = (1.0 + k_1) / (1.0 + k_2);
+    return (x * x + k_1 * x) / (k_3 * (x + k_2));
+}
+
+fn srgb_transfer(x: f32) -> f32 {
+    if (x >= 0.0031308) {
+        return 1.055 * pow(x, 1.0 / 2.4) - 0.055;
+    }
+    return 12.92 * x;
+}
+
+// Returns the 8-bit channels scaled back to 0..1, matching `Okhsl::to_srgb`.
+fn okhsl_to_srgb(h: f32, s: f32, l: f32) -> vec3<f32> {
+    if (l >= 1.0) {
+        return vec3<f32>(1.0);
+    }
+    if (l <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    let a_ = cos(2.0 * PI * h);
+    let b_ = sin(2.0 * PI * h);
+    let L = toe_inv(l);
+
+    let cusp = find_cusp(a_, b_);
+    let C_max = find_gamut_intersection(a_, b_, L, 1.0, L, cusp);
+    let ST_max = ST(cusp.C / cusp.L, cusp.C / (1.0 - cusp.L));
+    let k = C_max / min(L * ST_max.S, (1.0 - L) * ST_max.T);
+
+    let ST_mid = get_ST_mid(a_, b_);
+    var C_a = L * ST_mid.S;
+    var C_b = (1.0 - L) * ST_mid.T;
+    let C_mid = 0.9 * k * sqrt(sqrt(1.0 / (1.0 / (C_a * C_a * C_a * C_a) + 1.0 / (C_b * C_b * C_b * C_b))));
+
+    C_a = L * 0.4;
+    C_b = (1.0 - L) * 0.8;
+    let C_0 = sqrt(1.0 / (1.0 / (C_a * C_a) + 1.0 / (C_b * C_b)));
+
+    let mid = 0.8;
+    let mid_inv = 1.25;
+    var C: f32;
+    if (s < mid) {
+        let t = mid_inv * s;
+        let k_1 = mid * C_0;
+        let k_2 = 1.0 - k_1 / C_mid;
+        C = t * k_1 / (1.0 - k_2 * t);
+    } else {
+        let t = (s - mid) / (1.0 - mid);
+        let k_0 = C_mid;
+        let k_1 = (1.0 - mid) * C_mid * C_mid * mid_inv * mid_inv / C_0;
+        let k_2 = 1.0 - k_1 / (C_max - C_mid);
+        C = k_0 + t * k_1 / (1.0 - k_2 * t);
+    }
+
+    let rgb = oklab_to_linear_srgb(L, C * a_, C * b_);
+    let srgb = vec3<f32>(srgb_transfer(rgb.r), srgb_transfer(rgb.g), srgb_transfer(rgb.b));
+    return round(clamp(srgb, vec3<f32>(0.0), vec3<f32>(1.0)) * 255.0) / 255.0;
+}
+
+fn linearize(v: f32) -> f32 {
+    if (v <= 0.04045) {
+        return v / 12.92;
+    }
+    return pow((v + 0.055) / 1.055, 2.4);
+}
+
+fn apca_clamp(y: f32) -> f32 {
+    if (y >= 0.022) {
+        return y;
+    }
+    return y + pow(0.022 - y, 1.414);
+}
+
+fn clears(rgb: vec3<f32>, surface: Surface) -> bool {
+    let fg_lum = 0.2126 * linearize(rgb.r) + 0.7152 * linearize(rgb.g) + 0.0722 * linearize(rgb.b);
+    let wcag = (max(fg_lum, surface.bg_lum) + 0.05) / (min(fg_lum, surface.bg_lum) + 0.05);
+
+    let fg_luma = apca_clamp(0.2126729 * pow(rgb.r, 2.4) + 0.7151522 * pow(rgb.g, 2.4)
+        + 0.0721750 * pow(rgb.b, 2.4));
+    let bg_luma = surface.bg_luma;
+    var c = 0.0;
+    if (abs(bg_luma - fg_luma) >= 0.0005) {
+        if (fg_luma < bg_luma) {
+            c = (pow(bg_luma, 0.56) - pow(fg_luma, 0.57)) * 1.14;
+        } else {
+            c = (pow(bg_luma, 0.65) - pow(fg_luma, 0.62)) * 1.14;
+        }
+    }
+    var apca = 0.0;
+    if (abs(c) >= 0.1) {
+        apca = (abs(c) - 0.027) * 100.0;
+    }
+    // Light text on a darker background is reverse polarity.
+    var min_apca = surface.min_apca;
+    if (c < 0.0) {
+        min_apca = surface.min_apca_reverse;
+    }
+
+    return wcag >= surface.min_wcag && apca >= min_apca;
+}
+
+fn delinearize(v: f32) -> f32 {
+    if (v <= 0.0031308) {
+        return v * 12.92;
+    }
+    return 1.055 * pow(v, 1.0 / 2.4) - 0.055;
+}
+
+fn cbrt(x: f32) -> f32 {
+    if (x <= 0.0) {
+        return 0.0;
+    }
+    return pow(x, 1.0 / 3.0);
+}
+
+// What a deficiency shows for an 8-bit color, rounded back to 8 bits. 0 is
+// typical vision, then protan, deutan and tritan.
+fn simulate(rgb: vec3<f32>, deficiency: u32) -> vec3<f32> {
+    if (deficiency == 0u) {
+        return rgb;
+    }
+    var m: mat3x3<f32>;
+    if (deficiency == 1u) {
+        m = mat3x3<f32>(
+            0.152286, 0.114503, -0.003882,
+            1.052583, 0.786281, -0.048116,
+            -0.204868, 0.099216, 1.051998,
+        );
+    } else if (deficiency == 2u) {
+        m = mat3x3<f32>(
+            0.367322, 0.280085, -0.011820,
+            0.860646, 0.672501, 0.042940,
+            -0.227968, 0.047413, 0.968881,
+        );
+    } else {
+        m = mat3x3<f32>(
+            1.255528, -0.078411, 0.004733,
+            -0.076749, 0.930809, 0.691367,
+            -0.178779, 0.147602, 0.303900,
+        );
+    }
+    let linear = vec3<f32>(linearize(rgb.r), linearize(rgb.g), linearize(rgb.b));
+    let shown = clamp(m * linear, vec3<f32>(0.0), vec3<f32>(1.0));
+    let srgb = vec3<f32>(delinearize(shown.r), delinearize(shown.g), delinearize(shown.b));
+    return round(srgb * 255.0) / 255.0;
+}
+
+fn srgb_to_oklab(rgb: vec3<f32>) -> vec3<f32> {
+    let r = linearize(rgb.r);
+    let g = linearize(rgb.g);
+    let b = linearize(rgb.b);
+    let l_ = cbrt(0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b);
+    let m_ = cbrt(0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b);
+    let s_ = cbrt(0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b);
+    return vec3<f32>(
+        0.2104542553 * l_ + 0.7936177850 * m_ - 0.0040720468 * s_,
+        1.9779984951 * l_ - 2.4285922050 * m_ + 0.4505937099 * s_,
+        0.0259040371 * l_ + 0.7827717662 * m_ - 0.8086757660 * s_,
+    );
+}
+
+// Whether the first `count` colors stay at least ΔE `min` apart as
+// `deficiency` shows them.
+fn apart(count: u32, deficiency: u32, min: f32) -> bool {
+    for (var i = 0u; i < count; i = i + 1u) {
+        seen[i] = srgb_to_oklab(simulate(colors[i], deficiency));
+    }
+    for (var i = 0u; i < count; i = i + 1u) {
+        for (var j = i + 1u; j < count; j = j + 1u) {
+            if (distance(seen[i], seen[j]) * 100.0 < min) {
+                return false;
+            }
+        }
+    }
+    return true;
+}
+
+@compute @workgroup_size(64)
+fn sweep(@builtin(global_invocation_id) id: vec3<u32>) {
+    let idx = id.x;
+    if (idx >= CUBE_LEN) {
+        return;
+    }
+
+    let o = idx % 360u;
+    let ls = idx / 360u;
+    let lightness = f32(ls / 101u) / 100.0;
+    let saturation = f32(ls % 101u) / 100.0;
+
+    let surface = Surface(
+        params.bg_lum,
+        params.bg_luma,
+        params.min_wcag,
+        params.min_apca,
+        params.min_apca_reverse,
+    );
+    let count = min(u32(params.count), MAX_COUNT);
+    for (var n = 0u; n < count; n = n + 1u) {
+        let hue_degrees = (f32(o) + f32(n) * 360.0 / params.count) % 360.0;
+        colors[n] = okhsl_to_srgb(hue_degrees / 360.0, saturation, lightness);
+        if (!clears(colors[n], surface)) {
+            return;
+        }
+    }
+
+    if (params.separation > 0.0 && !apart(count, 0u, params.separation)) {
+        return;
+    }
+    if (params.cvd > 0.0) {
+        for (var deficiency = 1u; deficiency <= 3u; deficiency = deficiency + 1u) {
+            if (!apart(count, deficiency, params.cvd)) {
+                return;
+            }
+        }
+    }
+
+    atomicOr(&valid[idx / 32u], 1u << (idx % 32u));
+}
+
+@compute @workgroup_size(64)
+fn score_candidates(@builtin(global_invocation_id) id: vec3<u32>) {
+    let idx = id.x;
+    if (idx >= CANDIDATES) {
+        return;
+    }
+
+    let lightness = f32(idx / 11u) / 100.0;
+    let saturation = score.s * (1.0 - f32(idx % 11u) / 10.0);
+    let rgb = okhsl_to_srgb(score.h, saturation, lightness);
+
+    var result = 1u;
+    for (var i = 0u; i < score.surfaces; i = i + 1u) {
+        if (!clears(rgb, surfaces[i])) {
+            result = 0u;
+        }
+    }
+    passing[idx] = result;
+}
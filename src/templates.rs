@@ -0,0 +1,122 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::apply::Scheme;
+use crate::config;
+use crate::log;
+use crate::write_file;
+
+// Renders every `*.tmpl` in `~/.config/colorize/templates` into
+// `~/.cache/colorize`, dropping the extension, so apps colorize has no
+// exporter for can still be themed. The syntax is pywal's, so its templates
+// work as is: `{color4}` is `#rrggbb`, `{color4.strip}` leaves out the `#`,
+// `{color4.rgb}` is `r,g,b` and `{color4.r}` one channel; `{{` and `}}` are
+// literal braces. Besides pywal's background, foreground, cursor and
+// color0..color15, the generated colors are `palette0` onwards.
+pub fn render(scheme: &Scheme) {
+    let Some(templates) = config::dir().map(|dir| dir.join("templates")) else { return };
+    let Some(out_dir) = out_dir() else {
+        eprintln!("Neither XDG_CACHE_HOME nor HOME is set; not rendering templates");
+        return;
+    };
+    render_dir(&templates, &out_dir, scheme);
+}
+
+// Renders the `*.tmpl` files of `dir` into `out_dir`; nothing happens when
+// there are none.
+pub fn render_dir(dir: &Path, out_dir: &Path, scheme: &Scheme) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut templates: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tmpl"))
+        .collect();
+    if templates.is_empty() {
+        return;
+    }
+    templates.sort();
+    let variables = variables(scheme);
+    let mut rendered = 0;
+    for template in &templates {
+        let filled = fs::read_to_string(template)
+            .map_err(|err| err.to_string())
+            .and_then(|text| fill(&text, &variables));
+        match filled {
+            Ok(out) => {
+                let path = out_dir.join(template.file_stem().unwrap_or_default());
+                write_file(&path, out);
+                log::debug(format!("Wrote {}", path.display()));
+                rendered += 1;
+            }
+            Err(err) => log::warn(format!("Skipping template {}: {err}", template.display())),
+        }
+    }
+    log::info(format!(
+        "Rendered {rendered} of {} templates to {}",
+        templates.len(),
+        out_dir.display()
+    ));
+}
+
+fn out_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".cache")))?;
+    Some(base.join("colorize"))
+}
+
+fn variables(scheme: &Scheme) -> Vec<(String, [u8; 3])> {
+    let special = [
+        ("background", scheme.background),
+        ("foreground", scheme.foreground),
+        ("cursor", scheme.cursor),
+        ("surface", scheme.surface),
+        ("surface_variant", scheme.surface_variant),
+    ];
+    special
+        .iter()
+        .map(|(name, rgb)| ((*name).to_string(), *rgb))
+        .chain(scheme.ansi.iter().enumerate().map(|(i, rgb)| (format!("color{i}"), *rgb)))
+        .chain(scheme.palette.iter().enumerate().map(|(i, rgb)| (format!("palette{i}"), *rgb)))
+        .collect()
+}
+
+fn fill(text: &str, variables: &[(String, [u8; 3])]) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let brace = &rest[start..=start];
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix(brace) {
+            out.push_str(brace);
+            rest = after;
+            continue;
+        }
+        if brace == "}" {
+            return Err(String::from("unmatched `}`, write `}}` for a literal brace"));
+        }
+        let end = rest.find('}').ok_or("unclosed `{`, write `{{` for a literal brace")?;
+        out.push_str(&value(&rest[..end], variables)?);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn value(field: &str, variables: &[(String, [u8; 3])]) -> Result<String, String> {
+    let (name, filter) = field.split_once('.').unwrap_or((field, ""));
+    let &(_, [r, g, b]) = variables
+        .iter()
+        .find(|(known, _)| known == name)
+        .ok_or_else(|| format!("unknown variable `{name}`"))?;
+    Ok(match filter {
+        "" => format!("#{r:02x}{g:02x}{b:02x}"),
+        "strip" => format!("{r:02x}{g:02x}{b:02x}"),
+        "rgb" => format!("{r},{g},{b}"),
+        "r" => r.to_string(),
+        "g" => g.to_string(),
+        "b" => b.to_string(),
+        _ => return Err(format!("unknown filter `{filter}` on `{name}`")),
+    })
+}
@@ -0,0 +1,205 @@
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{IsTerminal, Read, Write};
+use std::process::Command;
+use std::sync::OnceLock;
+
+pub struct Capabilities {
+    pub truecolor: bool,
+    pub colors: u32,
+    pub background: Option<[u8; 3]>,
+    pub osc4: bool,
+    pub kitty_graphics: bool,
+    pub sixel: bool,
+}
+
+// Detected once per run. Environment and terminfo give the color depth; when
+// attached to a terminal it is also asked for its background (OSC 11), the
+// value of palette slot 1 (OSC 4) and its device attributes, which list sixel.
+pub fn caps() -> &'static Capabilities {
+    static CAPS: OnceLock<Capabilities> = OnceLock::new();
+    CAPS.get_or_init(detect)
+}
+
+fn detect() -> Capabilities {
+    let term = env::var("TERM").unwrap_or_default();
+    let program = env::var("TERM_PROGRAM").unwrap_or_default();
+
+    // A Windows console takes 24-bit color once VT processing is on and no
+    // escape sequences at all before.
+    #[cfg(windows)]
+    let (truecolor, colors) =
+        if crate::console::enable_vt() { (true, 1 << 24) } else { (false, 0) };
+    #[cfg(not(windows))]
+    let truecolor = env::var("COLORTERM").is_ok_and(|v| v == "truecolor" || v == "24bit")
+        || term.contains("direct")
+        || term.contains("kitty");
+    #[cfg(not(windows))]
+    let colors = if truecolor { 1 << 24 } else { terminfo_colors(&term) };
+
+    let kitty_graphics = env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || program == "ghostty"
+        || program == "WezTerm";
+
+    let response = if std::io::stdout().is_terminal() {
+        probe("\x1b]11;?\x1b\\\x1b]4;1;?\x1b\\\x1b[c").unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    let background = osc_color(&response, "11;");
+    let osc4 = osc_color(&response, "4;1;").is_some();
+    let sixel = term.starts_with("foot") || term.starts_with("mlterm") || da1_has_sixel(&response);
+
+    Capabilities { truecolor, colors, background, osc4, kitty_graphics, sixel }
+}
+
+pub fn print_report() {
+    let caps = caps();
+    let yes_no = |v: bool| if v { "yes" } else { "no" };
+    let background = caps
+        .background
+        .map_or_else(|| String::from("unknown"), |[r, g, b]| format!("#{r:02X}{g:02X}{b:02X}"));
+
+    println!("Truecolor:        {}", yes_no(caps.truecolor));
+    println!("Colors:           {}", caps.colors);
+    println!("Background:       {background}");
+    println!("OSC 4 palette:    {}", yes_no(caps.osc4));
+    println!("Kitty graphics:   {}", yes_no(caps.kitty_graphics));
+    println!("Sixel:            {}", yes_no(caps.sixel));
+}
+
+#[cfg(not(windows))]
+fn terminfo_colors(term: &str) -> u32 {
+    let tput = Command::new("tput").arg("colors").stderr(std::process::Stdio::null()).output();
+    if let Some(colors) =
+        tput.ok().and_then(|o| String::from_utf8(o.stdout).ok()?.trim().parse().ok())
+    {
+        return colors;
+    }
+    if term.contains("256color") {
+        256
+    } else if term.is_empty() || term == "dumb" {
+        0
+    } else {
+        16
+    }
+}
+
+// Writes the queries to the controlling terminal and collects the replies. The
+// primary device attributes request goes last: every terminal answers it, so
+// its reply marks the end and unsupported queries cost no timeout.
+pub fn probe(queries: &str) -> Option<String> {
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let saved = stty(&["-g"])?;
+    stty(&["raw", "-echo", "min", "0", "time", "2"])?;
+
+    let mut response = Vec::new();
+    if tty.write_all(queries.as_bytes()).and_then(|()| tty.flush()).is_ok() {
+        let mut buf = [0; 256];
+        while let Ok(n @ 1..) = tty.read(&mut buf) {
+            response.extend_from_slice(&buf[..n]);
+            if da1_complete(&response) {
+                break;
+            }
+        }
+    }
+
+    stty(&[saved.trim()]);
+    Some(String::from_utf8_lossy(&response).into_owned())
+}
+
+// Columns of the controlling terminal or Windows console, falling back to
+// $COLUMNS and then 80.
+pub fn width() -> usize {
+    static WIDTH: OnceLock<usize> = OnceLock::new();
+    *WIDTH.get_or_init(|| {
+        stty(&["size"])
+            .and_then(|size| size.split_whitespace().nth(1)?.parse().ok())
+            .or_else(console_width)
+            .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+            .filter(|&columns| columns > 0)
+            .unwrap_or(80)
+    })
+}
+
+pub fn read_key() {
+    let Ok(mut tty) = File::open("/dev/tty") else { return };
+    let Some(saved) = stty(&["-g"]) else { return };
+    if stty(&["raw", "-echo", "min", "1", "time", "0"]).is_some() {
+        let _ = tty.read(&mut [0; 16]);
+    }
+    stty(&[saved.trim()]);
+}
+
+// Runs `f` with the terminal unbuffered and silent, reads returning after at
+// most a tenth of a second, so `f` can poll for keys between frames.
+pub fn with_keys<T>(f: impl FnOnce(&mut File) -> T) -> Option<T> {
+    let mut tty = File::open("/dev/tty").ok()?;
+    let saved = stty(&["-g"])?;
+    stty(&["-icanon", "-echo", "min", "0", "time", "1"])?;
+    let result = f(&mut tty);
+    stty(&[saved.trim()]);
+    Some(result)
+}
+
+#[cfg(windows)]
+fn console_width() -> Option<usize> {
+    crate::console::width()
+}
+
+#[cfg(not(windows))]
+const fn console_width() -> Option<usize> {
+    None
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output =
+        Command::new("stty").args(args).stdin(File::open("/dev/tty").ok()?).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn da1_complete(response: &[u8]) -> bool {
+    let Some(start) = response.windows(3).position(|w| w == b"\x1b[?") else { return false };
+    response[start..].contains(&b'c')
+}
+
+fn da1_has_sixel(response: &str) -> bool {
+    let Some(start) = response.find("\x1b[?") else { return false };
+    let Some(end) = response[start..].find('c') else { return false };
+    response[start + 3..start + end].split(';').any(|attr| attr == "4")
+}
+
+// Parses `ESC ] <prefix> rgb:RRRR/GGGG/BBBB` (1–4 hex digits per channel).
+pub fn osc_color(response: &str, prefix: &str) -> Option<[u8; 3]> {
+    let start = response.find(&format!("\x1b]{prefix}rgb:"))? + 2 + prefix.len() + 4;
+    let spec: String =
+        response[start..].chars().take_while(|c| c.is_ascii_hexdigit() || *c == '/').collect();
+    let mut channels = spec.split('/').map(|c| {
+        if c.is_empty() || c.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(c, 16).ok()?;
+        let max = (1 << (4 * c.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    Some([channels.next()??, channels.next()??, channels.next()??])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc_color_scales_every_channel_width() {
+        let reply = |spec: &str| format!("\x1b]11;rgb:{spec}\x1b\\");
+        assert_eq!(osc_color(&reply("f/8/0"), "11;"), Some([255, 136, 0]));
+        assert_eq!(osc_color(&reply("1e/1e/2e"), "11;"), Some([0x1e, 0x1e, 0x2e]));
+        assert_eq!(osc_color(&reply("1e1e/1e1e/2e2e"), "11;"), Some([0x1e, 0x1e, 0x2e]));
+        assert_eq!(osc_color(&reply("ffffffff/0/0"), "11;"), None);
+        assert_eq!(osc_color(&reply("1e1e/1e1e"), "11;"), None);
+        assert_eq!(osc_color(&reply("1e/1e/2e"), "10;"), None);
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use crate::apply::Scheme;
+use crate::config::{self, Config};
+use crate::context::Context;
+use crate::grade::Grade;
+use crate::palette::Palette;
+use crate::{export, image, log, report, templates, write_file};
+
+// The palette.toml keys and the flags they stand for.
+const KEYS: [(&str, &str); 6] = [
+    ("background", "-b"),
+    ("lightness", "-l"),
+    ("saturation", "-s"),
+    ("offset", "-o"),
+    ("count", "-c"),
+    ("bundle", "--bundle"),
+];
+
+const EXAMPLE_TEMPLATE: &str = "\
+# Rendered into exports/templates/ by `colorize build`, without the .tmpl
+# extension. See the README for the variables; {{ and }} are literal braces.
+background='{background}'
+foreground='{foreground}'
+accent='{palette0}'
+";
+
+// `colorize init <name>`: a theme project for the palette just generated.
+// palette.toml holds the parameters and templates/ the user's own templates;
+// exports/ and preview/ are regenerated by `colorize build` and can be left
+// out of version control.
+pub fn init(
+    dir: &Path,
+    backgrounds: &[String],
+    lightness: f32,
+    saturation: f32,
+    offset: f32,
+    count: i32,
+) {
+    if dir.join("palette.toml").exists() {
+        eprintln!("{} already has a palette.toml; run colorize build there instead", dir.display());
+        std::process::exit(1);
+    }
+    let palette = format!(
+        "# Generation parameters of this theme. `colorize build` regenerates exports/\n\
+         # and preview/ from them; flags given to build override them.\n\
+         background = \"{}\"\nlightness = {lightness}\nsaturation = {saturation}\n\
+         offset = {offset}\ncount = {count}\nbundle = \"all\"\n",
+        backgrounds.join(",")
+    );
+    write_file(&dir.join("palette.toml"), palette);
+    write_file(&dir.join("templates").join("colors.sh.tmpl"), EXAMPLE_TEMPLATE);
+    write_file(&dir.join(".gitignore"), "/exports/\n/preview/\n");
+    log::info(format!("Created theme {}", dir.display()));
+}
+
+// The flags palette.toml in `dir` stands for, to go before the command line's.
+pub fn args(dir: &Path) -> Vec<String> {
+    let Some(palette) = Config::read(&dir.join("palette.toml")) else {
+        eprintln!("No palette.toml in {}; create a theme with colorize init <name>", dir.display());
+        std::process::exit(1);
+    };
+    flags(&palette)
+}
+
+// The flags of a preset in `~/.config/colorize/presets/<name>.toml`, which
+// has the same keys as palette.toml.
+pub fn preset(name: &str) -> Vec<String> {
+    let path = config::dir().map(|dir| dir.join("presets").join(format!("{name}.toml")));
+    let Some(preset) = path.as_deref().and_then(Config::read) else {
+        eprintln!("No preset {name}; import one with colorize gallery import <n>");
+        std::process::exit(1);
+    };
+    flags(&preset)
+}
+
+fn flags(palette: &Config) -> Vec<String> {
+    let mut args = Vec::new();
+    for (key, flag) in KEYS {
+        if let Some(value) = palette.get(key) {
+            args.extend([flag.to_string(), value]);
+        }
+    }
+    args
+}
+
+// `colorize build`: every bundle group, the theme's templates and a preview
+// page and image.
+pub fn build(
+    dir: &Path,
+    groups: &[&str],
+    palette: &Palette,
+    options: export::Options,
+    surfaces: &[&Context],
+    grade: &Grade,
+) {
+    let exports = dir.join("exports");
+    export::bundle(groups, &exports, palette, options);
+    templates::render_dir(
+        &dir.join("templates"),
+        &exports.join("templates"),
+        &Scheme::new(palette),
+    );
+
+    let preview = dir.join("preview");
+    report::write(&preview.join("palette"), "html", palette, surfaces, grade, None);
+    let image = image::palette_image(palette);
+    write_file(&preview.join("palette.ppm"), image.ppm());
+    log::debug(format!("Wrote {}", preview.join("palette.ppm").display()));
+}
@@ -0,0 +1,210 @@
+use std::env;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::apply::Scheme;
+use crate::log;
+use crate::palette::Palette;
+use crate::write_file;
+
+// pywal's cache file set, so whatever already reads `~/.cache/wal` (spicetify,
+// oomox scripts, betterlockscreen, sourced shell variables) picks up colorize
+// palettes unchanged. There is no wallpaper, which pywal writes as "None".
+pub fn write(palette: &Palette) {
+    let scheme = &Scheme::new(palette);
+    let Some(dir) = cache_dir() else {
+        eprintln!("Neither XDG_CACHE_HOME nor HOME is set; not writing the wal cache");
+        return;
+    };
+    let files = [
+        ("colors", plain(scheme)),
+        ("colors.json", json(scheme)),
+        ("colors.sh", shell(scheme)),
+        ("colors.Xresources", xresources(scheme)),
+        ("colors.css", css(scheme)),
+        ("colors.scss", variables(scheme, "$", ": ", ";")),
+        ("colors.yml", yaml(scheme)),
+        ("colors-kitty.conf", variables(scheme, "", " ", "")),
+        ("colors-sway", variables(scheme, "set $", " ", "")),
+        ("colors-oomox", oomox(scheme)),
+        ("sequences", scheme.sequences()),
+    ];
+    for (name, contents) in &files {
+        write_file(&dir.join(name), contents);
+        log::debug(format!("Wrote {}", dir.join(name).display()));
+    }
+    log::info(format!("Wrote {} pywal files to {}", files.len(), dir.display()));
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".cache")))?;
+    Some(base.join("wal"))
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// background, foreground and cursor followed by color0..color15.
+fn named(scheme: &Scheme) -> Vec<(String, String)> {
+    let special = [
+        ("background", scheme.background),
+        ("foreground", scheme.foreground),
+        ("cursor", scheme.cursor),
+    ];
+    special
+        .iter()
+        .map(|(name, rgb)| ((*name).to_string(), hex(*rgb)))
+        .chain(scheme.ansi.iter().enumerate().map(|(i, rgb)| (format!("color{i}"), hex(*rgb))))
+        .collect()
+}
+
+fn plain(scheme: &Scheme) -> String {
+    scheme.ansi.iter().map(|rgb| hex(*rgb) + "\n").collect()
+}
+
+fn variables(scheme: &Scheme, prefix: &str, separator: &str, suffix: &str) -> String {
+    let mut out = String::new();
+    for (name, value) in named(scheme) {
+        writeln!(out, "{prefix}{name}{separator}{value}{suffix}").unwrap();
+    }
+    out
+}
+
+fn json(scheme: &Scheme) -> String {
+    let colors: Vec<String> = named(scheme)[3..]
+        .iter()
+        .map(|(name, value)| format!("        \"{name}\": \"{value}\""))
+        .collect();
+    format!(
+        "{{\n    \"wallpaper\": \"None\",\n    \"alpha\": \"100\",\n    \"special\": {{\n        \
+         \"background\": \"{}\",\n        \"foreground\": \"{}\",\n        \"cursor\": \"{}\"\n    }},\n    \
+         \"colors\": {{\n{}\n    }}\n}}\n",
+        hex(scheme.background),
+        hex(scheme.foreground),
+        hex(scheme.cursor),
+        colors.join(",\n")
+    )
+}
+
+fn shell(scheme: &Scheme) -> String {
+    let mut out = String::from("# Shell variables\n# Generated by colorize\nwallpaper='None'\n\n");
+    for (name, value) in named(scheme) {
+        writeln!(out, "{name}='{value}'").unwrap();
+    }
+    out
+}
+
+fn xresources(scheme: &Scheme) -> String {
+    let mut out = String::new();
+    for (name, value) in named(scheme) {
+        let name = if name == "cursor" { "cursorColor" } else { &name };
+        writeln!(out, "*{name}: {value}\n*.{name}: {value}").unwrap();
+    }
+    out
+}
+
+fn css(scheme: &Scheme) -> String {
+    let mut out = String::from(":root {\n    --wallpaper: url(\"None\");\n");
+    for (name, value) in named(scheme) {
+        writeln!(out, "    --{name}: {value};").unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn yaml(scheme: &Scheme) -> String {
+    let named = named(scheme);
+    let mut out = String::from("wallpaper: \"None\"\n\nspecial:\n");
+    for (name, value) in &named[..3] {
+        writeln!(out, "  {name}: \"{value}\"").unwrap();
+    }
+    out.push_str("\ncolors:\n");
+    for (name, value) in &named[3..] {
+        writeln!(out, "  {name}: \"{value}\"").unwrap();
+    }
+    out
+}
+
+// The keys oomox reads from pywal's `colors-oomox` template, without the `#`.
+fn oomox(scheme: &Scheme) -> String {
+    let bare = |rgb: [u8; 3]| hex(rgb)[1..].to_string();
+    let (bg, fg) = (bare(scheme.background), bare(scheme.foreground));
+    let ansi = scheme.ansi.map(bare);
+    format!(
+        "NAME=wal\nBG={bg}\nFG={fg}\nMENU_BG={bg}\nMENU_FG={fg}\nSEL_BG={}\nSEL_FG={bg}\n\
+         TXT_BG={bg}\nTXT_FG={fg}\nBTN_BG={}\nBTN_FG={fg}\nHDR_BTN_BG={}\nHDR_BTN_FG={fg}\n\
+         GTK3_GENERATE_DARK=True\nROUNDNESS=0\nSPACING=3\nGRADIENT=0.0\n",
+        ansi[4], ansi[1], ansi[2]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Options;
+    use crate::json::Json;
+
+    // Every slot a different gray, so a shifted slot shows.
+    fn scheme() -> Scheme {
+        Scheme {
+            background: [0x10, 0x20, 0x30],
+            foreground: [0xe0, 0xd0, 0xc0],
+            cursor: [0xff, 0x00, 0x80],
+            ansi: std::array::from_fn(|i| [i as u8 * 0x11; 3]),
+            surface: [0x20, 0x30, 0x40],
+            surface_variant: [0x30, 0x40, 0x50],
+            palette: Vec::new(),
+            options: Options::default(),
+        }
+    }
+
+    #[test]
+    fn json_has_pywals_keys_and_slots() {
+        let scheme = scheme();
+        let json = Json::parse(&json(&scheme)).unwrap();
+        let string = |value: &str| Some(Json::String(value.to_string()));
+        assert_eq!(json.get("wallpaper").cloned(), string("None"));
+
+        let special = json.get("special").unwrap();
+        assert_eq!(special.get("background").cloned(), string("#102030"));
+        assert_eq!(special.get("foreground").cloned(), string("#e0d0c0"));
+        assert_eq!(special.get("cursor").cloned(), string("#ff0080"));
+
+        let Some(Json::Object(colors)) = json.get("colors") else { panic!("no colors") };
+        let expected: Vec<_> = (0..16)
+            .map(|i| (format!("color{i}"), Json::String(hex([i as u8 * 0x11; 3]))))
+            .collect();
+        assert_eq!(colors, &expected);
+    }
+
+    #[test]
+    fn shell_and_sequences_keep_the_slot_order() {
+        let scheme = scheme();
+        let shell = shell(&scheme);
+        let lines: Vec<_> = shell.lines().skip_while(|line| !line.is_empty()).skip(1).collect();
+        assert_eq!(
+            &lines[..4],
+            [
+                "background='#102030'",
+                "foreground='#e0d0c0'",
+                "cursor='#ff0080'",
+                "color0='#000000'"
+            ]
+        );
+        assert_eq!(lines.len(), 3 + 16);
+        assert_eq!(lines[3 + 15], "color15='#ffffff'");
+
+        let sequences = scheme.sequences();
+        let codes: Vec<_> =
+            sequences.split("\x1b]").skip(1).map(|seq| seq.split_once(";rgb:").unwrap()).collect();
+        let slots: Vec<String> = (0..16).map(|i| format!("4;{i}")).collect();
+        let expected: Vec<_> = slots.iter().map(String::as_str).chain(["10", "11", "12"]).collect();
+        assert_eq!(codes.iter().map(|(code, _)| *code).collect::<Vec<_>>(), expected);
+        assert_eq!(codes[5].1, "55/55/55\x1b\\");
+        assert_eq!(codes[17].1, "10/20/30\x1b\\");
+        assert_eq!(plain(&scheme).lines().count(), 16);
+    }
+}
@@ -0,0 +1,99 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::log;
+
+type Source = fn() -> Option<String>;
+
+const SOURCES: [(&str, Source); 4] =
+    [("swww", swww), ("hyprpaper", hyprpaper), ("feh", feh), ("GNOME", gnome)];
+
+// The current wallpaper, asking swww, hyprpaper, feh's `~/.fehbg` and GNOME in
+// that order. The first answer naming a file that exists wins.
+pub fn detect() -> Option<PathBuf> {
+    SOURCES.iter().find_map(|(name, source)| {
+        let path = PathBuf::from(source()?);
+        log::debug(format!("{name} reports {}", path.display()));
+        path.is_file().then_some(path)
+    })
+}
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// `eDP-1: 1920x1080, scale: 1, currently displaying: image: /path/wall.png`
+fn swww() -> Option<String> {
+    let query = output("swww", &["query"])?;
+    query.lines().find_map(|line| Some(line.split_once("image: ")?.1.trim().to_string()))
+}
+
+// `eDP-1 = /path/wall.png` from the running daemon, else the `wallpaper =
+// monitor,/path/wall.png` lines of its config.
+fn hyprpaper() -> Option<String> {
+    if let Some(active) = output("hyprctl", &["hyprpaper", "listactive"])
+        && let Some(path) = active.lines().find_map(|line| Some(line.split_once(" = ")?.1.trim()))
+    {
+        return Some(path.to_string());
+    }
+    let config = config_home()?.join("hypr").join("hyprpaper.conf");
+    let config = fs::read_to_string(config).ok()?;
+    config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        let path = value.split_once(',')?.1.trim();
+        (key.trim() == "wallpaper").then(|| expand_home(path))
+    })
+}
+
+// The last quoted argument of the `feh --bg-fill '/path/wall.png'` line.
+fn feh() -> Option<String> {
+    let fehbg = fs::read_to_string(Path::new(&env::var_os("HOME")?).join(".fehbg")).ok()?;
+    let line = fehbg.lines().rfind(|line| line.trim_start().starts_with("feh"))?;
+    let quoted = line.rsplit('\'').nth(1)?;
+    Some(quoted.to_string())
+}
+
+// `'file:///path/wall%20dark.png'`, the dark variant when the desktop prefers it.
+fn gnome() -> Option<String> {
+    let scheme = output("gsettings", &["get", "org.gnome.desktop.interface", "color-scheme"]);
+    let key =
+        if scheme.is_some_and(|s| s.contains("dark")) { "picture-uri-dark" } else { "picture-uri" };
+    let uri = output("gsettings", &["get", "org.gnome.desktop.background", key])?;
+    let path = uri.trim().trim_matches('\'').strip_prefix("file://")?;
+    Some(percent_decode(path))
+}
+
+fn config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| std::str::from_utf8(tail.get(..2)?).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(decoded) = decoded {
+            bytes.push(decoded);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}